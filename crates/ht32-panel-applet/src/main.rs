@@ -72,6 +72,18 @@ async fn main() -> Result<()> {
                             let mut s = cmd_state.lock().unwrap();
                             s.network_interface = iface;
                         }
+                        if let Ok(ms) = c.get_refresh_interval().await {
+                            let mut s = cmd_state.lock().unwrap();
+                            s.refresh_interval = ms;
+                        }
+                        if let Ok(percent) = c.get_brightness().await {
+                            let mut s = cmd_state.lock().unwrap();
+                            s.brightness = percent;
+                        }
+                        if let Ok(off) = c.is_display_off().await {
+                            let mut s = cmd_state.lock().unwrap();
+                            s.display_off = off;
+                        }
                         if let Ok(interfaces) = c.list_network_interfaces().await {
                             let mut s = cmd_state.lock().unwrap();
                             s.network_interfaces = interfaces;
@@ -157,6 +169,51 @@ async fn main() -> Result<()> {
                                 }
                             }
                         }
+                        Some(TrayCommand::SetRefreshInterval(ms)) => {
+                            if let Some(ref c) = client {
+                                match c.set_refresh_interval(ms).await {
+                                    Ok(()) => {
+                                        let mut s = cmd_state.lock().unwrap();
+                                        s.refresh_interval = ms;
+                                        debug!("Refresh interval set to {} ms", ms);
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to set refresh interval: {}", e);
+                                        client = None; // Mark for reconnection
+                                    }
+                                }
+                            }
+                        }
+                        Some(TrayCommand::SetBrightness(percent)) => {
+                            if let Some(ref c) = client {
+                                match c.set_brightness(percent).await {
+                                    Ok(()) => {
+                                        let mut s = cmd_state.lock().unwrap();
+                                        s.brightness = percent;
+                                        debug!("Brightness set to {}%", percent);
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to set brightness: {}", e);
+                                        client = None; // Mark for reconnection
+                                    }
+                                }
+                            }
+                        }
+                        Some(TrayCommand::SetDisplayOff(off)) => {
+                            if let Some(ref c) = client {
+                                match c.set_display_off(off).await {
+                                    Ok(()) => {
+                                        let mut s = cmd_state.lock().unwrap();
+                                        s.display_off = off;
+                                        debug!("Display switched {}", if off { "off" } else { "on" });
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to switch display: {}", e);
+                                        client = None; // Mark for reconnection
+                                    }
+                                }
+                            }
+                        }
                         Some(TrayCommand::QuitDaemon) => {
                             if let Some(ref c) = client {
                                 match c.quit().await {
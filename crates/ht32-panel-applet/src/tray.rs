@@ -22,6 +22,24 @@ const ORIENTATIONS: &[(&str, &str)] = &[
     ("Portrait (Upside Down)", "portrait-upside-down"),
 ];
 
+/// Refresh interval options: (display name, milliseconds)
+const REFRESH_INTERVALS: &[(&str, u32)] = &[
+    ("0.5 s", 500),
+    ("1 s", 1000),
+    ("2.5 s", 2500),
+    ("5 s", 5000),
+    ("10 s", 10000),
+];
+
+/// Brightness options: (display name, percent)
+const BRIGHTNESS_LEVELS: &[(&str, u8)] = &[
+    ("100%", 100),
+    ("75%", 75),
+    ("50%", 50),
+    ("25%", 25),
+    ("10%", 10),
+];
+
 /// Face options: (display name, face string)
 const FACES: &[(&str, &str)] = &[("ASCII", "ascii"), ("Professional", "professional")];
 
@@ -32,6 +50,9 @@ pub enum TrayCommand {
     SetOrientation(String),
     SetFace(String),
     SetNetworkInterface(String),
+    SetRefreshInterval(u32),
+    SetBrightness(u8),
+    SetDisplayOff(bool),
     QuitDaemon,
 }
 
@@ -46,6 +67,9 @@ pub struct TrayState {
     pub face: String,
     pub network_interface: String,
     pub network_interfaces: Vec<String>,
+    pub refresh_interval: u32,
+    pub brightness: u8,
+    pub display_off: bool,
 }
 
 impl Default for TrayState {
@@ -60,6 +84,9 @@ impl Default for TrayState {
             face: "professional".to_string(),
             network_interface: String::new(),
             network_interfaces: Vec::new(),
+            refresh_interval: 2500,
+            brightness: 100,
+            display_off: false,
         }
     }
 }
@@ -151,6 +178,47 @@ impl HT32PanelTray {
         }
     }
 
+    fn set_refresh_interval(&mut self, index: usize) {
+        if let Some((_, ms)) = REFRESH_INTERVALS.get(index) {
+            if let Err(e) = self
+                .command_tx
+                .try_send(TrayCommand::SetRefreshInterval(*ms))
+            {
+                debug!("Failed to send refresh interval command: {}", e);
+            }
+            // Update local state immediately for UI feedback
+            if let Ok(mut s) = self.state.lock() {
+                s.refresh_interval = *ms;
+            }
+        }
+    }
+
+    fn set_brightness(&mut self, index: usize) {
+        if let Some((_, percent)) = BRIGHTNESS_LEVELS.get(index) {
+            if let Err(e) = self
+                .command_tx
+                .try_send(TrayCommand::SetBrightness(*percent))
+            {
+                debug!("Failed to send brightness command: {}", e);
+            }
+            // Update local state immediately for UI feedback
+            if let Ok(mut s) = self.state.lock() {
+                s.brightness = *percent;
+            }
+        }
+    }
+
+    fn toggle_display_off(&mut self) {
+        let off = !self.state.lock().unwrap().display_off;
+        if let Err(e) = self.command_tx.try_send(TrayCommand::SetDisplayOff(off)) {
+            debug!("Failed to send display command: {}", e);
+        }
+        // Update local state immediately for UI feedback
+        if let Ok(mut s) = self.state.lock() {
+            s.display_off = off;
+        }
+    }
+
     fn quit_daemon(&self) {
         if let Err(e) = self.command_tx.try_send(TrayCommand::QuitDaemon) {
             debug!("Failed to send quit command: {}", e);
@@ -189,6 +257,9 @@ impl Tray for HT32PanelTray {
         let current_face = state.face.clone();
         let current_network = state.network_interface.clone();
         let network_interfaces = state.network_interfaces.clone();
+        let current_refresh = state.refresh_interval;
+        let current_brightness = state.brightness;
+        let display_off = state.display_off;
         let web_enabled = state.web_enabled;
         drop(state);
 
@@ -210,6 +281,18 @@ impl Tray for HT32PanelTray {
             .position(|(_, f)| *f == current_face)
             .unwrap_or(0);
 
+        // Find current refresh interval index (none selected for custom values)
+        let refresh_selected = REFRESH_INTERVALS
+            .iter()
+            .position(|(_, ms)| *ms == current_refresh)
+            .unwrap_or(usize::MAX);
+
+        // Find current brightness index (none selected for custom values)
+        let brightness_selected = BRIGHTNESS_LEVELS
+            .iter()
+            .position(|(_, percent)| *percent == current_brightness)
+            .unwrap_or(usize::MAX);
+
         // Find current network interface index (0 = auto)
         let network_selected = if current_network.is_empty() {
             0 // Auto
@@ -248,6 +331,24 @@ impl Tray for HT32PanelTray {
             })
             .collect();
 
+        // Create refresh interval radio items
+        let refresh_options: Vec<RadioItem> = REFRESH_INTERVALS
+            .iter()
+            .map(|(name, _)| RadioItem {
+                label: name.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        // Create brightness radio items
+        let brightness_options: Vec<RadioItem> = BRIGHTNESS_LEVELS
+            .iter()
+            .map(|(name, _)| RadioItem {
+                label: name.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
         // Create network interface radio items (Auto + available interfaces)
         let mut network_options: Vec<RadioItem> = vec![RadioItem {
             label: "Auto".to_string(),
@@ -287,6 +388,41 @@ impl Tray for HT32PanelTray {
                 ..Default::default()
             }
             .into(),
+            SubMenu {
+                label: "Refresh Interval".to_string(),
+                submenu: vec![RadioGroup {
+                    selected: refresh_selected,
+                    select: Box::new(|tray: &mut Self, index| {
+                        tray.set_refresh_interval(index);
+                    }),
+                    options: refresh_options,
+                }
+                .into()],
+                ..Default::default()
+            }
+            .into(),
+            SubMenu {
+                label: "Brightness".to_string(),
+                submenu: vec![RadioGroup {
+                    selected: brightness_selected,
+                    select: Box::new(|tray: &mut Self, index| {
+                        tray.set_brightness(index);
+                    }),
+                    options: brightness_options,
+                }
+                .into()],
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Display Off".to_string(),
+                checked: display_off,
+                activate: Box::new(|tray: &mut Self| {
+                    tray.toggle_display_off();
+                }),
+                ..Default::default()
+            }
+            .into(),
             SubMenu {
                 label: "Network Interface".to_string(),
                 submenu: vec![RadioGroup {
@@ -2,9 +2,12 @@
 //!
 //! CLI for controlling the HT32 Panel daemon via D-Bus.
 
+mod viewer;
+
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use ht32_panel_client::{BusType, DaemonClient};
+use std::path::PathBuf;
 use tracing_subscriber::EnvFilter;
 
 #[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
@@ -41,6 +44,10 @@ struct Cli {
     #[arg(long, default_value = "auto", value_enum)]
     bus: CliBusType,
 
+    /// Panel to control (default: the daemon's first panel)
+    #[arg(short, long, global = true)]
+    panel: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -67,6 +74,44 @@ enum Commands {
         #[command(subcommand)]
         action: ComplicationCommands,
     },
+    /// Face carousel that rotates through a playlist
+    Carousel {
+        #[command(subcommand)]
+        action: CarouselCommands,
+    },
+    /// Show the time-of-day schedule and the active rule
+    Schedule,
+    /// Draw a notification over the face, or show the notification history
+    Notify {
+        /// Message text
+        #[arg(required_unless_present = "history")]
+        text: Option<String>,
+
+        /// Severity: info, warning, critical
+        #[arg(short, long, default_value = "info")]
+        severity: String,
+
+        /// Seconds to show the notification (1-3600)
+        #[arg(short = 't', long, default_value = "5")]
+        seconds: u32,
+
+        /// Show the notification history instead
+        #[arg(long, conflicts_with = "text")]
+        history: bool,
+    },
+    /// Set a custom value shown by faces, or list the custom values
+    Custom {
+        /// Key of the value; faces show it as custom:<key>
+        key: Option<String>,
+
+        /// New value
+        #[arg(requires = "key", allow_negative_numbers = true)]
+        value: Option<f64>,
+
+        /// Unit shown after the value
+        #[arg(short, long, default_value = "")]
+        unit: String,
+    },
     /// Save a screenshot of the display
     Screenshot {
         /// Output file path (default: screenshot.png)
@@ -78,6 +123,23 @@ enum Commands {
         #[command(subcommand)]
         action: DaemonCommands,
     },
+    /// Decode a virtual LCD stream (devices.lcd = "virtual:<path>") into PNGs
+    View {
+        /// Path of the virtual LCD file, FIFO or socket
+        path: PathBuf,
+
+        /// Output PNG path, overwritten after every frame
+        #[arg(short, long, default_value = "panel.png")]
+        output: PathBuf,
+
+        /// Write numbered PNGs (panel-00001.png, ...) instead of overwriting
+        #[arg(long)]
+        sequence: bool,
+
+        /// Create a Unix socket at PATH and wait for the daemon to connect
+        #[arg(long)]
+        listen: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -100,6 +162,20 @@ enum LcdCommands {
     },
     /// List available faces
     ListFaces,
+    /// Set or show the display refresh interval
+    RefreshInterval {
+        /// Interval in milliseconds, 500-10000 (omit to show current)
+        ms: Option<u32>,
+    },
+    /// Set or show the display brightness
+    Brightness {
+        /// Brightness in percent, 0-100 (omit to show current)
+        percent: Option<u8>,
+    },
+    /// Switch the display off (black frames)
+    Off,
+    /// Switch the display back on
+    On,
     /// Show device information
     Info,
 }
@@ -129,6 +205,8 @@ enum LedCommands {
 enum DaemonCommands {
     /// Check if daemon is running
     Status,
+    /// List the panels driven by the daemon
+    Panels,
     /// Request daemon shutdown
     Quit,
 }
@@ -139,13 +217,37 @@ enum ThemeCommands {
     Show,
     /// Set theme by name
     Set {
-        /// Theme name (default, hacker, solarized-light, solarized-dark, nord, tokyonight)
+        /// Theme name (see `theme list`; built-ins: ember, hacker, nord, solarized-dark,
+        /// solarized-light, tokyonight)
         name: String,
     },
     /// List available themes
     List,
 }
 
+#[derive(Subcommand)]
+enum CarouselCommands {
+    /// Show the playlist and whether the carousel is running
+    Show,
+    /// Set the playlist
+    Set {
+        /// Entries as FACE[@THEME][:SECONDS], e.g. professional:30 arcs@nord:15 clock
+        /// (default: 30 seconds)
+        #[arg(required = true)]
+        entries: Vec<String>,
+    },
+    /// Remove all faces from the playlist
+    Clear,
+    /// Start rotating through the playlist
+    Start,
+    /// Stop the carousel on the current face
+    Stop,
+    /// Show the next face of the playlist
+    Next,
+    /// Show the previous face of the playlist
+    Previous,
+}
+
 #[derive(Subcommand)]
 enum ComplicationCommands {
     /// List available complications for the current face with their options
@@ -192,8 +294,24 @@ async fn main() -> Result<()> {
     };
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
+    // The viewer reads the virtual LCD directly and does not need the daemon
+    if let Commands::View {
+        path,
+        output,
+        sequence,
+        listen,
+    } = cli.command
+    {
+        return viewer::run(&viewer::ViewerOptions {
+            path,
+            output,
+            sequence,
+            listen,
+        });
+    }
+
     // Connect to daemon
-    let client = DaemonClient::connect_with_bus(cli.bus.into())
+    let client = DaemonClient::connect_to_panel(cli.bus.into(), cli.panel.as_deref())
         .await
         .context("Failed to connect to daemon. Is ht32paneld running?")?;
 
@@ -202,8 +320,18 @@ async fn main() -> Result<()> {
         Commands::Led { action } => handle_led(action, &client).await,
         Commands::Theme { action } => handle_theme(action, &client).await,
         Commands::Complication { action } => handle_complication(action, &client).await,
+        Commands::Carousel { action } => handle_carousel(action, &client).await,
+        Commands::Schedule => handle_schedule(&client).await,
+        Commands::Notify {
+            text,
+            severity,
+            seconds,
+            history,
+        } => handle_notify(text, &severity, seconds, history, &client).await,
+        Commands::Custom { key, value, unit } => handle_custom(key, value, &unit, &client).await,
         Commands::Screenshot { output } => handle_screenshot(&output, &client).await,
         Commands::Daemon { action } => handle_daemon(action, &client).await,
+        Commands::View { .. } => unreachable!("handled before connecting"),
     }
 }
 
@@ -233,14 +361,51 @@ async fn handle_lcd(action: LcdCommands, client: &DaemonClient) -> Result<()> {
                 println!("  {}", face);
             }
         }
+        LcdCommands::RefreshInterval { ms } => {
+            if let Some(ms) = ms {
+                client.set_refresh_interval(ms).await?;
+                println!("Refresh interval set to: {} ms", ms);
+            } else {
+                let current = client.get_refresh_interval().await?;
+                println!("Current refresh interval: {} ms", current);
+            }
+        }
+        LcdCommands::Brightness { percent } => {
+            if let Some(percent) = percent {
+                client.set_brightness(percent).await?;
+                println!("Brightness set to: {}%", percent);
+            } else {
+                let current = client.get_brightness().await?;
+                println!("Current brightness: {}%", current);
+            }
+        }
+        LcdCommands::Off => {
+            client.set_display_off(true).await?;
+            println!("Display switched off");
+        }
+        LcdCommands::On => {
+            client.set_display_off(false).await?;
+            println!("Display switched on");
+        }
         LcdCommands::Info => {
             let connected = client.is_connected().await?;
             let orientation = client.get_orientation().await?;
             let face = client.get_face().await?;
+            let refresh_interval = client.get_refresh_interval().await?;
+            let brightness = client.get_brightness().await?;
+            let display_off = client.is_display_off().await?;
+            let panel = client.panel_id().await?;
             println!("LCD Status:");
+            println!("  Panel: {}", panel);
             println!("  Connected: {}", if connected { "yes" } else { "no" });
             println!("  Orientation: {}", orientation);
             println!("  Face: {}", face);
+            println!("  Refresh interval: {} ms", refresh_interval);
+            if display_off {
+                println!("  Brightness: {}% (display off)", brightness);
+            } else {
+                println!("  Brightness: {}%", brightness);
+            }
         }
     }
 
@@ -311,6 +476,13 @@ async fn handle_daemon(action: DaemonCommands, client: &DaemonClient) -> Result<
             println!("Daemon: running");
             println!("LCD connected: {}", if connected { "yes" } else { "no" });
         }
+        DaemonCommands::Panels => {
+            let panels = client.list_panels().await?;
+            println!("Panels:");
+            for panel in panels {
+                println!("  {}", panel);
+            }
+        }
         DaemonCommands::Quit => {
             client.quit().await?;
             println!("Shutdown request sent to daemon");
@@ -429,6 +601,152 @@ async fn handle_complication(action: ComplicationCommands, client: &DaemonClient
     Ok(())
 }
 
+/// Parses a playlist entry given as FACE[@THEME][:SECONDS].
+fn parse_playlist_entry(entry: &str) -> Result<(String, String, u32)> {
+    let (spec, duration) = match entry.rsplit_once(':') {
+        Some((spec, secs)) => {
+            let secs = secs
+                .parse()
+                .with_context(|| format!("Invalid duration in playlist entry: {}", entry))?;
+            (spec, secs)
+        }
+        None => (entry, 30),
+    };
+    let (face, theme) = spec.split_once('@').unwrap_or((spec, ""));
+    if face.is_empty() {
+        anyhow::bail!("Missing face in playlist entry: {}", entry);
+    }
+    Ok((face.to_string(), theme.to_string(), duration))
+}
+
+async fn handle_schedule(client: &DaemonClient) -> Result<()> {
+    let rules = client.list_schedules().await?;
+    let active = client.active_schedule().await?;
+    println!("Active rule: {}", active.as_deref().unwrap_or("none"));
+    if rules.is_empty() {
+        println!("  (no rules configured)");
+    }
+    for (name, start, end) in rules {
+        let marker = if active.as_deref() == Some(name.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+        if end.is_empty() {
+            println!("  {} {} from {}", marker, name, start);
+        } else {
+            println!("  {} {} {}-{}", marker, name, start, end);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_notify(
+    text: Option<String>,
+    severity: &str,
+    seconds: u32,
+    history: bool,
+    client: &DaemonClient,
+) -> Result<()> {
+    if history {
+        let notifications = client.list_notifications().await?;
+        if notifications.is_empty() {
+            println!("No notifications");
+        }
+        for (_, time, severity, text, seconds) in notifications {
+            println!("{}  {:<8} {} ({}s)", time, severity, text, seconds);
+        }
+    } else if let Some(text) = text {
+        let id = client.show_notification(&text, severity, seconds).await?;
+        println!("Notification {} shown for {}s", id, seconds);
+    }
+    Ok(())
+}
+
+async fn handle_custom(
+    key: Option<String>,
+    value: Option<f64>,
+    unit: &str,
+    client: &DaemonClient,
+) -> Result<()> {
+    match (key, value) {
+        (Some(key), Some(value)) => {
+            client.set_custom_value(&key, value, unit).await?;
+            println!("Custom value {} set to {} {}", key, value, unit);
+        }
+        (key, _) => {
+            let values = client.list_custom_values().await?;
+            let values: Vec<_> = values
+                .into_iter()
+                .filter(|(k, _, _)| key.as_ref().is_none_or(|key| key == k))
+                .collect();
+            if values.is_empty() {
+                println!("No custom values");
+            }
+            for (key, value, unit) in values {
+                println!("{:<16} {} {}", key, value, unit);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_carousel(action: CarouselCommands, client: &DaemonClient) -> Result<()> {
+    match action {
+        CarouselCommands::Show => {
+            let playlist = client.get_playlist().await?;
+            let running = client.is_carousel_running().await?;
+            let index = client.carousel_index().await? as usize;
+            println!("Carousel: {}", if running { "running" } else { "stopped" });
+            if playlist.is_empty() {
+                println!("  (playlist empty)");
+            }
+            for (i, (face, theme, duration)) in playlist.iter().enumerate() {
+                let marker = if i == index { "*" } else { " " };
+                if theme.is_empty() {
+                    println!("  {} {} for {}s", marker, face, duration);
+                } else {
+                    println!("  {} {} ({}) for {}s", marker, face, theme, duration);
+                }
+            }
+        }
+        CarouselCommands::Set { entries } => {
+            let entries = entries
+                .iter()
+                .map(|e| parse_playlist_entry(e))
+                .collect::<Result<Vec<_>>>()?;
+            let refs: Vec<(&str, &str, u32)> = entries
+                .iter()
+                .map(|(face, theme, duration)| (face.as_str(), theme.as_str(), *duration))
+                .collect();
+            client.set_playlist(&refs).await?;
+            println!("Playlist set to {} face(s)", refs.len());
+        }
+        CarouselCommands::Clear => {
+            client.set_playlist(&[]).await?;
+            println!("Playlist cleared");
+        }
+        CarouselCommands::Start => {
+            client.start_carousel().await?;
+            println!("Carousel started");
+        }
+        CarouselCommands::Stop => {
+            client.stop_carousel().await?;
+            println!("Carousel stopped");
+        }
+        CarouselCommands::Next => {
+            client.next_face().await?;
+            println!("Face set to: {}", client.get_face().await?);
+        }
+        CarouselCommands::Previous => {
+            client.previous_face().await?;
+            println!("Face set to: {}", client.get_face().await?);
+        }
+    }
+
+    Ok(())
+}
+
 async fn handle_screenshot(output: &str, client: &DaemonClient) -> Result<()> {
     let png_data = client.get_screen_png().await?;
     std::fs::write(output, &png_data).context("Failed to write screenshot file")?;
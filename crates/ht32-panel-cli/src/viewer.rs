@@ -0,0 +1,116 @@
+//! Viewer for virtual LCD streams.
+//!
+//! Reads the raw packets written by a daemon configured with
+//! `devices.lcd = "virtual:<path>"`, rebuilds the screen image and writes it
+//! out as PNG whenever a frame is complete.
+
+use anyhow::{Context, Result};
+use ht32_panel_hw::lcd::{decode_packet, Framebuffer, BUFFER_SIZE};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+
+/// Options for the viewer.
+pub struct ViewerOptions {
+    /// Path of the virtual LCD stream
+    pub path: PathBuf,
+    /// Output PNG path
+    pub output: PathBuf,
+    /// Write numbered PNGs instead of overwriting the output
+    pub sequence: bool,
+    /// Create a Unix socket at the path and wait for the daemon
+    pub listen: bool,
+}
+
+/// Decodes the stream until it ends, writing a PNG for every frame.
+///
+/// A frame ends when a full redraw completes, or when a heartbeat or
+/// orientation packet follows partial refreshes.
+pub fn run(options: &ViewerOptions) -> Result<()> {
+    let mut reader = open(options)?;
+    let mut screen = Framebuffer::new();
+    let mut packet = vec![0u8; BUFFER_SIZE];
+    let mut pending = false;
+    let mut frames = 0usize;
+
+    loop {
+        match reader.read_exact(&mut packet) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).context("Failed to read virtual LCD stream"),
+        }
+
+        let Some(decoded) = decode_packet(&packet) else {
+            tracing::warn!("Skipping undecodable packet");
+            continue;
+        };
+        decoded.apply(&mut screen);
+
+        let updates_pixels = decoded.has_pixels();
+        if updates_pixels {
+            pending = true;
+        }
+        if pending && (decoded.ends_redraw() || !updates_pixels) {
+            frames += 1;
+            write_frame(&screen, options, frames)?;
+            pending = false;
+        }
+    }
+
+    if pending {
+        frames += 1;
+        write_frame(&screen, options, frames)?;
+    }
+
+    println!("Stream ended after {} frame(s)", frames);
+    Ok(())
+}
+
+fn open(options: &ViewerOptions) -> Result<Box<dyn Read>> {
+    if !options.listen {
+        let file = File::open(&options.path)
+            .with_context(|| format!("Failed to open {}", options.path.display()))?;
+        return Ok(Box::new(file));
+    }
+
+    // Remove a stale socket left behind by a previous viewer
+    if std::fs::symlink_metadata(&options.path).is_ok() {
+        std::fs::remove_file(&options.path)
+            .with_context(|| format!("Failed to remove {}", options.path.display()))?;
+    }
+    let listener = UnixListener::bind(&options.path)
+        .with_context(|| format!("Failed to listen on {}", options.path.display()))?;
+    println!("Waiting for daemon on {}", options.path.display());
+    let (stream, _) = listener.accept().context("Failed to accept connection")?;
+    println!("Daemon connected");
+    Ok(Box::new(stream))
+}
+
+fn write_frame(screen: &Framebuffer, options: &ViewerOptions, frame: usize) -> Result<()> {
+    let path = if options.sequence {
+        numbered_path(&options.output, frame)
+    } else {
+        options.output.clone()
+    };
+
+    let file =
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut encoder = png::Encoder::new(file, screen.width() as u32, screen.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&screen.to_rgba8())?;
+
+    tracing::debug!("Wrote frame {} to {}", frame, path.display());
+    Ok(())
+}
+
+/// Returns `dir/name-00042.png` for `dir/name.png` and frame 42.
+fn numbered_path(output: &Path, frame: usize) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "frame".to_string());
+    output.with_file_name(format!("{}-{:05}.png", stem, frame))
+}
@@ -34,12 +34,61 @@ trait Daemon1 {
     /// Clears the display to a solid color.
     fn clear_display(&self, color: &str) -> zbus::Result<()>;
 
+    /// Sets the display refresh interval in milliseconds (500-10000).
+    fn set_refresh_interval(&self, ms: u32) -> zbus::Result<()>;
+
+    /// Gets the display refresh interval in milliseconds.
+    fn get_refresh_interval(&self) -> zbus::Result<u32>;
+
+    /// Sets the display brightness in percent (0-100).
+    fn set_brightness(&self, percent: u8) -> zbus::Result<()>;
+
+    /// Gets the display brightness in percent.
+    fn get_brightness(&self) -> zbus::Result<u8>;
+
+    /// Switches the display off or back on.
+    fn set_display_off(&self, off: bool) -> zbus::Result<()>;
+
     /// Sets the display face.
     fn set_face(&self, face: &str) -> zbus::Result<()>;
 
     /// Gets the current face name.
     fn get_face(&self) -> zbus::Result<String>;
 
+    /// Sets the carousel playlist as (face, theme, seconds) entries.
+    /// An empty theme keeps the current one.
+    fn set_playlist(&self, entries: &[(&str, &str, u32)]) -> zbus::Result<()>;
+
+    /// Gets the carousel playlist as (face, theme, seconds) entries.
+    fn get_playlist(&self) -> zbus::Result<Vec<(String, String, u32)>>;
+
+    /// Starts rotating through the playlist.
+    fn start_carousel(&self) -> zbus::Result<()>;
+
+    /// Stops the carousel on the current face.
+    fn stop_carousel(&self) -> zbus::Result<()>;
+
+    /// Shows the next face of the playlist.
+    fn next_face(&self) -> zbus::Result<()>;
+
+    /// Shows the previous face of the playlist.
+    fn previous_face(&self) -> zbus::Result<()>;
+
+    /// Lists the schedule rules as (name, start, end) entries.
+    fn list_schedules(&self) -> zbus::Result<Vec<(String, String, String)>>;
+
+    /// Draws a notification over the face; returns its id.
+    fn show_notification(&self, text: &str, severity: &str, seconds: u32) -> zbus::Result<u32>;
+
+    /// Lists the notification history as (id, time, severity, text, seconds) entries.
+    fn list_notifications(&self) -> zbus::Result<Vec<(u32, String, String, String, u32)>>;
+
+    /// Sets a custom value shown by faces.
+    fn set_custom_value(&self, key: &str, value: f64, unit: &str) -> zbus::Result<()>;
+
+    /// Lists the custom values as (key, value, unit) entries.
+    fn list_custom_values(&self) -> zbus::Result<Vec<(String, f64, String)>>;
+
     /// Sets LED parameters.
     fn set_led(&self, theme: u8, intensity: u8, speed: u8) -> zbus::Result<()>;
 
@@ -105,9 +154,46 @@ trait Daemon1 {
     /// Returns the current framebuffer as PNG data.
     fn get_screen_png(&self) -> zbus::Result<Vec<u8>>;
 
+    /// Lists the ids of all panels, default first.
+    fn list_panels(&self) -> zbus::Result<Vec<String>>;
+
     /// Shuts down the daemon.
     fn quit(&self) -> zbus::Result<()>;
 
+    /// Emitted when the display orientation changes.
+    #[zbus(signal, name = "OrientationChanged")]
+    fn orientation_signal(&self, orientation: &str) -> zbus::Result<()>;
+
+    /// Emitted when the LED theme, intensity or speed changes.
+    #[zbus(signal)]
+    fn led_changed(&self, theme: u8, intensity: u8, speed: u8) -> zbus::Result<()>;
+
+    /// Emitted when the face, color theme, refresh interval, brightness,
+    /// carousel or enabled complications change.
+    #[zbus(signal)]
+    fn display_settings_changed(&self, face: &str, theme: &str) -> zbus::Result<()>;
+
+    /// Emitted when a complication option is set.
+    #[zbus(signal)]
+    fn complication_option_changed(
+        &self,
+        complication_id: &str,
+        option_id: &str,
+        value: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a metric crosses a warning or critical threshold.
+    #[zbus(signal)]
+    fn alert_raised(&self, metric: &str, level: &str, value: f64) -> zbus::Result<()>;
+
+    /// Emitted when a schedule rule becomes active or ends.
+    #[zbus(signal)]
+    fn schedule_changed(&self, rule: &str) -> zbus::Result<()>;
+
+    /// Emitted when a notification is queued for display.
+    #[zbus(signal)]
+    fn notification_posted(&self, id: u32, text: &str, severity: &str) -> zbus::Result<()>;
+
     /// Whether the LCD device is connected.
     #[zbus(property)]
     fn connected(&self) -> zbus::Result<bool>;
@@ -116,6 +202,10 @@ trait Daemon1 {
     #[zbus(property)]
     fn web_enabled(&self) -> zbus::Result<bool>;
 
+    /// Id of the panel served by this object.
+    #[zbus(property)]
+    fn panel_id(&self) -> zbus::Result<String>;
+
     /// Current display orientation.
     #[zbus(property)]
     fn orientation(&self) -> zbus::Result<String>;
@@ -132,9 +222,33 @@ trait Daemon1 {
     #[zbus(property)]
     fn led_speed(&self) -> zbus::Result<u8>;
 
+    /// Current color theme name.
+    #[zbus(property)]
+    fn theme(&self) -> zbus::Result<String>;
+
     /// Current display face name.
     #[zbus(property)]
     fn face(&self) -> zbus::Result<String>;
+
+    /// Current display refresh interval in milliseconds.
+    #[zbus(property)]
+    fn refresh_interval(&self) -> zbus::Result<u32>;
+
+    /// Whether the display is switched off.
+    #[zbus(property)]
+    fn display_off(&self) -> zbus::Result<bool>;
+
+    /// Whether the face carousel is rotating.
+    #[zbus(property)]
+    fn carousel_running(&self) -> zbus::Result<bool>;
+
+    /// Index of the current carousel playlist entry.
+    #[zbus(property)]
+    fn carousel_index(&self) -> zbus::Result<u32>;
+
+    /// Name of the active schedule rule, or empty if none is active.
+    #[zbus(property)]
+    fn active_schedule(&self) -> zbus::Result<String>;
 }
 
 /// D-Bus client wrapper for the daemon.
@@ -152,6 +266,13 @@ impl DaemonClient {
 
     /// Attempts to connect to the daemon via D-Bus with specified bus type.
     pub async fn connect_with_bus(bus_type: BusType) -> Result<Self> {
+        Self::connect_to_panel(bus_type, None).await
+    }
+
+    /// Attempts to connect to a specific panel of the daemon.
+    ///
+    /// `None` selects the default panel.
+    pub async fn connect_to_panel(bus_type: BusType, panel: Option<&str>) -> Result<Self> {
         let connection = match bus_type {
             BusType::Session => {
                 debug!("Connecting to session bus");
@@ -194,9 +315,17 @@ impl DaemonClient {
             }
         };
 
-        let proxy = Daemon1Proxy::new(&connection)
-            .await
-            .context("Failed to create D-Bus proxy")?;
+        let proxy = match panel {
+            Some(panel) => Daemon1Proxy::builder(&connection)
+                .path(format!("/org/ht32panel/Daemon/panels/{}", panel))
+                .context("Invalid panel id")?
+                .build()
+                .await
+                .context("Failed to create D-Bus proxy")?,
+            None => Daemon1Proxy::new(&connection)
+                .await
+                .context("Failed to create D-Bus proxy")?,
+        };
 
         Ok(Self { proxy })
     }
@@ -238,6 +367,54 @@ impl DaemonClient {
             .context("Failed to clear display via D-Bus")
     }
 
+    /// Sets the display refresh interval in milliseconds (500-10000).
+    pub async fn set_refresh_interval(&self, ms: u32) -> Result<()> {
+        self.proxy
+            .set_refresh_interval(ms)
+            .await
+            .context("Failed to set refresh interval via D-Bus")
+    }
+
+    /// Gets the display refresh interval in milliseconds.
+    pub async fn get_refresh_interval(&self) -> Result<u32> {
+        self.proxy
+            .get_refresh_interval()
+            .await
+            .context("Failed to get refresh interval via D-Bus")
+    }
+
+    /// Sets the display brightness in percent (0-100).
+    pub async fn set_brightness(&self, percent: u8) -> Result<()> {
+        self.proxy
+            .set_brightness(percent)
+            .await
+            .context("Failed to set brightness via D-Bus")
+    }
+
+    /// Gets the display brightness in percent.
+    pub async fn get_brightness(&self) -> Result<u8> {
+        self.proxy
+            .get_brightness()
+            .await
+            .context("Failed to get brightness via D-Bus")
+    }
+
+    /// Switches the display off (black frames) or back on.
+    pub async fn set_display_off(&self, off: bool) -> Result<()> {
+        self.proxy
+            .set_display_off(off)
+            .await
+            .context("Failed to switch display via D-Bus")
+    }
+
+    /// Checks if the display is switched off.
+    pub async fn is_display_off(&self) -> Result<bool> {
+        self.proxy
+            .display_off()
+            .await
+            .context("Failed to get display state via D-Bus")
+    }
+
     /// Sets the display face.
     pub async fn set_face(&self, face: &str) -> Result<()> {
         self.proxy
@@ -254,6 +431,125 @@ impl DaemonClient {
             .context("Failed to get face via D-Bus")
     }
 
+    /// Sets the carousel playlist as (face, theme, seconds) entries.
+    /// An empty theme keeps the current one.
+    pub async fn set_playlist(&self, entries: &[(&str, &str, u32)]) -> Result<()> {
+        self.proxy
+            .set_playlist(entries)
+            .await
+            .context("Failed to set playlist via D-Bus")
+    }
+
+    /// Gets the carousel playlist as (face, theme, seconds) entries.
+    pub async fn get_playlist(&self) -> Result<Vec<(String, String, u32)>> {
+        self.proxy
+            .get_playlist()
+            .await
+            .context("Failed to get playlist via D-Bus")
+    }
+
+    /// Starts rotating through the playlist.
+    pub async fn start_carousel(&self) -> Result<()> {
+        self.proxy
+            .start_carousel()
+            .await
+            .context("Failed to start carousel via D-Bus")
+    }
+
+    /// Stops the carousel on the current face.
+    pub async fn stop_carousel(&self) -> Result<()> {
+        self.proxy
+            .stop_carousel()
+            .await
+            .context("Failed to stop carousel via D-Bus")
+    }
+
+    /// Shows the next face of the playlist.
+    pub async fn next_face(&self) -> Result<()> {
+        self.proxy
+            .next_face()
+            .await
+            .context("Failed to show next face via D-Bus")
+    }
+
+    /// Shows the previous face of the playlist.
+    pub async fn previous_face(&self) -> Result<()> {
+        self.proxy
+            .previous_face()
+            .await
+            .context("Failed to show previous face via D-Bus")
+    }
+
+    /// Checks if the face carousel is rotating.
+    pub async fn is_carousel_running(&self) -> Result<bool> {
+        self.proxy
+            .carousel_running()
+            .await
+            .context("Failed to get carousel status via D-Bus")
+    }
+
+    /// Gets the index of the current carousel playlist entry.
+    pub async fn carousel_index(&self) -> Result<u32> {
+        self.proxy
+            .carousel_index()
+            .await
+            .context("Failed to get carousel index via D-Bus")
+    }
+
+    /// Lists the schedule rules as (name, start, end) entries.
+    /// The end is empty for rules that last until the next rule starts.
+    pub async fn list_schedules(&self) -> Result<Vec<(String, String, String)>> {
+        self.proxy
+            .list_schedules()
+            .await
+            .context("Failed to list schedules via D-Bus")
+    }
+
+    /// Gets the name of the active schedule rule, if any.
+    pub async fn active_schedule(&self) -> Result<Option<String>> {
+        let rule = self
+            .proxy
+            .active_schedule()
+            .await
+            .context("Failed to get active schedule via D-Bus")?;
+        Ok(Some(rule).filter(|r| !r.is_empty()))
+    }
+
+    /// Draws a notification over the face for a number of seconds (0 for
+    /// the daemon's default). Returns the notification id.
+    pub async fn show_notification(&self, text: &str, severity: &str, seconds: u32) -> Result<u32> {
+        self.proxy
+            .show_notification(text, severity, seconds)
+            .await
+            .context("Failed to show notification via D-Bus")
+    }
+
+    /// Lists the notification history, oldest first, as (id, time, severity,
+    /// text, seconds) entries.
+    pub async fn list_notifications(&self) -> Result<Vec<(u32, String, String, String, u32)>> {
+        self.proxy
+            .list_notifications()
+            .await
+            .context("Failed to list notifications via D-Bus")
+    }
+
+    /// Sets a custom value, shared by all panels, shown by faces bound to
+    /// `custom:<key>`. The unit may be empty.
+    pub async fn set_custom_value(&self, key: &str, value: f64, unit: &str) -> Result<()> {
+        self.proxy
+            .set_custom_value(key, value, unit)
+            .await
+            .context("Failed to set custom value via D-Bus")
+    }
+
+    /// Lists the custom values as (key, value, unit) entries, sorted by key.
+    pub async fn list_custom_values(&self) -> Result<Vec<(String, f64, String)>> {
+        self.proxy
+            .list_custom_values()
+            .await
+            .context("Failed to list custom values via D-Bus")
+    }
+
     /// Sets LED parameters.
     pub async fn set_led(&self, theme: u8, intensity: u8, speed: u8) -> Result<()> {
         self.proxy
@@ -342,6 +638,22 @@ impl DaemonClient {
             .context("Failed to get screen PNG via D-Bus")
     }
 
+    /// Lists the ids of all panels, default first.
+    pub async fn list_panels(&self) -> Result<Vec<String>> {
+        self.proxy
+            .list_panels()
+            .await
+            .context("Failed to list panels via D-Bus")
+    }
+
+    /// Gets the id of the connected panel.
+    pub async fn panel_id(&self) -> Result<String> {
+        self.proxy
+            .panel_id()
+            .await
+            .context("Failed to get panel id via D-Bus")
+    }
+
     /// Shuts down the daemon.
     pub async fn quit(&self) -> Result<()> {
         self.proxy
@@ -0,0 +1,291 @@
+//! Threshold alerts for CPU, temperature and RAM.
+//!
+//! Each panel tracks the alert level of its metrics between frames. Levels
+//! only drop once a value falls a small margin below the threshold, so a
+//! metric hovering around a threshold doesn't flicker between colors or
+//! raise a stream of alerts.
+
+use ht32_panel_hw::led::LedTheme;
+
+use crate::config::{AlertsConfig, Threshold};
+use crate::rendering::Canvas;
+use crate::sensors::data::{AlertLevel, MetricAlerts, SystemData};
+
+/// Margin below a threshold a value must fall to clear its alert level.
+const HYSTERESIS: f64 = 2.0;
+
+/// Width of the border drawn while flashing, in pixels.
+const FLASH_BORDER: u32 = 4;
+
+/// An alert raised when a metric crossed a threshold upwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// Metric name (`cpu_percent`, `cpu_temp` or `ram_percent`)
+    pub metric: &'static str,
+    /// Level the metric reached
+    pub level: AlertLevel,
+    /// Value that crossed the threshold
+    pub value: f64,
+}
+
+/// Change of the LED alert override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedAlert {
+    /// Show this theme until the alert clears.
+    Show(LedTheme),
+    /// Restore the user's LED settings.
+    Restore,
+}
+
+/// Tracks alert levels for one panel.
+pub struct AlertTracker {
+    config: AlertsConfig,
+    levels: MetricAlerts,
+    raised: Vec<Alert>,
+    flash_on: bool,
+    led_active: bool,
+}
+
+impl AlertTracker {
+    /// Creates a tracker with every metric at the normal level.
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            levels: MetricAlerts::default(),
+            raised: Vec::new(),
+            flash_on: false,
+            led_active: false,
+        }
+    }
+
+    /// Updates the alert levels from freshly sampled data and stores them in it.
+    pub fn update(&mut self, data: &mut SystemData) {
+        let previous = self.levels;
+        self.levels = MetricAlerts {
+            cpu_percent: level(
+                self.config.cpu_percent,
+                Some(data.cpu_percent),
+                previous.cpu_percent,
+            ),
+            cpu_temp: level(self.config.cpu_temp, data.cpu_temp, previous.cpu_temp),
+            ram_percent: level(
+                self.config.ram_percent,
+                Some(data.ram_percent),
+                previous.ram_percent,
+            ),
+        };
+
+        for (metric, before, after, value) in [
+            (
+                "cpu_percent",
+                previous.cpu_percent,
+                self.levels.cpu_percent,
+                Some(data.cpu_percent),
+            ),
+            (
+                "cpu_temp",
+                previous.cpu_temp,
+                self.levels.cpu_temp,
+                data.cpu_temp,
+            ),
+            (
+                "ram_percent",
+                previous.ram_percent,
+                self.levels.ram_percent,
+                Some(data.ram_percent),
+            ),
+        ] {
+            match value {
+                Some(value) if after > before => self.raised.push(Alert {
+                    metric,
+                    level: after,
+                    value,
+                }),
+                _ => {}
+            }
+        }
+
+        data.alerts = self.levels;
+    }
+
+    /// Returns the alerts raised since the last call.
+    pub fn take_raised(&mut self) -> Vec<Alert> {
+        std::mem::take(&mut self.raised)
+    }
+
+    /// Draws the flashing border on every other frame while a metric is
+    /// critical, if flashing is enabled.
+    pub fn draw_flash(&mut self, canvas: &mut Canvas, color: u32) {
+        if !self.config.flash || self.levels.max() < AlertLevel::Critical {
+            self.flash_on = false;
+            return;
+        }
+        self.flash_on = !self.flash_on;
+        if !self.flash_on {
+            return;
+        }
+
+        let (width, height) = canvas.dimensions();
+        let b = FLASH_BORDER.min(width / 2).min(height / 2);
+        canvas.fill_rect(0, 0, width, b, color);
+        canvas.fill_rect(0, (height - b) as i32, width, b, color);
+        canvas.fill_rect(0, 0, b, height, color);
+        canvas.fill_rect((width - b) as i32, 0, b, height, color);
+    }
+
+    /// Returns a change of the LED override, if any.
+    ///
+    /// The configured LED theme is shown while any metric is critical.
+    pub fn led_change(&mut self) -> Option<LedAlert> {
+        let theme = self.config.led_theme()?;
+        let active = self.levels.max() == AlertLevel::Critical;
+        if active == self.led_active {
+            return None;
+        }
+        self.led_active = active;
+        Some(if active {
+            LedAlert::Show(theme)
+        } else {
+            LedAlert::Restore
+        })
+    }
+}
+
+/// Returns the alert level of a value, given the level it had last frame.
+fn level(threshold: Option<Threshold>, value: Option<f64>, current: AlertLevel) -> AlertLevel {
+    let (Some(threshold), Some(value)) = (threshold, value) else {
+        return AlertLevel::Normal;
+    };
+    // A level that is already reached holds until the value drops below the margin
+    let past = |limit: Option<f64>, held: bool| {
+        limit.is_some_and(|limit| value >= limit || (held && value > limit - HYSTERESIS))
+    };
+
+    if past(threshold.critical, current == AlertLevel::Critical) {
+        AlertLevel::Critical
+    } else if past(threshold.warning, current >= AlertLevel::Warning) {
+        AlertLevel::Warning
+    } else {
+        AlertLevel::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> AlertTracker {
+        AlertTracker::new(AlertsConfig {
+            cpu_temp: Some(Threshold {
+                warning: Some(70.0),
+                critical: Some(80.0),
+            }),
+            ram_percent: Some(Threshold {
+                warning: None,
+                critical: Some(90.0),
+            }),
+            flash: true,
+            led_theme: Some("rainbow".to_string()),
+            ..AlertsConfig::default()
+        })
+    }
+
+    fn sample(tracker: &mut AlertTracker, cpu_temp: f64, ram_percent: f64) -> MetricAlerts {
+        let mut data = SystemData {
+            cpu_percent: 100.0,
+            cpu_temp: Some(cpu_temp),
+            ram_percent,
+            ..SystemData::default()
+        };
+        tracker.update(&mut data);
+        data.alerts
+    }
+
+    #[test]
+    fn test_levels_follow_thresholds() {
+        let mut tracker = tracker();
+
+        let alerts = sample(&mut tracker, 65.0, 50.0);
+        assert_eq!(alerts, MetricAlerts::default());
+
+        let alerts = sample(&mut tracker, 72.0, 95.0);
+        assert_eq!(alerts.cpu_temp, AlertLevel::Warning);
+        assert_eq!(alerts.ram_percent, AlertLevel::Critical);
+        // CPU usage has no thresholds configured
+        assert_eq!(alerts.cpu_percent, AlertLevel::Normal);
+
+        assert_eq!(
+            sample(&mut tracker, 85.0, 50.0).cpu_temp,
+            AlertLevel::Critical
+        );
+    }
+
+    #[test]
+    fn test_levels_clear_with_hysteresis() {
+        let mut tracker = tracker();
+        sample(&mut tracker, 81.0, 50.0);
+
+        assert_eq!(
+            sample(&mut tracker, 79.0, 50.0).cpu_temp,
+            AlertLevel::Critical
+        );
+        assert_eq!(
+            sample(&mut tracker, 77.5, 50.0).cpu_temp,
+            AlertLevel::Warning
+        );
+        assert_eq!(
+            sample(&mut tracker, 69.0, 50.0).cpu_temp,
+            AlertLevel::Warning
+        );
+        assert_eq!(
+            sample(&mut tracker, 67.0, 50.0).cpu_temp,
+            AlertLevel::Normal
+        );
+    }
+
+    #[test]
+    fn test_only_rising_levels_are_raised() {
+        let mut tracker = tracker();
+        sample(&mut tracker, 75.0, 50.0);
+        sample(&mut tracker, 85.0, 50.0);
+        sample(&mut tracker, 85.0, 50.0);
+        sample(&mut tracker, 60.0, 50.0);
+
+        let raised = tracker.take_raised();
+        let levels: Vec<_> = raised.iter().map(|a| (a.metric, a.level)).collect();
+        assert_eq!(
+            levels,
+            [
+                ("cpu_temp", AlertLevel::Warning),
+                ("cpu_temp", AlertLevel::Critical)
+            ]
+        );
+        assert_eq!(raised[1].value, 85.0);
+        assert!(tracker.take_raised().is_empty());
+    }
+
+    #[test]
+    fn test_led_and_flash_follow_critical_level() {
+        let mut tracker = tracker();
+        let mut canvas = Canvas::new(320, 170);
+
+        sample(&mut tracker, 75.0, 50.0);
+        assert_eq!(tracker.led_change(), None);
+        tracker.draw_flash(&mut canvas, 0xFF0000);
+        assert!(!tracker.flash_on);
+
+        sample(&mut tracker, 90.0, 50.0);
+        assert_eq!(
+            tracker.led_change(),
+            Some(LedAlert::Show(LedTheme::Rainbow))
+        );
+        assert_eq!(tracker.led_change(), None);
+        tracker.draw_flash(&mut canvas, 0xFF0000);
+        assert!(tracker.flash_on);
+        tracker.draw_flash(&mut canvas, 0xFF0000);
+        assert!(!tracker.flash_on);
+
+        sample(&mut tracker, 50.0, 50.0);
+        assert_eq!(tracker.led_change(), Some(LedAlert::Restore));
+    }
+}
@@ -0,0 +1,267 @@
+//! Face carousel.
+//!
+//! A panel can rotate through a playlist of faces, each shown for its own
+//! duration and optionally switching the color theme. The render loop ticks
+//! the carousel every frame; the entry returned by a tick is applied to the
+//! display.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Longest time a playlist entry may be shown, in seconds.
+pub const MAX_ENTRY_DURATION: u32 = 24 * 60 * 60;
+
+/// A face shown by the carousel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    /// Face name.
+    pub face: String,
+
+    /// Color theme to switch to, or `None` to keep the current one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+
+    /// How long the face is shown, in seconds.
+    #[serde(default = "default_duration")]
+    pub duration: u32,
+}
+
+fn default_duration() -> u32 {
+    30
+}
+
+/// Playlist persisted with the display settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Playlist {
+    /// Whether the carousel is rotating.
+    #[serde(default)]
+    pub running: bool,
+
+    /// Faces to rotate through, in order.
+    #[serde(default)]
+    pub entries: Vec<PlaylistEntry>,
+}
+
+impl Playlist {
+    /// Returns true if there is nothing worth persisting.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Position of a panel in its playlist.
+pub struct Carousel {
+    entries: Vec<PlaylistEntry>,
+    index: usize,
+    /// When the current entry ends; `None` while stopped
+    deadline: Option<Instant>,
+}
+
+impl Carousel {
+    /// Creates a stopped carousel at the start of the playlist.
+    pub fn new(entries: Vec<PlaylistEntry>) -> Self {
+        Self {
+            entries,
+            index: 0,
+            deadline: None,
+        }
+    }
+
+    /// Returns the playlist for persisting.
+    pub fn playlist(&self) -> Playlist {
+        Playlist {
+            running: self.is_running(),
+            entries: self.entries.clone(),
+        }
+    }
+
+    /// Returns the playlist entries.
+    pub fn entries(&self) -> &[PlaylistEntry] {
+        &self.entries
+    }
+
+    /// Returns the index of the current entry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns true if the carousel is rotating.
+    pub fn is_running(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    /// Returns the time left until the next switch, if running.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline.map(|d| d.saturating_duration_since(now))
+    }
+
+    /// Replaces the playlist and rewinds to its first entry.
+    ///
+    /// Returns the first entry if the carousel keeps running.
+    pub fn set_entries(
+        &mut self,
+        entries: Vec<PlaylistEntry>,
+        now: Instant,
+    ) -> Option<PlaylistEntry> {
+        let running = self.is_running();
+        self.entries = entries;
+        self.index = 0;
+        self.deadline = None;
+        if running {
+            self.start(now)
+        } else {
+            None
+        }
+    }
+
+    /// Starts rotating from the current entry and returns it.
+    ///
+    /// Returns `None` if the playlist is empty.
+    pub fn start(&mut self, now: Instant) -> Option<PlaylistEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.show(self.index, true, now)
+    }
+
+    /// Stops rotating. Returns true if the carousel was running.
+    pub fn stop(&mut self) -> bool {
+        self.deadline.take().is_some()
+    }
+
+    /// Steps to the next entry and returns it.
+    pub fn next(&mut self, now: Instant) -> Option<PlaylistEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.show(
+            (self.index + 1) % self.entries.len(),
+            self.is_running(),
+            now,
+        )
+    }
+
+    /// Steps to the previous entry and returns it.
+    pub fn previous(&mut self, now: Instant) -> Option<PlaylistEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let len = self.entries.len();
+        self.show((self.index + len - 1) % len, self.is_running(), now)
+    }
+
+    /// Advances to the next entry once the current one's time is up.
+    pub fn tick(&mut self, now: Instant) -> Option<PlaylistEntry> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => self.next(now),
+            _ => None,
+        }
+    }
+
+    /// Makes `index` the current entry, timing it if the carousel runs.
+    fn show(&mut self, index: usize, run: bool, now: Instant) -> Option<PlaylistEntry> {
+        let entry = self.entries.get(index)?.clone();
+        self.index = index;
+        self.deadline = run.then(|| now + Duration::from_secs(entry.duration.max(1) as u64));
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(face: &str, duration: u32) -> PlaylistEntry {
+        PlaylistEntry {
+            face: face.to_string(),
+            theme: None,
+            duration,
+        }
+    }
+
+    fn carousel() -> Carousel {
+        Carousel::new(vec![
+            entry("professional", 30),
+            entry("arcs", 15),
+            entry("clock", 10),
+        ])
+    }
+
+    fn faces(entry: Option<PlaylistEntry>) -> Option<String> {
+        entry.map(|e| e.face)
+    }
+
+    #[test]
+    fn test_rotates_on_entry_durations() {
+        let mut carousel = carousel();
+        let t0 = Instant::now();
+        assert_eq!(carousel.tick(t0), None);
+
+        assert_eq!(faces(carousel.start(t0)), Some("professional".into()));
+        assert_eq!(carousel.tick(t0 + Duration::from_secs(29)), None);
+
+        let t1 = t0 + Duration::from_secs(30);
+        assert_eq!(faces(carousel.tick(t1)), Some("arcs".into()));
+        assert_eq!(carousel.remaining(t1), Some(Duration::from_secs(15)));
+
+        let t2 = t1 + Duration::from_secs(15);
+        assert_eq!(faces(carousel.tick(t2)), Some("clock".into()));
+        let t3 = t2 + Duration::from_secs(10);
+        assert_eq!(faces(carousel.tick(t3)), Some("professional".into()));
+        assert_eq!(carousel.index(), 0);
+    }
+
+    #[test]
+    fn test_stepping_keeps_run_state() {
+        let mut carousel = carousel();
+        let now = Instant::now();
+
+        assert_eq!(faces(carousel.previous(now)), Some("clock".into()));
+        assert!(!carousel.is_running());
+        assert_eq!(faces(carousel.next(now)), Some("professional".into()));
+
+        carousel.start(now);
+        assert_eq!(faces(carousel.next(now)), Some("arcs".into()));
+        assert!(carousel.is_running());
+
+        assert!(carousel.stop());
+        assert!(!carousel.stop());
+        assert_eq!(carousel.tick(now + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn test_empty_playlist_never_runs() {
+        let mut carousel = Carousel::new(Vec::new());
+        let now = Instant::now();
+        assert_eq!(carousel.start(now), None);
+        assert_eq!(carousel.next(now), None);
+        assert!(!carousel.is_running());
+
+        let mut carousel = self::carousel();
+        carousel.start(now);
+        carousel.next(now);
+        assert_eq!(carousel.set_entries(Vec::new(), now), None);
+        assert!(!carousel.is_running());
+        assert_eq!(carousel.index(), 0);
+    }
+
+    #[test]
+    fn test_playlist_round_trips_through_toml() {
+        let playlist = Playlist {
+            running: true,
+            entries: vec![
+                entry("professional", 30),
+                PlaylistEntry {
+                    theme: Some("nord".to_string()),
+                    ..entry("arcs", 15)
+                },
+            ],
+        };
+        let text = toml::to_string(&playlist).unwrap();
+        assert_eq!(toml::from_str::<Playlist>(&text).unwrap(), playlist);
+
+        let parsed: Playlist = toml::from_str("[[entries]]\nface = \"clock\"\n").unwrap();
+        assert!(!parsed.running);
+        assert_eq!(parsed.entries, vec![entry("clock", 30)]);
+    }
+}
@@ -1,8 +1,16 @@
 //! Configuration management.
 
 use anyhow::{Context, Result};
+use ht32_panel_hw::led::LedTheme;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::custom_values::CustomCommand;
+use crate::faces::ThemeDefinition;
+use crate::notification_monitor::NotificationRule;
+use crate::notifications;
+use crate::schedule::ScheduleRule;
 
 /// Main configuration structure.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,7 +27,20 @@ pub struct Config {
     #[serde(default = "default_state_dir")]
     pub state_dir: String,
 
-    /// Display refresh interval in milliseconds (500-10000)
+    /// Directory with custom face definitions (defaults to `<state_dir>/faces`)
+    #[serde(default)]
+    pub faces_dir: Option<String>,
+
+    /// Directory with user theme files (defaults to `<state_dir>/themes`)
+    #[serde(default)]
+    pub themes_dir: Option<String>,
+
+    /// User-defined color themes
+    #[serde(default)]
+    pub themes: Vec<ThemeDefinition>,
+
+    /// Initial display refresh interval in milliseconds (500-10000), used
+    /// until one is set at runtime
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval: u64,
 
@@ -27,6 +48,26 @@ pub struct Config {
     #[serde(default = "default_heartbeat")]
     pub heartbeat: u64,
 
+    /// Metric alert thresholds
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+
+    /// Time-of-day schedule rules
+    #[serde(default)]
+    pub schedule: Vec<ScheduleRule>,
+
+    /// Session and power management through logind
+    #[serde(default)]
+    pub logind: LogindConfig,
+
+    /// Mirroring of desktop notifications
+    #[serde(default)]
+    pub desktop_notifications: DesktopNotificationsConfig,
+
+    /// Commands whose output sets custom values
+    #[serde(default)]
+    pub custom_values: Vec<CustomCommand>,
+
     /// Device configuration
     #[serde(default)]
     pub devices: DevicesConfig,
@@ -43,9 +84,34 @@ pub struct WebConfig {
     #[serde(default)]
     pub enable: bool,
 
-    /// Server listen address (e.g., "0.0.0.0:8686")
+    /// Server listen address (e.g., "0.0.0.0:8686"), or "unix:<path>" for a
+    /// Unix socket
     #[serde(default = "default_listen")]
     pub listen: String,
+
+    /// Octal permissions of the Unix socket (e.g., "0660")
+    #[serde(default)]
+    pub socket_mode: Option<String>,
+
+    /// Group owning the Unix socket, by name or id
+    #[serde(default)]
+    pub socket_group: Option<String>,
+
+    /// Users who log in with HTTP basic auth
+    #[serde(default)]
+    pub users: Vec<WebUser>,
+
+    /// Bearer tokens accepted for scripts
+    #[serde(default)]
+    pub tokens: Vec<WebToken>,
+
+    /// PEM certificate chain; serves HTTPS together with `tls_key`
+    #[serde(default)]
+    pub tls_cert: Option<String>,
+
+    /// PEM private key of `tls_cert`
+    #[serde(default)]
+    pub tls_key: Option<String>,
 }
 
 impl Default for WebConfig {
@@ -53,7 +119,137 @@ impl Default for WebConfig {
         Self {
             enable: false,
             listen: default_listen(),
+            socket_mode: None,
+            socket_group: None,
+            users: Vec::new(),
+            tokens: Vec::new(),
+            tls_cert: None,
+            tls_key: None,
+        }
+    }
+}
+
+impl WebConfig {
+    /// Parses the listen address.
+    pub fn listen_addr(&self) -> Result<ListenAddr> {
+        match self.listen.strip_prefix("unix:") {
+            Some(path) if Path::new(path).is_absolute() => Ok(ListenAddr::Unix(path.into())),
+            Some(path) => anyhow::bail!("web.listen: Unix socket path '{}' is not absolute", path),
+            None => self
+                .listen
+                .parse()
+                .map(ListenAddr::Tcp)
+                .with_context(|| format!("web.listen: invalid address '{}'", self.listen)),
+        }
+    }
+
+    /// Parses the octal socket permissions.
+    pub fn socket_mode(&self) -> Result<Option<u32>> {
+        self.socket_mode
+            .as_deref()
+            .map(|mode| {
+                u32::from_str_radix(mode, 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o777)
+                    .with_context(|| format!("web.socket_mode: invalid mode '{}'", mode))
+            })
+            .transpose()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let addr = self.listen_addr()?;
+        self.socket_mode()?;
+        if matches!(addr, ListenAddr::Unix(_)) && self.tls_cert.is_some() {
+            anyhow::bail!("web: TLS is not supported on Unix sockets");
+        }
+        let mut seen = std::collections::HashSet::new();
+        for user in &self.users {
+            if user.name.is_empty() || user.name.contains(':') {
+                anyhow::bail!("web.users: invalid user name '{}'", user.name);
+            }
+            if !seen.insert(user.name.as_str()) {
+                anyhow::bail!("web.users: duplicate user '{}'", user.name);
+            }
+            argon2::PasswordHash::new(&user.password_hash).map_err(|e| {
+                anyhow::anyhow!("web.users '{}': invalid password_hash: {}", user.name, e)
+            })?;
         }
+        for token in &self.tokens {
+            token.digest()?;
+        }
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            anyhow::bail!("web: tls_cert and tls_key must be set together");
+        }
+        Ok(())
+    }
+}
+
+/// Where the web server listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    /// A TCP address and port.
+    Tcp(std::net::SocketAddr),
+    /// A Unix socket path.
+    Unix(PathBuf),
+}
+
+/// What an authenticated web client may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebRole {
+    /// View the preview, status and settings, but change nothing.
+    ReadOnly,
+    /// Change every setting.
+    #[default]
+    Admin,
+}
+
+/// A user of the web server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebUser {
+    /// Login name
+    pub name: String,
+
+    /// Argon2 hash of the password in PHC format (`$argon2id$...`)
+    pub password_hash: String,
+
+    /// What the user may do
+    #[serde(default)]
+    pub role: WebRole,
+}
+
+/// A bearer token accepted by the web server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebToken {
+    /// Name shown in logs
+    pub name: String,
+
+    /// SHA-256 hash of the token in hex. Tokens are random, so a fast hash
+    /// suffices and each request can be checked without Argon2.
+    pub token_hash: String,
+
+    /// What the token may do
+    #[serde(default)]
+    pub role: WebRole,
+}
+
+impl WebToken {
+    /// Decodes the SHA-256 hash of the token.
+    pub fn digest(&self) -> Result<[u8; 32]> {
+        let hash = self.token_hash.trim();
+        let mut digest = [0u8; 32];
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            anyhow::bail!(
+                "web.tokens '{}': token_hash must be a SHA-256 hash in hex",
+                self.name
+            );
+        }
+        for (byte, pair) in digest.iter_mut().zip(hash.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair)?, 16)?;
+        }
+        Ok(digest)
     }
 }
 
@@ -86,16 +282,162 @@ impl Default for DbusConfig {
     }
 }
 
+/// Session and power management through logind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogindConfig {
+    /// Whether to watch logind for suspend, lock and idle
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Blank the panels while the session is locked
+    #[serde(default = "default_true")]
+    pub blank_on_lock: bool,
+
+    /// Blank the panels while the session is idle
+    #[serde(default = "default_true")]
+    pub blank_on_idle: bool,
+
+    /// logind session id to watch (defaults to `XDG_SESSION_ID`, then
+    /// whichever session is active on seat0)
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+impl Default for LogindConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            blank_on_lock: true,
+            blank_on_idle: true,
+            session: None,
+        }
+    }
+}
+
+/// Mirroring of desktop notifications from the session bus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesktopNotificationsConfig {
+    /// Whether to mirror desktop notifications onto the panels
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Seconds to show a notification unless a rule says otherwise
+    #[serde(default = "default_notification_seconds")]
+    pub seconds: u32,
+
+    /// Ids of the panels to show notifications on; all panels when empty
+    #[serde(default)]
+    pub panels: Vec<String>,
+
+    /// Rules deciding which notifications are shown and how; the first
+    /// matching rule wins
+    #[serde(default)]
+    pub rules: Vec<NotificationRule>,
+}
+
+impl Default for DesktopNotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            seconds: default_notification_seconds(),
+            panels: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Warning and critical thresholds for one metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    /// Value at which the metric is drawn in the theme's warning color
+    #[serde(default)]
+    pub warning: Option<f64>,
+
+    /// Value at which the metric is drawn in the theme's critical color
+    #[serde(default)]
+    pub critical: Option<f64>,
+}
+
+/// Alert thresholds and actions. Metrics without thresholds never alert.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// CPU usage thresholds in percent
+    #[serde(default)]
+    pub cpu_percent: Option<Threshold>,
+
+    /// CPU temperature thresholds in °C
+    #[serde(default)]
+    pub cpu_temp: Option<Threshold>,
+
+    /// RAM usage thresholds in percent
+    #[serde(default)]
+    pub ram_percent: Option<Threshold>,
+
+    /// Flash a border around the panel while any metric is critical
+    #[serde(default)]
+    pub flash: bool,
+
+    /// LED theme to switch to while any metric is critical
+    /// (rainbow, breathing, colors, off or auto)
+    #[serde(default)]
+    pub led_theme: Option<String>,
+}
+
+impl AlertsConfig {
+    fn validate(&self) -> Result<()> {
+        for (metric, threshold) in [
+            ("cpu_percent", &self.cpu_percent),
+            ("cpu_temp", &self.cpu_temp),
+            ("ram_percent", &self.ram_percent),
+        ] {
+            if let Some(Threshold {
+                warning: Some(warning),
+                critical: Some(critical),
+            }) = threshold
+            {
+                if warning > critical {
+                    anyhow::bail!(
+                        "alerts.{}: warning threshold {} is above critical threshold {}",
+                        metric,
+                        warning,
+                        critical
+                    );
+                }
+            }
+        }
+        if let Some(ref theme) = self.led_theme {
+            theme
+                .parse::<LedTheme>()
+                .map_err(|_| anyhow::anyhow!("alerts.led_theme: unknown LED theme '{}'", theme))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the LED theme to show while a metric is critical.
+    pub fn led_theme(&self) -> Option<LedTheme> {
+        self.led_theme.as_deref().and_then(|t| t.parse().ok())
+    }
+}
+
 /// Device configuration for LCD and LED hardware.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DevicesConfig {
-    /// LCD device path or "auto" for auto-detection
+    /// LCD device path, "auto" for auto-detection, or "virtual:<path>" to
+    /// write packets to a file, FIFO or Unix socket instead of hardware
     #[serde(default = "default_lcd_device")]
     pub lcd: String,
 
     /// LED serial port path
     #[serde(default = "default_led_device")]
     pub led: String,
+
+    /// Named panels; when empty, a single panel is built from `lcd` and `led`
+    #[serde(default)]
+    pub panels: Vec<PanelConfig>,
 }
 
 impl Default for DevicesConfig {
@@ -103,10 +445,132 @@ impl Default for DevicesConfig {
         Self {
             lcd: default_lcd_device(),
             led: default_led_device(),
+            panels: Vec::new(),
+        }
+    }
+}
+
+impl DevicesConfig {
+    /// Returns the panels to drive. The first panel is the default one.
+    pub fn panels(&self) -> Vec<PanelConfig> {
+        if !self.panels.is_empty() {
+            return self.panels.clone();
+        }
+        vec![PanelConfig {
+            id: DEFAULT_PANEL_ID.to_string(),
+            lcd: self.lcd.clone(),
+            serial: None,
+            led: self.led.clone(),
+            face: None,
+            theme: None,
+            orientation: None,
+        }]
+    }
+
+    /// Returns true if panels are declared explicitly.
+    pub fn has_named_panels(&self) -> bool {
+        !self.panels.is_empty()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for panel in &self.panels {
+            if !is_valid_panel_id(&panel.id) {
+                anyhow::bail!(
+                    "Invalid panel id '{}': use letters, digits and underscores",
+                    panel.id
+                );
+            }
+            if !seen.insert(panel.id.as_str()) {
+                anyhow::bail!("Duplicate panel id '{}'", panel.id);
+            }
         }
+        // Auto-detection opens the first panel found, so two such panels
+        // would both drive the same screen
+        let auto: Vec<&str> = self
+            .panels
+            .iter()
+            .filter(|panel| panel.lcd_target() == LcdTarget::Auto)
+            .map(|panel| panel.id.as_str())
+            .collect();
+        if auto.len() > 1 {
+            anyhow::bail!(
+                "Panels {} all auto-detect their LCD: set `serial` or `lcd` on all but one",
+                auto.join(", ")
+            );
+        }
+        Ok(())
     }
 }
 
+/// Id of the panel built from the top-level `lcd` and `led` settings.
+pub const DEFAULT_PANEL_ID: &str = "default";
+
+/// Returns true if the id can be used in D-Bus object paths and URLs.
+pub fn is_valid_panel_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A single LCD/LED pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelConfig {
+    /// Panel id used by D-Bus, the web UI and the CLI
+    pub id: String,
+
+    /// LCD device path, "auto" for auto-detection, or "virtual:<path>"
+    #[serde(default = "default_lcd_device")]
+    pub lcd: String,
+
+    /// USB serial number of the LCD; takes precedence over `lcd`
+    #[serde(default)]
+    pub serial: Option<String>,
+
+    /// LED serial port path
+    #[serde(default = "default_led_device")]
+    pub led: String,
+
+    /// Initial face, used until one is chosen at runtime
+    #[serde(default)]
+    pub face: Option<String>,
+
+    /// Initial color theme, used until one is chosen at runtime
+    #[serde(default)]
+    pub theme: Option<String>,
+
+    /// Initial orientation, used until one is chosen at runtime
+    #[serde(default)]
+    pub orientation: Option<String>,
+}
+
+impl PanelConfig {
+    /// Parses the LCD device setting.
+    pub fn lcd_target(&self) -> LcdTarget {
+        if let Some(serial) = &self.serial {
+            return LcdTarget::Serial(serial.clone());
+        }
+        match self.lcd.as_str() {
+            "auto" | "" => LcdTarget::Auto,
+            lcd => match lcd.strip_prefix("virtual:") {
+                Some(path) => LcdTarget::Virtual(PathBuf::from(path)),
+                None => LcdTarget::Path(lcd.to_string()),
+            },
+        }
+    }
+}
+
+/// Where LCD packets are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LcdTarget {
+    /// Auto-detect the panel by VID:PID
+    Auto,
+    /// A specific hidraw device path
+    Path(String),
+    /// The panel with this USB serial number
+    Serial(String),
+    /// A file, FIFO or Unix socket receiving raw packets
+    Virtual(PathBuf),
+}
+
 /// Canvas configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanvasConfig {
@@ -147,6 +611,25 @@ fn default_state_dir() -> String {
     }
 }
 
+/// Shortest display refresh interval in milliseconds.
+pub const MIN_REFRESH_INTERVAL: u32 = 500;
+
+/// Longest display refresh interval in milliseconds.
+pub const MAX_REFRESH_INTERVAL: u32 = 10000;
+
+/// Returns true if the refresh interval is within the supported range.
+pub fn is_valid_refresh_interval(ms: u64) -> bool {
+    (MIN_REFRESH_INTERVAL as u64..=MAX_REFRESH_INTERVAL as u64).contains(&ms)
+}
+
+fn default_notification_seconds() -> u32 {
+    notifications::DEFAULT_SECONDS
+}
+
+fn default_true() -> bool {
+    true
+}
+
 fn default_refresh_interval() -> u64 {
     2500
 }
@@ -176,9 +659,105 @@ impl Config {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content =
             std::fs::read_to_string(path.as_ref()).context("Failed to read configuration file")?;
-        let config: Config = toml::from_str(&content).context("Failed to parse configuration")?;
+        let mut config: Config =
+            toml::from_str(&content).context("Failed to parse configuration")?;
+        config.web.validate()?;
+        config.devices.validate()?;
+        config.validate_themes()?;
+        config.alerts.validate()?;
+        config.validate_schedule()?;
+        config.validate_desktop_notifications()?;
+        config.validate_custom_values()?;
+        // Older versions accepted any value, so clamp rather than refuse to start
+        if !is_valid_refresh_interval(config.refresh_interval) {
+            let clamped = config
+                .refresh_interval
+                .clamp(MIN_REFRESH_INTERVAL as u64, MAX_REFRESH_INTERVAL as u64);
+            warn!(
+                "refresh_interval must be between {} and {} ms, using {} instead of {}",
+                MIN_REFRESH_INTERVAL, MAX_REFRESH_INTERVAL, clamped, config.refresh_interval
+            );
+            config.refresh_interval = clamped;
+        }
         Ok(config)
     }
+
+    fn validate_themes(&self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for theme in &self.themes {
+            theme.to_theme()?;
+            if !seen.insert(theme.id.as_str()) {
+                anyhow::bail!("Duplicate theme id '{}'", theme.id);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_schedule(&self) -> Result<()> {
+        let panels = self.devices.panels();
+        for rule in &self.schedule {
+            rule.validate()?;
+            if let Some(panel) = rule
+                .panels
+                .iter()
+                .find(|id| !panels.iter().any(|p| &p.id == *id))
+            {
+                anyhow::bail!("schedule '{}': unknown panel '{}'", rule.name, panel);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_desktop_notifications(&self) -> Result<()> {
+        let config = &self.desktop_notifications;
+        if !(1..=notifications::MAX_SECONDS).contains(&config.seconds) {
+            anyhow::bail!(
+                "desktop_notifications.seconds must be between 1 and {}",
+                notifications::MAX_SECONDS
+            );
+        }
+        let panels = self.devices.panels();
+        if let Some(panel) = config
+            .panels
+            .iter()
+            .find(|id| !panels.iter().any(|p| &p.id == *id))
+        {
+            anyhow::bail!("desktop_notifications: unknown panel '{}'", panel);
+        }
+        for rule in &config.rules {
+            rule.validate()?;
+        }
+        Ok(())
+    }
+
+    fn validate_custom_values(&self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for command in &self.custom_values {
+            command
+                .validate()
+                .with_context(|| format!("custom_values '{}'", command.key))?;
+            if !seen.insert(command.key.as_str()) {
+                anyhow::bail!("Duplicate custom value '{}'", command.key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the directory user themes are loaded from.
+    pub fn themes_dir(&self) -> PathBuf {
+        match &self.themes_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.state_dir).join("themes"),
+        }
+    }
+
+    /// Returns the directory custom faces are loaded from.
+    pub fn faces_dir(&self) -> PathBuf {
+        match &self.faces_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.state_dir).join("faces"),
+        }
+    }
 }
 
 impl Default for Config {
@@ -187,8 +766,16 @@ impl Default for Config {
             web: WebConfig::default(),
             dbus: DbusConfig::default(),
             state_dir: default_state_dir(),
+            faces_dir: None,
+            themes_dir: None,
+            themes: Vec::new(),
             refresh_interval: default_refresh_interval(),
             heartbeat: default_heartbeat(),
+            alerts: AlertsConfig::default(),
+            schedule: Vec::new(),
+            logind: LogindConfig::default(),
+            desktop_notifications: DesktopNotificationsConfig::default(),
+            custom_values: Vec::new(),
             devices: DevicesConfig::default(),
             canvas: CanvasConfig::default(),
         }
@@ -0,0 +1,298 @@
+//! Custom values set by other programs.
+//!
+//! Named numeric values are pushed over D-Bus (`SetCustomValue`) or HTTP
+//! (`POST /custom/<key>`), or read from the output of commands the daemon
+//! runs on an interval. They are shared by all panels, keep a history like
+//! the built-in metrics, and are shown by custom face widgets bound to
+//! `custom:<key>`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::sensors::data::HISTORY_SIZE;
+use crate::state::AppState;
+
+/// Values kept before new keys are rejected.
+pub const MAX_VALUES: usize = 64;
+
+/// Longest key, in bytes.
+const MAX_KEY_LEN: usize = 32;
+
+/// Longest unit, in bytes.
+const MAX_UNIT_LEN: usize = 16;
+
+/// Longest interval between runs of a command, in seconds.
+pub const MAX_INTERVAL: u64 = 86400;
+
+fn default_interval() -> u64 {
+    10
+}
+
+/// A command whose output sets a custom value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomCommand {
+    /// Key of the value
+    pub key: String,
+
+    /// Shell command printing the value, optionally followed by a unit
+    pub command: String,
+
+    /// Seconds between runs; the command is killed if it takes longer
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+
+    /// Unit, overriding the one printed by the command
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+impl CustomCommand {
+    /// Checks the key and interval.
+    pub fn validate(&self) -> Result<()> {
+        validate_key(&self.key)?;
+        if self.command.trim().is_empty() {
+            bail!("Command for '{}' is empty", self.key);
+        }
+        if !(1..=MAX_INTERVAL).contains(&self.interval) {
+            bail!("Interval must be between 1 and {} seconds", MAX_INTERVAL);
+        }
+        if let Some(unit) = &self.unit {
+            validate_unit(unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a key is 1 to 32 letters, digits, '_', '-' or '.'.
+pub fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty()
+        || key.len() > MAX_KEY_LEN
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        bail!(
+            "Invalid key '{}': use up to {} letters, digits, '_', '-' and '.'",
+            key,
+            MAX_KEY_LEN
+        );
+    }
+    Ok(())
+}
+
+fn validate_unit(unit: &str) -> Result<()> {
+    if unit.len() > MAX_UNIT_LEN || unit.chars().any(char::is_control) {
+        bail!("Invalid unit '{}'", unit);
+    }
+    Ok(())
+}
+
+/// A custom value and its history.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CustomValue {
+    /// Latest value
+    pub value: f64,
+    /// Unit shown after the value; may be empty
+    pub unit: String,
+    /// Values in the order they were set, newest last
+    pub history: VecDeque<f64>,
+}
+
+impl CustomValue {
+    /// Formats the value with at most two decimals, followed by its unit.
+    pub fn text(&self) -> String {
+        let number = format!("{:.2}", self.value);
+        let number = number.trim_end_matches('0').trim_end_matches('.');
+        let number = if number == "-0" { "0" } else { number };
+        match self.unit.chars().next() {
+            None => number.to_string(),
+            Some('%' | '°') => format!("{}{}", number, self.unit),
+            Some(_) => format!("{} {}", number, self.unit),
+        }
+    }
+}
+
+/// Custom values shared by all panels.
+#[derive(Debug, Default)]
+pub struct CustomValues {
+    values: Mutex<BTreeMap<String, CustomValue>>,
+}
+
+impl CustomValues {
+    /// Sets a value, adding it to the key's history.
+    pub fn set(&self, key: &str, value: f64, unit: &str) -> Result<()> {
+        validate_key(key)?;
+        validate_unit(unit)?;
+        if !value.is_finite() {
+            bail!("Value for '{}' is not a finite number", key);
+        }
+
+        let mut values = self.values.lock().unwrap();
+        if !values.contains_key(key) && values.len() >= MAX_VALUES {
+            bail!("Too many custom values (at most {})", MAX_VALUES);
+        }
+        let entry = values
+            .entry(key.to_string())
+            .or_insert_with(|| CustomValue {
+                value,
+                unit: String::new(),
+                history: VecDeque::with_capacity(HISTORY_SIZE),
+            });
+        entry.value = value;
+        entry.unit = unit.to_string();
+        if entry.history.len() == HISTORY_SIZE {
+            entry.history.pop_front();
+        }
+        entry.history.push_back(value);
+        Ok(())
+    }
+
+    /// Returns a copy of all values, sorted by key.
+    pub fn snapshot(&self) -> BTreeMap<String, CustomValue> {
+        self.values.lock().unwrap().clone()
+    }
+}
+
+/// Parses command output: a number, optionally followed by a unit, on the
+/// first non-empty line.
+pub fn parse_output(output: &str) -> Result<(f64, Option<String>)> {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .context("Command printed nothing")?;
+    let (number, unit) = match line.split_once(char::is_whitespace) {
+        Some((number, unit)) => (number, Some(unit.trim().to_string())),
+        None => (line, None),
+    };
+    let value = number
+        .parse::<f64>()
+        .with_context(|| format!("'{}' is not a number", number))?;
+    Ok((value, unit))
+}
+
+/// Runs a command once and returns its value and unit.
+async fn run_command(command: &CustomCommand) -> Result<(f64, Option<String>)> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(command.interval),
+        tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&command.command)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .context("Command timed out")?
+    .context("Failed to run command")?;
+    if !output.status.success() {
+        bail!("Command exited with {}", output.status);
+    }
+    let (value, unit) = parse_output(&String::from_utf8_lossy(&output.stdout))?;
+    Ok((value, command.unit.clone().or(unit)))
+}
+
+/// Runs a command on its interval, setting its value from the output.
+async fn command_loop(values: Arc<CustomValues>, command: CustomCommand) {
+    let mut interval = tokio::time::interval(Duration::from_secs(command.interval));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let result = run_command(&command).await.and_then(|(value, unit)| {
+            values.set(&command.key, value, unit.as_deref().unwrap_or_default())
+        });
+        match result {
+            Ok(()) => debug!("Custom value '{}' updated", command.key),
+            Err(e) => warn!("Custom value '{}': {:#}", command.key, e),
+        }
+    }
+}
+
+/// Runs every configured command until the daemon exits.
+pub async fn run_custom_commands(state: Arc<AppState>) {
+    let tasks: Vec<_> = state
+        .config()
+        .custom_values
+        .iter()
+        .cloned()
+        .map(|command| tokio::spawn(command_loop(state.custom_values().clone(), command)))
+        .collect();
+    for task in tasks {
+        let _ = task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_keeps_history_and_formats() {
+        let values = CustomValues::default();
+        for i in 0..HISTORY_SIZE + 5 {
+            values.set("fan", i as f64, "RPM").unwrap();
+        }
+        values.set("load", 42.5, "%").unwrap();
+        values.set("ratio", 0.126, "").unwrap();
+
+        let snapshot = values.snapshot();
+        let fan = &snapshot["fan"];
+        assert_eq!(fan.history.len(), HISTORY_SIZE);
+        assert_eq!(fan.history.back(), Some(&((HISTORY_SIZE + 4) as f64)));
+        assert_eq!(fan.text(), format!("{} RPM", HISTORY_SIZE + 4));
+        assert_eq!(snapshot["load"].text(), "42.5%");
+        assert_eq!(snapshot["ratio"].text(), "0.13");
+
+        assert!(values.set("bad key", 1.0, "").is_err());
+        assert!(values.set("nan", f64::NAN, "").is_err());
+        assert!(values.set("unit", 1.0, "a very long unit name").is_err());
+    }
+
+    #[test]
+    fn test_rejects_keys_past_the_limit() {
+        let values = CustomValues::default();
+        for i in 0..MAX_VALUES {
+            values.set(&format!("v{}", i), 1.0, "").unwrap();
+        }
+        assert!(values.set("one_more", 1.0, "").is_err());
+        // Existing keys can still be updated
+        values.set("v0", 2.0, "").unwrap();
+    }
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(parse_output("42\n").unwrap(), (42.0, None));
+        assert_eq!(
+            parse_output("\n  1200 RPM\nignored\n").unwrap(),
+            (1200.0, Some("RPM".to_string()))
+        );
+        assert!(parse_output("").is_err());
+        assert!(parse_output("fast").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_command_uses_configured_unit() {
+        let command = CustomCommand {
+            key: "answer".to_string(),
+            command: "echo 42 things".to_string(),
+            interval: 5,
+            unit: Some("°C".to_string()),
+        };
+        assert_eq!(
+            run_command(&command).await.unwrap(),
+            (42.0, Some("°C".to_string()))
+        );
+
+        let failing = CustomCommand {
+            command: "exit 3".to_string(),
+            ..command
+        };
+        assert!(run_command(&failing).await.is_err());
+    }
+}
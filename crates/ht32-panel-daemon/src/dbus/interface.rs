@@ -7,45 +7,103 @@ use std::sync::Arc;
 use ht32_panel_hw::{lcd::parse_hex_color, Orientation};
 use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
-use zbus::{interface, Connection};
+use zbus::{interface, object_server::SignalEmitter, Connection};
 
+use crate::carousel::PlaylistEntry;
 use crate::config::DbusBusType;
-use crate::state::AppState;
+use crate::notifications::{self, Severity};
+use crate::state::{AppState, PanelState};
 
 /// D-Bus signal types for state change notifications.
+///
+/// Each signal carries the id of the panel that changed.
 #[derive(Clone, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum DaemonSignals {
     /// Orientation was changed.
-    OrientationChanged,
+    OrientationChanged { panel: String },
     /// LED settings changed.
-    LedChanged,
+    LedChanged { panel: String },
     /// Display settings (theme, face, etc.) changed.
-    DisplaySettingsChanged,
+    DisplaySettingsChanged { panel: String },
     /// Complication option changed.
-    ComplicationOptionChanged,
+    ComplicationOptionChanged {
+        panel: String,
+        complication: String,
+        option: String,
+        value: String,
+    },
+    /// LCD device was attached or detached.
+    ConnectionChanged { panel: String },
+    /// A metric crossed a warning or critical threshold.
+    AlertRaised {
+        panel: String,
+        metric: String,
+        level: String,
+        value: f64,
+    },
+    /// A schedule rule became active or ended.
+    ScheduleChanged { panel: String },
+    /// A notification was queued for display.
+    NotificationPosted {
+        panel: String,
+        id: u32,
+        text: String,
+        severity: String,
+    },
 }
 
-/// D-Bus interface implementation for the HT32 Panel Daemon.
+impl DaemonSignals {
+    /// Returns the id of the panel this signal refers to.
+    pub fn panel(&self) -> &str {
+        match self {
+            DaemonSignals::OrientationChanged { panel }
+            | DaemonSignals::LedChanged { panel }
+            | DaemonSignals::DisplaySettingsChanged { panel }
+            | DaemonSignals::ConnectionChanged { panel }
+            | DaemonSignals::ScheduleChanged { panel }
+            | DaemonSignals::ComplicationOptionChanged { panel, .. }
+            | DaemonSignals::AlertRaised { panel, .. }
+            | DaemonSignals::NotificationPosted { panel, .. } => panel,
+        }
+    }
+}
+
+/// Object path of the default panel.
+const DEFAULT_OBJECT_PATH: &str = "/org/ht32panel/Daemon";
+
+/// Returns the object path serving the given panel.
+pub fn panel_object_path(panel: &str) -> String {
+    format!("{}/panels/{}", DEFAULT_OBJECT_PATH, panel)
+}
+
+/// D-Bus interface implementation for one panel of the HT32 Panel Daemon.
 pub struct Daemon1Interface {
-    state: Arc<AppState>,
+    app: Arc<AppState>,
+    state: Arc<PanelState>,
     signal_tx: broadcast::Sender<DaemonSignals>,
     shutdown_tx: tokio::sync::mpsc::Sender<()>,
 }
 
 impl Daemon1Interface {
-    /// Creates a new D-Bus interface.
+    /// Creates a new D-Bus interface for a panel.
     pub fn new(
-        state: Arc<AppState>,
+        app: Arc<AppState>,
+        state: Arc<PanelState>,
         signal_tx: broadcast::Sender<DaemonSignals>,
         shutdown_tx: tokio::sync::mpsc::Sender<()>,
     ) -> Self {
         Self {
+            app,
             state,
             signal_tx,
             shutdown_tx,
         }
     }
+
+    fn panel(&self) -> String {
+        self.state.id().to_string()
+    }
 }
 
 #[interface(name = "org.ht32panel.Daemon1")]
@@ -61,7 +119,9 @@ impl Daemon1Interface {
             .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
 
         // Emit signal
-        let _ = self.signal_tx.send(DaemonSignals::OrientationChanged);
+        let _ = self.signal_tx.send(DaemonSignals::OrientationChanged {
+            panel: self.panel(),
+        });
 
         debug!("D-Bus: SetOrientation({})", orientation);
         Ok(())
@@ -85,13 +145,64 @@ impl Daemon1Interface {
         Ok(())
     }
 
+    /// Sets the display refresh interval in milliseconds (500-10000).
+    fn set_refresh_interval(&self, ms: u32) -> zbus::fdo::Result<()> {
+        self.state
+            .set_refresh_interval(ms)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+
+        debug!("D-Bus: SetRefreshInterval({})", ms);
+        Ok(())
+    }
+
+    /// Gets the display refresh interval in milliseconds.
+    fn get_refresh_interval(&self) -> u32 {
+        self.state.refresh_interval_ms()
+    }
+
+    /// Sets the display brightness in percent (0-100).
+    fn set_brightness(&self, percent: u8) -> zbus::fdo::Result<()> {
+        self.state
+            .set_brightness(percent)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+
+        debug!("D-Bus: SetBrightness({})", percent);
+        Ok(())
+    }
+
+    /// Gets the display brightness in percent.
+    fn get_brightness(&self) -> u8 {
+        self.state.brightness()
+    }
+
+    /// Switches the display off (black frames) or back on.
+    fn set_display_off(&self, off: bool) {
+        self.state.set_display_off(off);
+
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+
+        debug!("D-Bus: SetDisplayOff({})", off);
+    }
+
     /// Sets the display face.
     fn set_face(&self, face: &str) -> zbus::fdo::Result<()> {
         self.state
             .set_face(face)
             .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
 
-        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged);
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
         debug!("D-Bus: SetFace({})", face);
         Ok(())
     }
@@ -101,6 +212,82 @@ impl Daemon1Interface {
         self.state.face_name()
     }
 
+    /// Sets the carousel playlist as (face, theme, seconds) entries.
+    /// An empty theme keeps the current one.
+    fn set_playlist(&self, entries: Vec<(String, String, u32)>) -> zbus::fdo::Result<()> {
+        let entries = entries
+            .into_iter()
+            .map(|(face, theme, duration)| PlaylistEntry {
+                face,
+                theme: (!theme.is_empty()).then_some(theme),
+                duration,
+            })
+            .collect();
+        self.state
+            .set_playlist(entries)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+        debug!("D-Bus: SetPlaylist");
+        Ok(())
+    }
+
+    /// Gets the carousel playlist as (face, theme, seconds) entries.
+    fn get_playlist(&self) -> Vec<(String, String, u32)> {
+        self.state
+            .playlist()
+            .into_iter()
+            .map(|e| (e.face, e.theme.unwrap_or_default(), e.duration))
+            .collect()
+    }
+
+    /// Starts rotating through the playlist.
+    fn start_carousel(&self) -> zbus::fdo::Result<()> {
+        self.state
+            .start_carousel()
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+        debug!("D-Bus: StartCarousel");
+        Ok(())
+    }
+
+    /// Stops the carousel on the current face.
+    fn stop_carousel(&self) {
+        if self.state.stop_carousel() {
+            let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: self.panel(),
+            });
+        }
+        debug!("D-Bus: StopCarousel");
+    }
+
+    /// Shows the next face of the playlist.
+    fn next_face(&self) -> zbus::fdo::Result<()> {
+        self.state
+            .next_face()
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+        debug!("D-Bus: NextFace");
+        Ok(())
+    }
+
+    /// Shows the previous face of the playlist.
+    fn previous_face(&self) -> zbus::fdo::Result<()> {
+        self.state
+            .previous_face()
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
+        debug!("D-Bus: PreviousFace");
+        Ok(())
+    }
+
     /// Gets the current color theme name.
     fn get_theme(&self) -> String {
         self.state.theme_name()
@@ -112,14 +299,16 @@ impl Daemon1Interface {
             .set_theme(name)
             .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
 
-        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged);
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
         debug!("D-Bus: SetTheme({})", name);
         Ok(())
     }
 
     /// Lists available color themes (IDs only, for backwards compatibility).
     fn list_themes(&self) -> Vec<String> {
-        self.state
+        self.app
             .available_themes()
             .iter()
             .map(|t| t.id.to_string())
@@ -129,13 +318,21 @@ impl Daemon1Interface {
     /// Lists available color themes with display names.
     /// Returns JSON-encoded theme data.
     fn list_themes_detailed(&self) -> Vec<String> {
-        self.state
+        self.app
             .available_themes()
             .iter()
             .map(|t| {
                 serde_json::json!({
                     "id": t.id,
-                    "display_name": t.display_name
+                    "display_name": t.display_name,
+                    "builtin": t.builtin,
+                    "primary": format!("#{:06X}", t.theme.primary),
+                    "secondary": format!("#{:06X}", t.theme.secondary),
+                    "text": format!("#{:06X}", t.theme.text),
+                    "background": format!("#{:06X}", t.theme.background),
+                    "accent": format!("#{:06X}", t.theme.accent),
+                    "warning": format!("#{:06X}", t.theme.warning),
+                    "critical": format!("#{:06X}", t.theme.critical),
                 })
                 .to_string()
             })
@@ -197,7 +394,9 @@ impl Daemon1Interface {
             .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
 
         // Emit signal
-        let _ = self.signal_tx.send(DaemonSignals::LedChanged);
+        let _ = self.signal_tx.send(DaemonSignals::LedChanged {
+            panel: self.panel(),
+        });
 
         debug!("D-Bus: SetLed({}, {}, {})", theme, intensity, speed);
         Ok(())
@@ -211,7 +410,9 @@ impl Daemon1Interface {
             .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
 
         // Emit signal
-        let _ = self.signal_tx.send(DaemonSignals::LedChanged);
+        let _ = self.signal_tx.send(DaemonSignals::LedChanged {
+            panel: self.panel(),
+        });
 
         debug!("D-Bus: LedOff");
         Ok(())
@@ -232,6 +433,148 @@ impl Daemon1Interface {
         Ok(())
     }
 
+    /// Lists the schedule rules of this panel as (name, start, end) entries.
+    /// The end is empty for rules that last until the next rule starts.
+    fn list_schedules(&self) -> Vec<(String, String, String)> {
+        self.state
+            .schedule_rules()
+            .into_iter()
+            .map(|rule| (rule.name, rule.start, rule.end.unwrap_or_default()))
+            .collect()
+    }
+
+    /// Draws a notification over the face for a number of seconds (0 for
+    /// the default of 5). Severity is info, warning or critical.
+    /// Returns the notification id.
+    fn show_notification(
+        &self,
+        text: &str,
+        severity: &str,
+        seconds: u32,
+    ) -> zbus::fdo::Result<u32> {
+        let severity: Severity = severity
+            .parse()
+            .map_err(|e: anyhow::Error| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+        let seconds = if seconds == 0 {
+            notifications::DEFAULT_SECONDS
+        } else {
+            seconds
+        };
+        let notification = self
+            .state
+            .show_notification(text, severity, seconds)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+
+        let _ = self.signal_tx.send(DaemonSignals::NotificationPosted {
+            panel: self.panel(),
+            id: notification.id,
+            text: notification.text.clone(),
+            severity: severity.to_string(),
+        });
+
+        debug!(
+            "D-Bus: ShowNotification({}, {}, {})",
+            text, severity, seconds
+        );
+        Ok(notification.id)
+    }
+
+    /// Lists the notification history, oldest first, as (id, time, severity,
+    /// text, seconds) entries. The time is in RFC 3339 format.
+    fn list_notifications(&self) -> Vec<(u32, String, String, String, u32)> {
+        self.state
+            .notification_history()
+            .into_iter()
+            .map(|n| {
+                (
+                    n.id,
+                    n.time.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+                    n.severity.to_string(),
+                    n.text,
+                    n.seconds,
+                )
+            })
+            .collect()
+    }
+
+    /// Sets a custom value shown by faces bound to `custom:<key>`. The
+    /// value is shared by all panels; the unit may be empty.
+    fn set_custom_value(&self, key: &str, value: f64, unit: &str) -> zbus::fdo::Result<()> {
+        self.app
+            .custom_values()
+            .set(key, value, unit)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+        debug!("D-Bus: SetCustomValue({}, {}, {})", key, value, unit);
+        Ok(())
+    }
+
+    /// Lists the custom values as (key, value, unit) entries, sorted by key.
+    fn list_custom_values(&self) -> Vec<(String, f64, String)> {
+        self.app
+            .custom_values()
+            .snapshot()
+            .into_iter()
+            .map(|(key, v)| (key, v.value, v.unit))
+            .collect()
+    }
+
+    // Signals
+
+    /// Emitted when the display orientation changes.
+    #[zbus(signal, name = "OrientationChanged")]
+    async fn orientation_signal(emitter: &SignalEmitter<'_>, orientation: &str)
+        -> zbus::Result<()>;
+
+    /// Emitted when the LED theme, intensity or speed changes.
+    #[zbus(signal)]
+    async fn led_changed(
+        emitter: &SignalEmitter<'_>,
+        theme: u8,
+        intensity: u8,
+        speed: u8,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when the face, color theme, refresh interval, brightness,
+    /// carousel or enabled complications change.
+    #[zbus(signal)]
+    async fn display_settings_changed(
+        emitter: &SignalEmitter<'_>,
+        face: &str,
+        theme: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a complication option is set.
+    #[zbus(signal)]
+    async fn complication_option_changed(
+        emitter: &SignalEmitter<'_>,
+        complication_id: &str,
+        option_id: &str,
+        value: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a metric crosses a warning or critical threshold.
+    #[zbus(signal)]
+    async fn alert_raised(
+        emitter: &SignalEmitter<'_>,
+        metric: &str,
+        level: &str,
+        value: f64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a schedule rule becomes active or ends.
+    /// The rule is empty when no rule is active.
+    #[zbus(signal)]
+    async fn schedule_changed(emitter: &SignalEmitter<'_>, rule: &str) -> zbus::Result<()>;
+
+    /// Emitted when a notification is queued for display.
+    #[zbus(signal)]
+    async fn notification_posted(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        text: &str,
+        severity: &str,
+    ) -> zbus::Result<()>;
+
     // Properties
 
     /// Whether the LCD device is connected.
@@ -243,7 +586,13 @@ impl Daemon1Interface {
     /// Whether the web UI is enabled.
     #[zbus(property)]
     fn web_enabled(&self) -> bool {
-        self.state.is_web_enabled()
+        self.app.is_web_enabled()
+    }
+
+    /// Id of the panel served by this object.
+    #[zbus(property)]
+    fn panel_id(&self) -> String {
+        self.panel()
     }
 
     /// Current display orientation.
@@ -282,9 +631,51 @@ impl Daemon1Interface {
         self.state.face_name()
     }
 
+    /// Current display refresh interval in milliseconds.
+    #[zbus(property)]
+    fn refresh_interval(&self) -> u32 {
+        self.state.refresh_interval_ms()
+    }
+
+    /// Current display brightness in percent.
+    #[zbus(property)]
+    fn brightness(&self) -> u8 {
+        self.state.brightness()
+    }
+
+    /// Whether the display is switched off.
+    #[zbus(property)]
+    fn display_off(&self) -> bool {
+        self.state.is_display_off()
+    }
+
+    /// Whether the face carousel is rotating.
+    #[zbus(property)]
+    fn carousel_running(&self) -> bool {
+        self.state.is_carousel_running()
+    }
+
+    /// Index of the current carousel playlist entry.
+    #[zbus(property)]
+    fn carousel_index(&self) -> u32 {
+        self.state.carousel_index() as u32
+    }
+
+    /// Name of the active schedule rule, or empty if none is active.
+    #[zbus(property)]
+    fn active_schedule(&self) -> String {
+        self.state.active_schedule().unwrap_or_default()
+    }
+
     /// Lists all available network interfaces.
     fn list_network_interfaces(&self) -> Vec<String> {
-        self.state.list_network_interfaces()
+        self.app.list_network_interfaces()
+    }
+
+    /// Lists the ids of all panels, default first.
+    /// Each panel is served at /org/ht32panel/Daemon/panels/<id>.
+    fn list_panels(&self) -> Vec<String> {
+        self.app.panel_ids()
     }
 
     /// Lists available complications for the current face.
@@ -313,62 +704,62 @@ impl Daemon1Interface {
             .map(|c| {
                 let is_enabled = enabled.contains(&c.id);
                 // Get current option values
-                let options: Vec<serde_json::Value> = c.options.iter().map(|opt| {
-                    let current_value = self.state.get_complication_option(&c.id, &opt.id)
-                        .unwrap_or_else(|| opt.default_value.clone());
-
-                    match &opt.option_type {
-                        crate::faces::ComplicationOptionType::Choice(choices) => {
-                            // For network interface, dynamically get available interfaces
-                            let choice_list: Vec<serde_json::Value> = if c.id == "network" && opt.id == "interface" {
-                                let mut ifaces: Vec<serde_json::Value> = vec![
-                                    serde_json::json!({"value": "auto", "label": "Auto-detect"})
-                                ];
-                                for iface in self.state.list_network_interfaces() {
-                                    ifaces.push(serde_json::json!({"value": iface, "label": iface}));
-                                }
-                                ifaces
-                            } else {
-                                choices.iter().map(|ch| {
-                                    serde_json::json!({"value": ch.value, "label": ch.label})
-                                }).collect()
-                            };
-                            serde_json::json!({
-                                "id": opt.id,
-                                "name": opt.name,
-                                "description": opt.description,
-                                "current_value": current_value,
-                                "type": "choice",
-                                "choices": choice_list
-                            })
-                        }
-                        crate::faces::ComplicationOptionType::Boolean => {
-                            serde_json::json!({
-                                "id": opt.id,
-                                "name": opt.name,
-                                "description": opt.description,
-                                "current_value": current_value,
-                                "type": "boolean",
-                                "choices": [
-                                    {"value": "true", "label": "Yes"},
-                                    {"value": "false", "label": "No"}
-                                ]
-                            })
+                let options: Vec<serde_json::Value> = c
+                    .options
+                    .iter()
+                    .map(|opt| {
+                        let current_value = self
+                            .state
+                            .get_complication_option(&c.id, &opt.id)
+                            .unwrap_or_else(|| opt.default_value.clone());
+
+                        match &opt.option_type {
+                            crate::faces::ComplicationOptionType::Choice(choices) => {
+                                let choice_list: Vec<serde_json::Value> = self
+                                    .app
+                                    .complication_choices(&c.id, &opt.id, choices)
+                                    .into_iter()
+                                    .map(|ch| {
+                                        serde_json::json!({"value": ch.value, "label": ch.label})
+                                    })
+                                    .collect();
+                                serde_json::json!({
+                                    "id": opt.id,
+                                    "name": opt.name,
+                                    "description": opt.description,
+                                    "current_value": current_value,
+                                    "type": "choice",
+                                    "choices": choice_list
+                                })
+                            }
+                            crate::faces::ComplicationOptionType::Boolean => {
+                                serde_json::json!({
+                                    "id": opt.id,
+                                    "name": opt.name,
+                                    "description": opt.description,
+                                    "current_value": current_value,
+                                    "type": "boolean",
+                                    "choices": [
+                                        {"value": "true", "label": "Yes"},
+                                        {"value": "false", "label": "No"}
+                                    ]
+                                })
+                            }
+                            crate::faces::ComplicationOptionType::Range { min, max, step } => {
+                                serde_json::json!({
+                                    "id": opt.id,
+                                    "name": opt.name,
+                                    "description": opt.description,
+                                    "current_value": current_value,
+                                    "type": "range",
+                                    "min": min,
+                                    "max": max,
+                                    "step": step
+                                })
+                            }
                         }
-                        crate::faces::ComplicationOptionType::Range { min, max, step } => {
-                            serde_json::json!({
-                                "id": opt.id,
-                                "name": opt.name,
-                                "description": opt.description,
-                                "current_value": current_value,
-                                "type": "range",
-                                "min": min,
-                                "max": max,
-                                "step": step
-                            })
-                        }
-                    }
-                }).collect();
+                    })
+                    .collect();
 
                 serde_json::json!({
                     "id": c.id,
@@ -377,7 +768,8 @@ impl Daemon1Interface {
                     "enabled": is_enabled,
                     "options": options,
                     "face": face_name
-                }).to_string()
+                })
+                .to_string()
             })
             .collect()
     }
@@ -392,7 +784,9 @@ impl Daemon1Interface {
         self.state
             .set_complication_enabled(complication_id, true)
             .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
-        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged);
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
         debug!("D-Bus: EnableComplication({})", complication_id);
         Ok(())
     }
@@ -402,7 +796,9 @@ impl Daemon1Interface {
         self.state
             .set_complication_enabled(complication_id, false)
             .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
-        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged);
+        let _ = self.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: self.panel(),
+        });
         debug!("D-Bus: DisableComplication({})", complication_id);
         Ok(())
     }
@@ -435,7 +831,12 @@ impl Daemon1Interface {
             .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
         let _ = self
             .signal_tx
-            .send(DaemonSignals::ComplicationOptionChanged);
+            .send(DaemonSignals::ComplicationOptionChanged {
+                panel: self.panel(),
+                complication: complication_id.to_string(),
+                option: option_id.to_string(),
+                value: value.to_string(),
+            });
         debug!(
             "D-Bus: SetComplicationOption({}, {}, {})",
             complication_id, option_id, value
@@ -482,22 +883,120 @@ async fn connect_to_bus(bus_type: DbusBusType) -> anyhow::Result<(Connection, &'
     }
 }
 
+/// Emits the D-Bus signal and `PropertiesChanged` for a state change of an object.
+async fn emit_signal(
+    connection: &Connection,
+    path: &str,
+    signal: &DaemonSignals,
+) -> zbus::Result<()> {
+    let iface_ref = connection
+        .object_server()
+        .interface::<_, Daemon1Interface>(path)
+        .await?;
+    let emitter = iface_ref.signal_emitter();
+    let iface = iface_ref.get().await;
+
+    match signal {
+        DaemonSignals::OrientationChanged { .. } => {
+            let orientation = iface.state.orientation().to_string();
+            Daemon1Interface::orientation_signal(emitter, &orientation).await?;
+            iface.orientation_changed(emitter).await
+        }
+        DaemonSignals::LedChanged { .. } => {
+            let (theme, intensity, speed) = iface.state.led_settings();
+            Daemon1Interface::led_changed(emitter, theme, intensity, speed).await?;
+            iface.led_theme_changed(emitter).await?;
+            iface.led_intensity_changed(emitter).await?;
+            iface.led_speed_changed(emitter).await
+        }
+        DaemonSignals::DisplaySettingsChanged { .. } => {
+            let face = iface.state.face_name();
+            let theme = iface.state.theme_name();
+            Daemon1Interface::display_settings_changed(emitter, &face, &theme).await?;
+            iface.face_changed(emitter).await?;
+            iface.theme_changed(emitter).await?;
+            iface.refresh_interval_changed(emitter).await?;
+            iface.brightness_changed(emitter).await?;
+            iface.display_off_changed(emitter).await?;
+            iface.carousel_running_changed(emitter).await?;
+            iface.carousel_index_changed(emitter).await
+        }
+        DaemonSignals::ComplicationOptionChanged {
+            complication,
+            option,
+            value,
+            ..
+        } => {
+            Daemon1Interface::complication_option_changed(emitter, complication, option, value)
+                .await
+        }
+        DaemonSignals::ConnectionChanged { .. } => iface.connected_changed(emitter).await,
+        DaemonSignals::AlertRaised {
+            metric,
+            level,
+            value,
+            ..
+        } => Daemon1Interface::alert_raised(emitter, metric, level, *value).await,
+        DaemonSignals::ScheduleChanged { .. } => {
+            let rule = iface.state.active_schedule().unwrap_or_default();
+            Daemon1Interface::schedule_changed(emitter, &rule).await?;
+            iface.active_schedule_changed(emitter).await
+        }
+        DaemonSignals::NotificationPosted {
+            id, text, severity, ..
+        } => Daemon1Interface::notification_posted(emitter, *id, text, severity).await,
+    }
+}
+
 /// Runs the D-Bus server.
+///
+/// The default panel is served at `/org/ht32panel/Daemon`, and every panel
+/// at `/org/ht32panel/Daemon/panels/<id>`.
 pub async fn run_dbus_server(
     state: Arc<AppState>,
     signal_tx: broadcast::Sender<DaemonSignals>,
     shutdown_tx: tokio::sync::mpsc::Sender<()>,
     bus_type: DbusBusType,
 ) -> anyhow::Result<Connection> {
-    let interface = Daemon1Interface::new(state, signal_tx, shutdown_tx);
-
     let (connection, bus_name) = connect_to_bus(bus_type).await?;
 
-    connection
-        .object_server()
-        .at("/org/ht32panel/Daemon", interface)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to register object: {}", e))?;
+    let default_panel = state.default_panel().clone();
+    let mut objects = vec![(DEFAULT_OBJECT_PATH.to_string(), default_panel)];
+    for panel in state.panels() {
+        objects.push((panel_object_path(panel.id()), panel.clone()));
+    }
+
+    let mut paths = Vec::with_capacity(objects.len());
+    for (path, panel) in objects {
+        paths.push((path.clone(), panel.id().to_string()));
+        let interface =
+            Daemon1Interface::new(state.clone(), panel, signal_tx.clone(), shutdown_tx.clone());
+        connection
+            .object_server()
+            .at(path.as_str(), interface)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to register object {}: {}", path, e))?;
+    }
+
+    // Forward state changes, whether made over D-Bus, the web UI or by the
+    // daemon itself, as D-Bus signals and property changes
+    let mut signal_rx = signal_tx.subscribe();
+    let emitter = connection.clone();
+    tokio::spawn(async move {
+        loop {
+            let signal = match signal_rx.recv().await {
+                Ok(signal) => signal,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            for (path, _) in paths.iter().filter(|(_, id)| id == signal.panel()) {
+                let result = emit_signal(&emitter, path, &signal).await;
+                if let Err(e) = result {
+                    warn!("Failed to emit signal on {}: {}", path, e);
+                }
+            }
+        }
+    });
 
     connection
         .request_name("org.ht32panel.Daemon")
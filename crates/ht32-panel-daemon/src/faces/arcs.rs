@@ -324,7 +324,7 @@ impl Face for ArcsFace {
                 large_radius,
                 large_stroke,
                 data.cpu_percent,
-                colors.primary,
+                theme.alert_color(data.alerts.cpu_percent, colors.primary),
                 colors.arc_bg,
             );
             canvas.draw_text(center_x - 10, cpu_cy - 6, "CPU", FONT_TINY, colors.dim);
@@ -335,7 +335,7 @@ impl Face for ArcsFace {
                 cpu_cy + 4,
                 &cpu_text,
                 FONT_SMALL,
-                colors.text,
+                theme.alert_color(data.alerts.cpu_percent, colors.text),
             );
             y += large_arc_height;
 
@@ -348,7 +348,7 @@ impl Face for ArcsFace {
                 large_radius,
                 large_stroke,
                 data.ram_percent,
-                colors.secondary,
+                theme.alert_color(data.alerts.ram_percent, colors.secondary),
                 colors.arc_bg,
             );
             canvas.draw_text(center_x - 12, ram_cy - 6, "RAM", FONT_TINY, colors.dim);
@@ -359,7 +359,7 @@ impl Face for ArcsFace {
                 ram_cy + 4,
                 &ram_text,
                 FONT_SMALL,
-                colors.text,
+                theme.alert_color(data.alerts.ram_percent, colors.text),
             );
             y += large_arc_height;
 
@@ -581,7 +581,7 @@ impl Face for ArcsFace {
                 gauge_radius,
                 stroke,
                 data.cpu_percent,
-                colors.primary,
+                theme.alert_color(data.alerts.cpu_percent, colors.primary),
                 colors.arc_bg,
             );
             canvas.draw_text(cpu_cx - 10, gauge_y - 8, "CPU", FONT_TINY, colors.dim);
@@ -592,7 +592,7 @@ impl Face for ArcsFace {
                 gauge_y + 2,
                 &cpu_text,
                 FONT_NORMAL,
-                colors.text,
+                theme.alert_color(data.alerts.cpu_percent, colors.text),
             );
 
             // Base element: RAM gauge (always shown)
@@ -604,7 +604,7 @@ impl Face for ArcsFace {
                 gauge_radius,
                 stroke,
                 data.ram_percent,
-                colors.secondary,
+                theme.alert_color(data.alerts.ram_percent, colors.secondary),
                 colors.arc_bg,
             );
             canvas.draw_text(ram_cx - 12, gauge_y - 8, "RAM", FONT_TINY, colors.dim);
@@ -615,7 +615,7 @@ impl Face for ArcsFace {
                 gauge_y + 2,
                 &ram_text,
                 FONT_NORMAL,
-                colors.text,
+                theme.alert_color(data.alerts.ram_percent, colors.text),
             );
 
             let io_x = ram_cx + gauge_radius as i32 + 40;
@@ -29,6 +29,10 @@
 //! NET  D:1.2M U:0.8M
 //! [__..--==++**##**++==..]
 //! ```
+//!
+//! The CPU Cores complication replaces the CPU bar with one sparkline
+//! character per core (`[#*=._.#+]`) and adds a line with the average
+//! frequency and load averages.
 
 use super::{
     complication_names, complication_options, complications, date_formats, draw_mini_analog_clock,
@@ -96,12 +100,44 @@ fn ascii_bar(percent: f64, width: usize) -> String {
     format!("[{}{}]", "#".repeat(filled), ".".repeat(empty))
 }
 
+/// Sparkline characters from lowest to highest.
+const CHARS: [char; 7] = ['_', '.', '-', '=', '+', '*', '#'];
+
+/// Returns the sparkline character for a value between 0 and 1.
+fn spark_char(normalized: f64) -> char {
+    let level = (normalized.clamp(0.0, 1.0) * (CHARS.len() - 1) as f64).round() as usize;
+    CHARS[level.min(CHARS.len() - 1)]
+}
+
+/// Creates a per-core usage strip with one sparkline character per core.
+/// Cores are averaged in groups when there are more than `width`.
+/// Returns something like "[#*=._.#+]"
+fn ascii_cores(cores: &[f64], width: usize) -> String {
+    let group = cores.len().div_ceil(width.max(1)).max(1);
+    let strip: String = cores
+        .chunks(group)
+        .map(|chunk| spark_char(chunk.iter().sum::<f64>() / chunk.len() as f64 / 100.0))
+        .collect();
+    format!("[{}]", strip)
+}
+
+/// Formats the average frequency and load averages for the per-core complication.
+/// Returns something like "2.4GHz  Load 0.52 0.61 0.70"
+fn cpu_details(data: &SystemData) -> String {
+    let mut parts = Vec::new();
+    if let Some(mhz) = data.cpu_freq_avg_mhz() {
+        parts.push(format!("{}Hz", SystemData::format_freq_compact(mhz)));
+    }
+    if let Some([one, five, fifteen]) = data.load_avg {
+        parts.push(format!("Load {:.2} {:.2} {:.2}", one, five, fifteen));
+    }
+    parts.join("  ")
+}
+
 /// Creates an ASCII sparkline from historical data.
 /// Uses ASCII characters to represent different heights:
 /// `_` (lowest), `.`, `-`, `=`, `+`, `*`, `#` (highest)
 fn ascii_sparkline(data: &std::collections::VecDeque<f64>, max_value: f64, width: usize) -> String {
-    const CHARS: [char; 7] = ['_', '.', '-', '=', '+', '*', '#'];
-
     if data.is_empty() || max_value <= 0.0 {
         return "_".repeat(width);
     }
@@ -121,9 +157,7 @@ fn ascii_sparkline(data: &std::collections::VecDeque<f64>, max_value: f64, width
         };
 
         let value = data.get(data_idx).copied().unwrap_or(0.0);
-        let normalized = (value / max_value).clamp(0.0, 1.0);
-        let level = (normalized * (CHARS.len() - 1) as f64).round() as usize;
-        result.push(CHARS[level.min(CHARS.len() - 1)]);
+        result.push(spark_char(value / max_value));
     }
 
     result
@@ -158,6 +192,7 @@ impl Face for AsciiFace {
             complications::network(true),
             complications::disk_io(true),
             complications::cpu_temp(true),
+            complications::cpu_cores(false),
         ]
     }
 
@@ -176,6 +211,9 @@ impl Face for AsciiFace {
         let bar_chars = if portrait { 10 } else { 16 };
 
         let is_enabled = |id: &str| complications.is_enabled(self.name(), id, true);
+        let show_cores =
+            complications.is_enabled(self.name(), complication_names::CPU_CORES, false)
+                && !data.cpu_cores.is_empty();
 
         // Get time format option
         let time_format = complications
@@ -292,7 +330,7 @@ impl Face for AsciiFace {
                         y,
                         &temp_val,
                         FONT_SMALL,
-                        colors.text,
+                        theme.alert_color(data.alerts.cpu_temp, colors.text),
                     );
                     y += line_height + section_spacing;
                 }
@@ -302,16 +340,31 @@ impl Face for AsciiFace {
             let cpu_label = format!("CPU: {:2.0}%", data.cpu_percent);
             canvas.draw_text(margin, y, &cpu_label, FONT_SMALL, colors.dim);
             y += line_height;
-            let cpu_bar = ascii_bar(data.cpu_percent, bar_width);
-            canvas.draw_text(margin, y, &cpu_bar, FONT_SMALL, colors.text);
+            let cpu_bar = if show_cores {
+                ascii_cores(&data.cpu_cores, bar_width)
+            } else {
+                ascii_bar(data.cpu_percent, bar_width)
+            };
+            let cpu_color = theme.alert_color(data.alerts.cpu_percent, colors.text);
+            canvas.draw_text(margin, y, &cpu_bar, FONT_SMALL, cpu_color);
             y += line_height + section_spacing;
 
+            // Complication: CPU frequency and load, below the per-core strip
+            if show_cores {
+                let details = cpu_details(data);
+                if !details.is_empty() {
+                    canvas.draw_text(margin, y, &details, FONT_SMALL, colors.dim);
+                    y += line_height + section_spacing;
+                }
+            }
+
             // RAM: label line, then bar on next line
             let ram_label = format!("RAM: {:2.0}%", data.ram_percent);
             canvas.draw_text(margin, y, &ram_label, FONT_SMALL, colors.dim);
             y += line_height;
             let ram_bar = ascii_bar(data.ram_percent, bar_width);
-            canvas.draw_text(margin, y, &ram_bar, FONT_SMALL, colors.text);
+            let ram_color = theme.alert_color(data.alerts.ram_percent, colors.text);
+            canvas.draw_text(margin, y, &ram_bar, FONT_SMALL, ram_color);
             y += line_height + section_spacing;
 
             // DSK: label line, then sparkline on next line
@@ -437,8 +490,12 @@ impl Face for AsciiFace {
                 }
             }
 
-            // Base element: CPU bar with optional temperature (always shown)
-            let cpu_bar = ascii_bar(data.cpu_percent, bar_chars);
+            // Base element: CPU bar (or per-core strip) with optional temperature
+            let cpu_bar = if show_cores {
+                ascii_cores(&data.cpu_cores, bar_chars)
+            } else {
+                ascii_bar(data.cpu_percent, bar_chars)
+            };
             let cpu_text = if is_enabled(complication_names::CPU_TEMP) {
                 if let Some(temp) = data.cpu_temp {
                     format!("CPU {} {:3.0}%  {:.0}°C", cpu_bar, data.cpu_percent, temp)
@@ -448,13 +505,27 @@ impl Face for AsciiFace {
             } else {
                 format!("CPU {} {:3.0}%", cpu_bar, data.cpu_percent)
             };
-            canvas.draw_text(margin, y, &cpu_text, FONT_NORMAL, colors.text);
+            // The line turns the color of its most severe metric
+            let cpu_level = data.alerts.cpu_percent.max(data.alerts.cpu_temp);
+            let cpu_color = theme.alert_color(cpu_level, colors.text);
+            canvas.draw_text(margin, y, &cpu_text, FONT_NORMAL, cpu_color);
             y += canvas.line_height(FONT_NORMAL) + 1;
 
+            // Complication: CPU frequency and load, aligned under the strip
+            if show_cores {
+                let details = cpu_details(data);
+                if !details.is_empty() {
+                    let indent = canvas.text_width("CPU ", FONT_NORMAL);
+                    canvas.draw_text(margin + indent, y, &details, FONT_SMALL, colors.dim);
+                    y += canvas.line_height(FONT_SMALL) + 1;
+                }
+            }
+
             // Base element: RAM bar (always shown)
             let ram_bar = ascii_bar(data.ram_percent, bar_chars);
             let ram_text = format!("RAM {} {:3.0}%", ram_bar, data.ram_percent);
-            canvas.draw_text(margin, y, &ram_text, FONT_NORMAL, colors.text);
+            let ram_color = theme.alert_color(data.alerts.ram_percent, colors.text);
+            canvas.draw_text(margin, y, &ram_text, FONT_NORMAL, ram_color);
             y += canvas.line_height(FONT_NORMAL) + 2;
 
             // Complication: Disk I/O
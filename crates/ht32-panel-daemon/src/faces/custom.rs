@@ -0,0 +1,1043 @@
+//! Custom faces loaded from declarative layout files.
+//!
+//! A face definition is a TOML or JSON file listing widgets, each bound to a
+//! `SystemData` field and placed separately for landscape and portrait:
+//!
+//! ```toml
+//! display_name = "Minimal"
+//!
+//! [[widgets]]
+//! type = "text"
+//! value = "time"
+//! complication = "time"
+//! size = 32
+//! align = "center"
+//! landscape = { x = 160, y = 20 }
+//! portrait = { x = 85, y = 40 }
+//!
+//! [[widgets]]
+//! type = "gauge"
+//! value = "cpu_percent"
+//! landscape = { x = 60, y = 110, radius = 40 }
+//! ```
+//!
+//! Widgets without a placement for the current orientation are not drawn.
+//! Widgets bound to a complication are only drawn while it is enabled.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use super::{
+    complication_names, complication_options, complications, date_formats, draw_mini_analog_clock,
+    parse_rgb, time_formats, Complication, EnabledComplications, Face, Theme,
+};
+use crate::custom_values::{self, CustomValue};
+use crate::rendering::Canvas;
+use crate::sensors::data::{AlertLevel, SystemData};
+
+/// Landscape canvas size that placements are checked against.
+const LANDSCAPE: (u32, u32) = (320, 170);
+
+/// Portrait canvas size that placements are checked against.
+const PORTRAIT: (u32, u32) = (170, 320);
+
+/// Custom faces loaded at startup.
+static REGISTRY: RwLock<Vec<Arc<FaceDefinition>>> = RwLock::new(Vec::new());
+
+/// A face defined in a layout file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaceDefinition {
+    /// Face id; defaults to the file name without extension.
+    #[serde(default)]
+    pub name: String,
+
+    /// Human-readable name; defaults to the id.
+    #[serde(default)]
+    pub display_name: Option<String>,
+
+    /// Widgets, drawn in order.
+    #[serde(default)]
+    pub widgets: Vec<Widget>,
+}
+
+/// Kind of widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    /// A line of text, optionally prefixed by a label.
+    Text,
+    /// A 270° arc gauge.
+    Gauge,
+    /// A horizontal progress bar.
+    Bar,
+    /// A scrolling history graph.
+    Graph,
+    /// Two history series in one graph.
+    DualGraph,
+    /// A small analog clock.
+    Clock,
+}
+
+/// Horizontal text alignment relative to the placement's `x`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Where a widget is drawn in one orientation.
+///
+/// `x`/`y` is the top-left corner, except for gauges and clocks where it is
+/// the center.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Placement {
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    #[serde(default)]
+    pub radius: u32,
+}
+
+/// A `SystemData` field a widget displays.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Hostname,
+    Time,
+    Date,
+    Uptime,
+    CpuPercent,
+    CpuFreq,
+    LoadAvg,
+    CpuTemp,
+    RamPercent,
+    DiskReadRate,
+    DiskWriteRate,
+    NetInterface,
+    NetRxRate,
+    NetTxRate,
+    IpAddress,
+    CpuHistory,
+    DiskHistory,
+    DiskReadHistory,
+    DiskWriteHistory,
+    NetHistory,
+    NetRxHistory,
+    NetTxHistory,
+    /// A custom value, written `custom:<key>`; both a number and a history.
+    #[serde(untagged)]
+    Custom(CustomKey),
+}
+
+/// A custom value bound to a widget, stored as written (`custom:<key>`),
+/// which is also the id of the complication that toggles it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CustomKey(String);
+
+impl CustomKey {
+    /// Returns the key of the custom value.
+    fn key(&self) -> &str {
+        &self.0[complication_names::CUSTOM_PREFIX.len()..]
+    }
+}
+
+impl TryFrom<String> for CustomKey {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        let key = s
+            .strip_prefix(complication_names::CUSTOM_PREFIX)
+            .ok_or_else(|| format!("Unknown value '{}'", s))?;
+        custom_values::validate_key(key).map_err(|e| e.to_string())?;
+        Ok(CustomKey(s))
+    }
+}
+
+impl Field {
+    /// Returns the numeric value, for gauges and bars.
+    fn number(&self, data: &SystemData) -> Option<f64> {
+        match self {
+            Field::CpuPercent => Some(data.cpu_percent),
+            Field::CpuFreq => data.cpu_freq_avg_mhz().map(f64::from),
+            Field::LoadAvg => data.load_avg.map(|l| l[0]),
+            Field::CpuTemp => data.cpu_temp,
+            Field::RamPercent => Some(data.ram_percent),
+            Field::DiskReadRate => Some(data.disk_read_rate),
+            Field::DiskWriteRate => Some(data.disk_write_rate),
+            Field::NetRxRate => Some(data.net_rx_rate),
+            Field::NetTxRate => Some(data.net_tx_rate),
+            Field::Custom(key) => data.custom.get(key.key()).map(|v| v.value),
+            _ => None,
+        }
+    }
+
+    /// Returns the alert level, for metrics with thresholds.
+    fn alert(&self, data: &SystemData) -> AlertLevel {
+        match self {
+            Field::CpuPercent => data.alerts.cpu_percent,
+            Field::CpuTemp => data.alerts.cpu_temp,
+            Field::RamPercent => data.alerts.ram_percent,
+            _ => AlertLevel::Normal,
+        }
+    }
+
+    /// Returns the history series, for graphs.
+    fn history<'a>(&self, data: &'a SystemData) -> Option<&'a VecDeque<f64>> {
+        match self {
+            Field::CpuHistory => Some(&data.cpu_history),
+            Field::DiskHistory => Some(&data.disk_history),
+            Field::DiskReadHistory => Some(&data.disk_read_history),
+            Field::DiskWriteHistory => Some(&data.disk_write_history),
+            Field::NetHistory => Some(&data.net_history),
+            Field::NetRxHistory => Some(&data.net_rx_history),
+            Field::NetTxHistory => Some(&data.net_tx_history),
+            Field::Custom(key) => data.custom.get(key.key()).map(|v| &v.history),
+            _ => None,
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Field::CpuPercent
+                | Field::CpuFreq
+                | Field::LoadAvg
+                | Field::CpuTemp
+                | Field::RamPercent
+                | Field::DiskReadRate
+                | Field::DiskWriteRate
+                | Field::NetRxRate
+                | Field::NetTxRate
+                | Field::Custom(_)
+        )
+    }
+
+    fn is_history(&self) -> bool {
+        matches!(
+            self,
+            Field::CpuHistory
+                | Field::DiskHistory
+                | Field::DiskReadHistory
+                | Field::DiskWriteHistory
+                | Field::NetHistory
+                | Field::NetRxHistory
+                | Field::NetTxHistory
+                | Field::Custom(_)
+        )
+    }
+
+    /// Returns the fixed graph scale of a history, if it isn't a rate.
+    fn graph_scale(&self) -> Option<f64> {
+        match self {
+            Field::CpuHistory => Some(100.0),
+            _ => None,
+        }
+    }
+
+    /// Formats the value as text.
+    fn text(&self, data: &SystemData, time_format: &str, date_format: &str) -> Option<String> {
+        match self {
+            Field::Hostname => Some(data.hostname.clone()),
+            Field::Time => Some(data.format_time(time_format)),
+            Field::Date => data.format_date(date_format),
+            Field::Uptime => Some(data.uptime.clone()),
+            Field::CpuPercent => Some(format!("{:.0}%", data.cpu_percent)),
+            Field::CpuFreq => data
+                .cpu_freq_avg_mhz()
+                .map(|mhz| format!("{}Hz", SystemData::format_freq_compact(mhz))),
+            Field::LoadAvg => data
+                .load_avg
+                .map(|[one, five, fifteen]| format!("{:.2} {:.2} {:.2}", one, five, fifteen)),
+            Field::CpuTemp => data.cpu_temp.map(|t| format!("{:.0}°C", t)),
+            Field::RamPercent => Some(format!("{:.0}%", data.ram_percent)),
+            Field::DiskReadRate => Some(SystemData::format_rate(data.disk_read_rate)),
+            Field::DiskWriteRate => Some(SystemData::format_rate(data.disk_write_rate)),
+            Field::NetInterface => Some(data.net_interface.clone()),
+            Field::NetRxRate => Some(SystemData::format_rate(data.net_rx_rate)),
+            Field::NetTxRate => Some(SystemData::format_rate(data.net_tx_rate)),
+            Field::IpAddress => data.display_ip.clone(),
+            Field::Custom(key) => data.custom.get(key.key()).map(CustomValue::text),
+            _ => None,
+        }
+    }
+}
+
+/// A theme color or a fixed `#RRGGBB` color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ColorRef {
+    Primary,
+    Secondary,
+    Text,
+    Background,
+    /// Text color faded towards the background.
+    Dim,
+    /// Primary color faded towards the background, for tracks and graph areas.
+    Track,
+    Accent,
+    Warning,
+    Critical,
+    Rgb(u32),
+}
+
+impl TryFrom<String> for ColorRef {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "primary" => Ok(ColorRef::Primary),
+            "secondary" => Ok(ColorRef::Secondary),
+            "text" => Ok(ColorRef::Text),
+            "background" => Ok(ColorRef::Background),
+            "dim" => Ok(ColorRef::Dim),
+            "track" => Ok(ColorRef::Track),
+            "accent" => Ok(ColorRef::Accent),
+            "warning" => Ok(ColorRef::Warning),
+            "critical" => Ok(ColorRef::Critical),
+            hex => parse_rgb(hex)
+                .map(ColorRef::Rgb)
+                .ok_or_else(|| format!("Invalid color '{}'", s)),
+        }
+    }
+}
+
+impl ColorRef {
+    fn resolve(self, theme: &Theme) -> u32 {
+        match self {
+            ColorRef::Primary => theme.primary,
+            ColorRef::Secondary => theme.secondary,
+            ColorRef::Text => theme.text,
+            ColorRef::Background => theme.background,
+            ColorRef::Dim => mix(theme.text, theme.background, 0.7),
+            ColorRef::Track => mix(theme.primary, theme.background, 0.2),
+            ColorRef::Accent => theme.accent,
+            ColorRef::Warning => theme.warning,
+            ColorRef::Critical => theme.critical,
+            ColorRef::Rgb(rgb) => rgb,
+        }
+    }
+}
+
+/// Mixes `color` into `background` by `factor`.
+fn mix(color: u32, background: u32, factor: f32) -> u32 {
+    let channel = |shift: u32| {
+        let c = ((color >> shift) & 0xFF) as f32;
+        let b = ((background >> shift) & 0xFF) as f32;
+        ((c * factor + b * (1.0 - factor)) as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+fn default_size() -> f32 {
+    14.0
+}
+
+fn default_thickness() -> f32 {
+    8.0
+}
+
+/// A single element of a custom face.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Widget {
+    /// Kind of widget.
+    #[serde(rename = "type")]
+    pub kind: WidgetKind,
+
+    /// Field to display.
+    #[serde(default)]
+    pub value: Option<Field>,
+
+    /// Second series of a dual graph.
+    #[serde(default)]
+    pub value2: Option<Field>,
+
+    /// Static text drawn before a text widget's value.
+    #[serde(default)]
+    pub label: Option<String>,
+
+    /// Complication that toggles this widget.
+    #[serde(default)]
+    pub complication: Option<String>,
+
+    /// Font size of text widgets.
+    #[serde(default = "default_size")]
+    pub size: f32,
+
+    /// Text alignment.
+    #[serde(default)]
+    pub align: Align,
+
+    /// Stroke width of gauges.
+    #[serde(default = "default_thickness")]
+    pub thickness: f32,
+
+    /// Full-scale value of gauges, bars and graphs; graphs auto-scale if unset.
+    #[serde(default)]
+    pub max: Option<f64>,
+
+    /// Foreground color.
+    #[serde(default)]
+    pub color: Option<ColorRef>,
+
+    /// Second series or clock hand color.
+    #[serde(default)]
+    pub color2: Option<ColorRef>,
+
+    /// Track or graph area color.
+    #[serde(default)]
+    pub background: Option<ColorRef>,
+
+    /// Placement in landscape.
+    #[serde(default)]
+    pub landscape: Option<Placement>,
+
+    /// Placement in portrait.
+    #[serde(default)]
+    pub portrait: Option<Placement>,
+}
+
+impl Widget {
+    /// Returns the complication that toggles the widget. Widgets bound to a
+    /// custom value are toggled by its complication unless they name one.
+    fn complication_id(&self) -> Option<&str> {
+        match (&self.complication, &self.value) {
+            (Some(id), _) => Some(id),
+            (None, Some(Field::Custom(key))) => Some(&key.0),
+            _ => None,
+        }
+    }
+
+    /// Checks the widget's bindings and that its placements fit on screen.
+    fn validate(&self) -> Result<()> {
+        let value = self.value.as_ref();
+        match self.kind {
+            WidgetKind::Text => match value {
+                Some(field) if field.is_history() && !field.is_number() => {
+                    bail!("text cannot show history field {:?}", field)
+                }
+                None if self.label.is_none() => bail!("text needs a value or a label"),
+                _ => {}
+            },
+            WidgetKind::Gauge | WidgetKind::Bar => {
+                if !value.is_some_and(Field::is_number) {
+                    bail!("{:?} needs a numeric value", self.kind);
+                }
+            }
+            WidgetKind::Graph => {
+                if !value.is_some_and(Field::is_history) {
+                    bail!("graph needs a history value");
+                }
+            }
+            WidgetKind::DualGraph => {
+                if !value.is_some_and(Field::is_history)
+                    || !self.value2.as_ref().is_some_and(Field::is_history)
+                {
+                    bail!("dual_graph needs history values for value and value2");
+                }
+            }
+            WidgetKind::Clock => {}
+        }
+
+        if self.landscape.is_none() && self.portrait.is_none() {
+            bail!("widget has no landscape or portrait placement");
+        }
+        for (placement, (width, height)) in [(self.landscape, LANDSCAPE), (self.portrait, PORTRAIT)]
+        {
+            let Some(placement) = placement else {
+                continue;
+            };
+            if let Some((x, y, w, h)) = self.bounds(&placement) {
+                if w == 0 || h == 0 {
+                    bail!("{:?} needs a size (width/height or radius)", self.kind);
+                }
+                if !fits((width, height), x, y, w, h) {
+                    bail!(
+                        "{:?} at ({}, {}) does not fit on a {}x{} screen",
+                        self.kind,
+                        placement.x,
+                        placement.y,
+                        width,
+                        height
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the area covered by a non-text widget.
+    fn bounds(&self, p: &Placement) -> Option<(i32, i32, u32, u32)> {
+        let round = |radius: u32, stroke: f32| {
+            if radius == 0 {
+                return (p.x, p.y, 0, 0);
+            }
+            let r = radius + (stroke / 2.0).ceil() as u32;
+            (p.x - r as i32, p.y - r as i32, r * 2, r * 2)
+        };
+        match self.kind {
+            WidgetKind::Text => None,
+            WidgetKind::Gauge => Some(round(p.radius, self.thickness)),
+            WidgetKind::Clock => Some(round(p.radius, 1.5)),
+            WidgetKind::Bar | WidgetKind::Graph | WidgetKind::DualGraph => {
+                Some((p.x, p.y, p.width, p.height))
+            }
+        }
+    }
+}
+
+/// Returns true if the rectangle lies within a screen of the given size.
+fn fits((width, height): (u32, u32), x: i32, y: i32, w: u32, h: u32) -> bool {
+    x >= 0 && y >= 0 && x + w as i32 <= width as i32 && y + h as i32 <= height as i32
+}
+
+impl FaceDefinition {
+    /// Parses a face definition from a TOML or JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut def: FaceDefinition = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => toml::from_str(&content)?,
+        };
+        if def.name.is_empty() {
+            def.name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+        }
+        def.validate()?;
+        Ok(def)
+    }
+
+    /// Checks the face name and every widget.
+    fn validate(&mut self) -> Result<()> {
+        self.name = self.name.to_lowercase();
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "Invalid face name '{}': use letters, digits, '-' and '_'",
+                self.name
+            );
+        }
+        if super::builtin_face(&self.name).is_some() {
+            bail!(
+                "Face name '{}' is already used by a built-in face",
+                self.name
+            );
+        }
+        for (i, widget) in self.widgets.iter().enumerate() {
+            widget
+                .validate()
+                .with_context(|| format!("Widget {}", i + 1))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the human-readable name.
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Registers a face, replacing any earlier face with the same name.
+pub fn register(def: FaceDefinition) {
+    let mut registry = REGISTRY.write().unwrap();
+    registry.retain(|d| d.name != def.name);
+    registry.push(Arc::new(def));
+    registry.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Loads every `.toml` and `.json` face definition in a directory.
+///
+/// Invalid files are logged and skipped. Returns the number of faces loaded.
+pub fn load_dir(dir: &Path) -> usize {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("toml" | "json")
+            )
+        })
+        .collect();
+    paths.sort();
+
+    let mut loaded = 0;
+    for path in paths {
+        match FaceDefinition::load(&path) {
+            Ok(def) => {
+                info!("Loaded custom face '{}' from {:?}", def.name, path);
+                register(def);
+                loaded += 1;
+            }
+            Err(e) => warn!("Skipping custom face {:?}: {:#}", path, e),
+        }
+    }
+    loaded
+}
+
+/// Looks up a registered face definition.
+pub fn find(name: &str) -> Option<Arc<FaceDefinition>> {
+    let name = name.to_lowercase();
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|d| d.name == name)
+        .cloned()
+}
+
+/// Returns all registered face definitions, sorted by name.
+pub fn all() -> Vec<Arc<FaceDefinition>> {
+    REGISTRY.read().unwrap().clone()
+}
+
+/// Returns the built-in complication with this id, or a plain toggle.
+fn complication_for(id: &str) -> Complication {
+    match id {
+        complication_names::TIME => complications::time(true),
+        complication_names::DATE => complications::date(true, date_formats::ISO),
+        complication_names::IP_ADDRESS => complications::ip_address(true),
+        complication_names::NETWORK => complications::network(true),
+        complication_names::DISK_IO => complications::disk_io(true),
+        complication_names::CPU_TEMP => complications::cpu_temp(true),
+        complication_names::CPU_CORES => complications::cpu_cores(true),
+        "hostname" => complications::hostname(true),
+        _ if id.starts_with(complication_names::CUSTOM_PREFIX) => {
+            complications::custom_value(&id[complication_names::CUSTOM_PREFIX.len()..], true)
+        }
+        _ => Complication::new(id, id, "Custom face element", true),
+    }
+}
+
+/// A face rendered from a [`FaceDefinition`].
+pub struct CustomFace {
+    def: Arc<FaceDefinition>,
+}
+
+impl CustomFace {
+    /// Creates a face from a definition.
+    pub fn new(def: Arc<FaceDefinition>) -> Self {
+        Self { def }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(
+        canvas: &mut Canvas,
+        widget: &Widget,
+        p: &Placement,
+        data: &SystemData,
+        time_format: &str,
+        date_format: &str,
+        color: u32,
+    ) {
+        let value = match &widget.value {
+            Some(field) => match field.text(data, time_format, date_format) {
+                Some(value) => value,
+                None => return,
+            },
+            None => String::new(),
+        };
+        let text = format!("{}{}", widget.label.as_deref().unwrap_or(""), value);
+        if text.is_empty() {
+            return;
+        }
+
+        let text_width = canvas.text_width(&text, widget.size);
+        let x = match widget.align {
+            Align::Left => p.x,
+            Align::Center => p.x - text_width / 2,
+            Align::Right => p.x - text_width,
+        };
+        let height = canvas.line_height(widget.size) as u32;
+        if fits(
+            canvas.dimensions(),
+            x,
+            p.y,
+            text_width.max(0) as u32,
+            height,
+        ) {
+            canvas.draw_text(x, p.y, &text, widget.size, color);
+        }
+    }
+
+    fn draw_gauge(
+        canvas: &mut Canvas,
+        widget: &Widget,
+        p: &Placement,
+        percent: f64,
+        fg: u32,
+        bg: u32,
+    ) {
+        use std::f32::consts::PI;
+
+        // 270° sweep starting bottom-left, as in the arcs face
+        let start_angle = 135.0 * PI / 180.0;
+        let end_angle = 405.0 * PI / 180.0;
+        canvas.draw_arc(
+            p.x,
+            p.y,
+            p.radius,
+            start_angle,
+            end_angle,
+            widget.thickness,
+            bg,
+        );
+        if percent > 0.0 {
+            let fill =
+                start_angle + (end_angle - start_angle) * (percent.min(100.0) / 100.0) as f32;
+            canvas.draw_arc(p.x, p.y, p.radius, start_angle, fill, widget.thickness, fg);
+        }
+    }
+}
+
+impl Face for CustomFace {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    fn available_complications(&self) -> Vec<Complication> {
+        let mut ids: Vec<&str> = Vec::new();
+        for id in self.def.widgets.iter().filter_map(Widget::complication_id) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids.into_iter().map(complication_for).collect()
+    }
+
+    fn render(
+        &self,
+        canvas: &mut Canvas,
+        data: &SystemData,
+        theme: &Theme,
+        complications: &EnabledComplications,
+    ) {
+        let (width, height) = canvas.dimensions();
+        let portrait = width < height;
+
+        let time_format = complications
+            .get_option(
+                self.name(),
+                complication_names::TIME,
+                complication_options::TIME_FORMAT,
+            )
+            .map(|s| s.as_str())
+            .unwrap_or(time_formats::DIGITAL_24H);
+        let date_format = complications
+            .get_option(
+                self.name(),
+                complication_names::DATE,
+                complication_options::DATE_FORMAT,
+            )
+            .map(|s| s.as_str())
+            .unwrap_or(date_formats::ISO);
+
+        for widget in &self.def.widgets {
+            let placement = if portrait {
+                widget.portrait
+            } else {
+                widget.landscape
+            };
+            let Some(p) = placement else {
+                continue;
+            };
+            if let Some(id) = widget.complication_id() {
+                if !complications.is_enabled(self.name(), id, true) {
+                    continue;
+                }
+            }
+            // Placements are validated for the panel size; skip on other canvases
+            if let Some((x, y, w, h)) = widget.bounds(&p) {
+                if !fits((width, height), x, y, w, h) {
+                    continue;
+                }
+            }
+
+            let default_color = match widget.kind {
+                WidgetKind::Text => ColorRef::Text,
+                _ => ColorRef::Primary,
+            };
+            let alert = widget
+                .value
+                .as_ref()
+                .map_or(AlertLevel::Normal, |f| f.alert(data));
+            let fg = theme.alert_color(alert, widget.color.unwrap_or(default_color).resolve(theme));
+            let fg2 = widget.color2.unwrap_or(ColorRef::Secondary).resolve(theme);
+            let bg = widget.background.unwrap_or(ColorRef::Track).resolve(theme);
+            let max = widget.max.unwrap_or(100.0);
+            let number = widget.value.as_ref().and_then(|f| f.number(data));
+            let percent = number.map(|v| if max > 0.0 { v / max * 100.0 } else { 0.0 });
+
+            match widget.kind {
+                WidgetKind::Text => {
+                    Self::draw_text(canvas, widget, &p, data, time_format, date_format, fg)
+                }
+                WidgetKind::Gauge => {
+                    if let Some(percent) = percent {
+                        Self::draw_gauge(canvas, widget, &p, percent, fg, bg);
+                    }
+                }
+                WidgetKind::Bar => {
+                    if let Some(percent) = percent {
+                        canvas.fill_rect(p.x, p.y, p.width, p.height, bg);
+                        let fill = ((p.width as f64 * percent / 100.0) as u32).min(p.width);
+                        if fill > 0 {
+                            canvas.fill_rect(p.x, p.y, fill, p.height, fg);
+                        }
+                    }
+                }
+                WidgetKind::Graph => {
+                    if let Some(history) = widget.value.as_ref().and_then(|f| f.history(data)) {
+                        let max = widget
+                            .max
+                            .or_else(|| widget.value.as_ref().and_then(Field::graph_scale))
+                            .unwrap_or_else(|| SystemData::compute_graph_scale(history));
+                        canvas.draw_graph(p.x, p.y, p.width, p.height, history, max, fg, bg);
+                    }
+                }
+                WidgetKind::DualGraph => {
+                    let first = widget.value.as_ref().and_then(|f| f.history(data));
+                    let second = widget.value2.as_ref().and_then(|f| f.history(data));
+                    if let (Some(first), Some(second)) = (first, second) {
+                        let max = widget.max.unwrap_or_else(|| {
+                            SystemData::compute_graph_scale(first)
+                                .max(SystemData::compute_graph_scale(second))
+                        });
+                        canvas.draw_dual_graph(
+                            p.x, p.y, p.width, p.height, first, second, max, fg, fg2, bg,
+                        );
+                    }
+                }
+                WidgetKind::Clock => {
+                    let hands = widget.color2.unwrap_or(ColorRef::Text).resolve(theme);
+                    draw_mini_analog_clock(
+                        canvas,
+                        p.x,
+                        p.y,
+                        p.radius,
+                        data.hour,
+                        data.minute,
+                        fg,
+                        hands,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r##"
+        display_name = "Minimal"
+
+        [[widgets]]
+        type = "text"
+        value = "time"
+        complication = "time"
+        size = 32
+        align = "center"
+        landscape = { x = 160, y = 20 }
+        portrait = { x = 85, y = 40 }
+
+        [[widgets]]
+        type = "gauge"
+        value = "cpu_percent"
+        color = "#ff8800"
+        landscape = { x = 60, y = 110, radius = 40 }
+        portrait = { x = 85, y = 160, radius = 50 }
+
+        [[widgets]]
+        type = "dual_graph"
+        value = "net_rx_history"
+        value2 = "net_tx_history"
+        complication = "network"
+        landscape = { x = 120, y = 80, width = 190, height = 40 }
+
+        [[widgets]]
+        type = "clock"
+        complication = "my_clock"
+        landscape = { x = 290, y = 30, radius = 15 }
+    "##;
+
+    fn write_face(dir: &Path, file: &str, content: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join(file), content).unwrap();
+    }
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ht32-panel-faces-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_load_dir_registers_faces() {
+        let dir = test_dir("load");
+        write_face(&dir, "test_minimal.toml", MINIMAL);
+        write_face(
+            &dir,
+            "test_json.json",
+            r#"{"widgets": [{"type": "bar", "value": "ram_percent",
+                "landscape": {"x": 10, "y": 10, "width": 100, "height": 8}}]}"#,
+        );
+        // Gauges cannot show text-only fields
+        write_face(
+            &dir,
+            "test_broken.toml",
+            "[[widgets]]\ntype = \"gauge\"\nvalue = \"hostname\"\nlandscape = { x = 50, y = 50, radius = 10 }\n",
+        );
+
+        assert_eq!(load_dir(&dir), 2);
+        assert!(find("test_broken").is_none());
+        assert_eq!(find("TEST_MINIMAL").unwrap().display_name(), "Minimal");
+        assert_eq!(find("test_json").unwrap().display_name(), "test_json");
+
+        let face = super::super::create_face("test_minimal").unwrap();
+        let ids: Vec<String> = face
+            .available_complications()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec!["time", "network", "my_clock"]);
+        assert!(super::super::available_faces()
+            .iter()
+            .any(|f| f.id == "test_minimal"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_definitions() {
+        let dir = test_dir("invalid");
+        let cases = [
+            ("offscreen.toml", "[[widgets]]\ntype = \"bar\"\nvalue = \"cpu_percent\"\nlandscape = { x = 300, y = 10, width = 40, height = 8 }\n"),
+            ("nosize.toml", "[[widgets]]\ntype = \"graph\"\nvalue = \"disk_history\"\nlandscape = { x = 0, y = 0 }\n"),
+            ("unplaced.toml", "[[widgets]]\ntype = \"text\"\nlabel = \"Hi\"\n"),
+            ("clock.toml", "name = \"clock\"\n"),
+            ("badcolor.toml", "[[widgets]]\ntype = \"text\"\nlabel = \"Hi\"\ncolor = \"purple\"\nlandscape = { x = 0, y = 0 }\n"),
+        ];
+        for (file, content) in cases {
+            write_face(&dir, file, content);
+            assert!(
+                FaceDefinition::load(&dir.join(file)).is_err(),
+                "{} should be rejected",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_value_widgets() {
+        let mut def: FaceDefinition = toml::from_str(
+            r##"
+            [[widgets]]
+            type = "text"
+            value = "custom:fan"
+            color = "#ff8800"
+            landscape = { x = 10, y = 10 }
+
+            [[widgets]]
+            type = "graph"
+            value = "custom:fan"
+            complication = "fan_graph"
+            landscape = { x = 10, y = 40, width = 100, height = 40 }
+        "##,
+        )
+        .unwrap();
+        def.name = "custom_value_test".to_string();
+        def.validate().unwrap();
+        assert!(toml::from_str::<Widget>("type = \"text\"\nvalue = \"custom:bad key\"").is_err());
+        assert!(toml::from_str::<Widget>("type = \"text\"\nvalue = \"fan\"").is_err());
+
+        let face = CustomFace::new(Arc::new(def));
+        let available = face.available_complications();
+        assert_eq!(available[0].id, "custom:fan");
+        assert_eq!(available[1].id, "fan_graph");
+
+        let values = custom_values::CustomValues::default();
+        values.set("fan", 1200.0, "RPM").unwrap();
+        let data = SystemData {
+            custom: values.snapshot(),
+            ..SystemData::default()
+        };
+        let field = face.def.widgets[0].value.clone().unwrap();
+        assert_eq!(field.text(&data, "", "").as_deref(), Some("1200 RPM"));
+        assert_eq!(field.number(&data), Some(1200.0));
+        assert_eq!(field.history(&data).map(|h| h.len()), Some(1));
+
+        let theme = Theme::default();
+        let orange = |complications: &EnabledComplications| {
+            let mut canvas = Canvas::new(LANDSCAPE.0, LANDSCAPE.1);
+            face.render(&mut canvas, &data, &theme, complications);
+            canvas
+                .pixmap_pixels()
+                .iter()
+                .filter(|p| p.red() == 0xFF && p.green() == 0x88 && p.blue() == 0x00)
+                .count()
+        };
+        let mut complications = EnabledComplications::new();
+        complications.init_from_defaults(&face);
+        assert!(orange(&complications) > 0);
+        complications.set_enabled(face.name(), "custom:fan", false);
+        assert_eq!(orange(&complications), 0);
+    }
+
+    #[test]
+    fn test_render_both_orientations() {
+        let mut def: FaceDefinition = toml::from_str(MINIMAL).unwrap();
+        def.name = "render_test".to_string();
+        def.validate().unwrap();
+        let face = CustomFace::new(Arc::new(def));
+
+        let mut complications = EnabledComplications::new();
+        complications.init_from_defaults(&face);
+        let data = SystemData {
+            cpu_percent: 50.0,
+            hour: 10,
+            minute: 30,
+            ..SystemData::default()
+        };
+        let theme = Theme::default();
+
+        for (w, h) in [LANDSCAPE, PORTRAIT] {
+            let mut canvas = Canvas::new(w, h);
+            canvas.set_background(theme.background);
+            canvas.clear();
+            face.render(&mut canvas, &data, &theme, &complications);
+            let drawn = canvas
+                .pixmap_pixels()
+                .iter()
+                .filter(|p| p.red() == 0xFF && p.green() == 0x88 && p.blue() == 0x00)
+                .count();
+            assert!(drawn > 0, "gauge not drawn at {}x{}", w, h);
+        }
+    }
+}
@@ -213,7 +213,7 @@ impl Face for DigitsFace {
                 y - 4,
                 &cpu_val,
                 FONT_TIME,
-                colors.segment_on,
+                theme.alert_color(data.alerts.cpu_percent, colors.segment_on),
             );
             y += canvas.line_height(FONT_TIME);
 
@@ -228,7 +228,7 @@ impl Face for DigitsFace {
                 y - 4,
                 &ram_val,
                 FONT_TIME,
-                colors.segment_on,
+                theme.alert_color(data.alerts.ram_percent, colors.segment_on),
             );
             y += canvas.line_height(FONT_TIME);
 
@@ -383,7 +383,7 @@ impl Face for DigitsFace {
                 "CPU",
                 &format!("{:.0}%", data.cpu_percent),
                 colors.label,
-                colors.segment_on,
+                theme.alert_color(data.alerts.cpu_percent, colors.segment_on),
             );
             Self::draw_segment_value_medium(
                 canvas,
@@ -392,7 +392,7 @@ impl Face for DigitsFace {
                 "RAM",
                 &format!("{:.0}%", data.ram_percent),
                 colors.label,
-                colors.segment_on,
+                theme.alert_color(data.alerts.ram_percent, colors.segment_on),
             );
             // Complication: CPU temperature
             if is_on(complication_names::CPU_TEMP) {
@@ -404,7 +404,7 @@ impl Face for DigitsFace {
                         "TEMP",
                         &format!("{:.0}°", temp),
                         colors.label,
-                        colors.segment_on,
+                        theme.alert_color(data.alerts.cpu_temp, colors.segment_on),
                     );
                 }
             }
@@ -9,23 +9,33 @@
 mod arcs;
 mod ascii;
 mod clock;
+mod custom;
 mod digits;
 mod professional;
+mod themes;
 
 pub use arcs::ArcsFace;
 pub use ascii::AsciiFace;
 pub use clock::ClockFace;
+pub use custom::{load_dir as load_custom_faces, CustomFace};
 pub use digits::DigitsFace;
 pub use professional::ProfessionalFace;
+pub use themes::{load_dir as load_user_themes, register as register_theme, ThemeDefinition};
 
 use crate::rendering::Canvas;
-use crate::sensors::data::SystemData;
+use crate::sensors::data::{AlertLevel, SystemData};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::f32::consts::PI;
 
+/// Warning color used by themes that don't define one - RGB888
+pub const DEFAULT_WARNING: u32 = 0xFFB000;
+
+/// Critical color used by themes that don't define one - RGB888
+pub const DEFAULT_CRITICAL: u32 = 0xFF3B30;
+
 /// Color theme for face rendering.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Theme {
     /// Primary color (used for highlights, interface names) - RGB888
     pub primary: u32,
@@ -35,65 +45,104 @@ pub struct Theme {
     pub text: u32,
     /// Background color - RGB888
     pub background: u32,
+    /// Extra accent color - RGB888
+    pub accent: u32,
+    /// Color for values past a warning threshold - RGB888
+    pub warning: u32,
+    /// Color for values past a critical threshold - RGB888
+    pub critical: u32,
 }
 
 impl Default for Theme {
     fn default() -> Self {
-        Self::from_preset("default")
+        Self::from_preset("default").unwrap()
     }
 }
 
 impl Theme {
-    /// Creates a theme from a preset name.
-    /// All themes are designed for good contrast ratios (WCAG AA compliant).
-    pub fn from_preset(name: &str) -> Self {
-        match name.to_lowercase().as_str() {
-            "hacker" => Self {
-                // Matrix-like green on black - high contrast
-                primary: 0x00FF00,   // Bright green
-                secondary: 0x00DD00, // Slightly darker green
-                text: 0x00FF00,      // Green text
-                background: 0x000000,
-            },
-            "ember" | "fire" => Self {
-                // Red/orange warm theme
-                primary: 0xFF6B35,   // Bright orange
-                secondary: 0xFF4444, // Red
-                text: 0xFFEEDD,      // Warm white
-                background: 0x1A0A00,
-            },
-            "solarized-light" | "solarized_light" => Self {
-                // Solarized Light
-                primary: 0x268BD2,    // Blue
-                secondary: 0x859900,  // Green
-                text: 0x073642,       // Base02 (darker for better contrast)
-                background: 0xFDF6E3, // Base3
-            },
-            "solarized-dark" | "solarized_dark" => Self {
-                // Solarized Dark
-                primary: 0x268BD2,    // Blue
-                secondary: 0x2AA198,  // Cyan (more visible)
-                text: 0xEEE8D5,       // Base2 (brighter for better contrast)
-                background: 0x002B36, // Base03
-            },
-            "nord" => Self {
-                // Nord
-                primary: 0x88C0D0,    // Nord8 (frost cyan)
-                secondary: 0x81A1C1,  // Nord9 (frost blue)
-                text: 0xECEFF4,       // Nord6 (snow storm white)
-                background: 0x2E3440, // Nord0
-            },
-            "tokyonight" | "tokyo-night" | "tokyo_night" => Self {
-                // Tokyo Night
-                primary: 0x7AA2F7,   // Blue
-                secondary: 0xBB9AF7, // Magenta
-                text: 0xE0E0FF,      // Brighter foreground
-                background: 0x1A1B26,
-            },
-            // Unknown theme - fall back to nord
-            _ => Self::from_preset("nord"),
+    /// Creates a theme from its base colors, using the secondary color as
+    /// accent and the default warning and critical colors.
+    pub const fn new(primary: u32, secondary: u32, text: u32, background: u32) -> Self {
+        Self {
+            primary,
+            secondary,
+            text,
+            background,
+            accent: secondary,
+            warning: DEFAULT_WARNING,
+            critical: DEFAULT_CRITICAL,
         }
     }
+
+    /// Looks up a built-in or user-defined theme by name.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::from_preset(name).or_else(|| themes::find(name))
+    }
+
+    /// Returns the color for a metric at the given alert level, or `normal`
+    /// while the metric is below its thresholds.
+    pub fn alert_color(&self, level: AlertLevel, normal: u32) -> u32 {
+        match level {
+            AlertLevel::Normal => normal,
+            AlertLevel::Warning => self.warning,
+            AlertLevel::Critical => self.critical,
+        }
+    }
+
+    /// Creates a theme from a built-in preset name.
+    /// All themes are designed for good contrast ratios (WCAG AA compliant).
+    pub fn from_preset(name: &str) -> Option<Self> {
+        let theme = match name.to_lowercase().as_str() {
+            // Matrix-like green on black - high contrast
+            "hacker" => Self::new(
+                0x00FF00, // Bright green
+                0x00DD00, // Slightly darker green
+                0x00FF00, // Green text
+                0x000000,
+            ),
+            // Red/orange warm theme
+            "ember" | "fire" => Self::new(
+                0xFF6B35, // Bright orange
+                0xFF4444, // Red
+                0xFFEEDD, // Warm white
+                0x1A0A00,
+            ),
+            "solarized-light" | "solarized_light" => Self::new(
+                0x268BD2, // Blue
+                0x859900, // Green
+                0x073642, // Base02 (darker for better contrast)
+                0xFDF6E3, // Base3
+            ),
+            "solarized-dark" | "solarized_dark" => Self::new(
+                0x268BD2, // Blue
+                0x2AA198, // Cyan (more visible)
+                0xEEE8D5, // Base2 (brighter for better contrast)
+                0x002B36, // Base03
+            ),
+            // Nord is also the default theme
+            "nord" | "default" => Self::new(
+                0x88C0D0, // Nord8 (frost cyan)
+                0x81A1C1, // Nord9 (frost blue)
+                0xECEFF4, // Nord6 (snow storm white)
+                0x2E3440, // Nord0
+            ),
+            "tokyonight" | "tokyo-night" | "tokyo_night" => Self::new(
+                0x7AA2F7, // Blue
+                0xBB9AF7, // Magenta
+                0xE0E0FF, // Brighter foreground
+                0x1A1B26,
+            ),
+            _ => return None,
+        };
+        Some(theme)
+    }
+}
+
+/// Parses a `#RRGGBB` color into RGB888.
+pub fn parse_rgb(s: &str) -> Option<u32> {
+    s.strip_prefix('#')
+        .filter(|h| h.len() == 6)
+        .and_then(|h| u32::from_str_radix(h, 16).ok())
 }
 
 /// Lighten a color by blending it towards white.
@@ -154,39 +203,40 @@ pub fn draw_mini_analog_clock(
 #[derive(Debug, Clone)]
 pub struct ThemeInfo {
     /// Internal identifier used for setting the theme.
-    pub id: &'static str,
+    pub id: String,
     /// Human-readable display name.
-    pub display_name: &'static str,
+    pub display_name: String,
+    /// The theme's colors.
+    pub theme: Theme,
+    /// Whether this is a built-in preset rather than a user theme.
+    pub builtin: bool,
+}
+
+impl ThemeInfo {
+    fn builtin(id: &str, display_name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+            theme: Theme::from_preset(id).unwrap(),
+            builtin: true,
+        }
+    }
 }
 
-/// Returns a list of available theme presets with display names.
+/// Returns a list of available themes with display names.
+///
+/// Built-in presets come first, followed by user themes sorted by id.
 pub fn available_themes() -> Vec<ThemeInfo> {
-    vec![
-        ThemeInfo {
-            id: "ember",
-            display_name: "Ember",
-        },
-        ThemeInfo {
-            id: "hacker",
-            display_name: "Hacker",
-        },
-        ThemeInfo {
-            id: "nord",
-            display_name: "Nord",
-        },
-        ThemeInfo {
-            id: "solarized-dark",
-            display_name: "Solarized Dark",
-        },
-        ThemeInfo {
-            id: "solarized-light",
-            display_name: "Solarized Light",
-        },
-        ThemeInfo {
-            id: "tokyonight",
-            display_name: "Tokyo Night",
-        },
-    ]
+    let mut themes = vec![
+        ThemeInfo::builtin("ember", "Ember"),
+        ThemeInfo::builtin("hacker", "Hacker"),
+        ThemeInfo::builtin("nord", "Nord"),
+        ThemeInfo::builtin("solarized-dark", "Solarized Dark"),
+        ThemeInfo::builtin("solarized-light", "Solarized Light"),
+        ThemeInfo::builtin("tokyonight", "Tokyo Night"),
+    ];
+    themes.extend(themes::all());
+    themes
 }
 
 /// Type of complication option value.
@@ -332,7 +382,10 @@ pub mod complication_names {
     pub const NETWORK: &str = "network";
     pub const DISK_IO: &str = "disk_io";
     pub const CPU_TEMP: &str = "cpu_temp";
+    pub const CPU_CORES: &str = "cpu_cores";
     pub const IP_ADDRESS: &str = "ip_address";
+    /// Prefix of custom value complications, followed by the value's key.
+    pub const CUSTOM_PREFIX: &str = "custom:";
 }
 
 /// Complication option IDs.
@@ -341,6 +394,7 @@ pub mod complication_options {
     pub const DATE_FORMAT: &str = "format";
     pub const IP_TYPE: &str = "ip_type";
     pub const INTERFACE: &str = "interface";
+    pub const SENSOR: &str = "sensor";
     pub const SIZE: &str = "size";
 }
 
@@ -459,13 +513,40 @@ pub mod complications {
         )
     }
 
-    /// CPU temperature complication.
+    /// CPU temperature complication with sensor options.
     pub fn cpu_temp(default_enabled: bool) -> Complication {
-        Complication::new(
+        Complication::with_options(
             complication_names::CPU_TEMP,
             "CPU Temperature",
             "Display CPU temperature",
             default_enabled,
+            vec![ComplicationOption::choice(
+                complication_options::SENSOR,
+                "Sensor",
+                "Temperature sensor to display",
+                vec![ComplicationChoice::new("auto", "Auto-detect")],
+                "auto",
+            )],
+        )
+    }
+
+    /// Per-core CPU complication.
+    pub fn cpu_cores(default_enabled: bool) -> Complication {
+        Complication::new(
+            complication_names::CPU_CORES,
+            "CPU Cores",
+            "Display per-core usage, frequency and load average",
+            default_enabled,
+        )
+    }
+
+    /// Custom value complication, for a value set by another program.
+    pub fn custom_value(key: &str, default_enabled: bool) -> Complication {
+        Complication::new(
+            &format!("{}{}", complication_names::CUSTOM_PREFIX, key),
+            &format!("Custom Value ({})", key),
+            &format!("Display the custom value '{}'", key),
+            default_enabled,
         )
     }
 
@@ -633,8 +714,14 @@ pub trait Face: Send + Sync {
     );
 }
 
-/// Creates a face by name.
+/// Creates a face by name, including custom faces.
 pub fn create_face(name: &str) -> Option<Box<dyn Face>> {
+    builtin_face(name)
+        .or_else(|| custom::find(name).map(|def| Box::new(CustomFace::new(def)) as Box<dyn Face>))
+}
+
+/// Creates a built-in face by name.
+fn builtin_face(name: &str) -> Option<Box<dyn Face>> {
     match name.to_lowercase().as_str() {
         "arcs" => Some(Box::new(ArcsFace::new())),
         "ascii" => Some(Box::new(AsciiFace::new())),
@@ -649,35 +736,37 @@ pub fn create_face(name: &str) -> Option<Box<dyn Face>> {
 #[derive(Debug, Clone)]
 pub struct FaceInfo {
     /// Internal identifier used for setting the face.
-    pub id: &'static str,
+    pub id: String,
     /// Human-readable display name.
-    pub display_name: &'static str,
+    pub display_name: String,
+}
+
+impl FaceInfo {
+    fn new(id: &str, display_name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+        }
+    }
 }
 
 /// Returns a list of available faces with display names.
+///
+/// Built-in faces come first, followed by custom faces sorted by id.
 pub fn available_faces() -> Vec<FaceInfo> {
-    vec![
-        FaceInfo {
-            id: "arcs",
-            display_name: "Arcs",
-        },
-        FaceInfo {
-            id: "ascii",
-            display_name: "ASCII",
-        },
-        FaceInfo {
-            id: "clock",
-            display_name: "Clock",
-        },
-        FaceInfo {
-            id: "digits",
-            display_name: "Digits",
-        },
-        FaceInfo {
-            id: "professional",
-            display_name: "Professional",
-        },
-    ]
+    let mut faces = vec![
+        FaceInfo::new("arcs", "Arcs"),
+        FaceInfo::new("ascii", "ASCII"),
+        FaceInfo::new("clock", "Clock"),
+        FaceInfo::new("digits", "Digits"),
+        FaceInfo::new("professional", "Professional"),
+    ];
+    faces.extend(
+        custom::all()
+            .iter()
+            .map(|def| FaceInfo::new(&def.name, def.display_name())),
+    );
+    faces
 }
 
 /// Returns available complications for a face by name.
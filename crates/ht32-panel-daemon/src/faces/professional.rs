@@ -286,7 +286,7 @@ impl Face for ProfessionalFace {
                         y,
                         &temp_val,
                         FONT_SMALL,
-                        colors.text,
+                        theme.alert_color(data.alerts.cpu_temp, colors.text),
                     );
                     y += line_height + section_spacing;
                 }
@@ -303,7 +303,7 @@ impl Face for ProfessionalFace {
                 bar_width,
                 tall_bar_height,
                 data.cpu_percent,
-                colors.bar_cpu,
+                theme.alert_color(data.alerts.cpu_percent, colors.bar_cpu),
                 colors.bar_bg,
             );
             y += tall_bar_height as i32 + section_spacing;
@@ -319,7 +319,7 @@ impl Face for ProfessionalFace {
                 bar_width,
                 tall_bar_height,
                 data.ram_percent,
-                colors.bar_ram,
+                theme.alert_color(data.alerts.ram_percent, colors.bar_ram),
                 colors.bar_bg,
             );
             y += tall_bar_height as i32 + section_spacing;
@@ -466,7 +466,7 @@ impl Face for ProfessionalFace {
                 bar_width,
                 BAR_HEIGHT,
                 data.cpu_percent,
-                colors.bar_cpu,
+                theme.alert_color(data.alerts.cpu_percent, colors.bar_cpu),
                 colors.bar_bg,
             );
             // CPU temp on same line (no label)
@@ -479,7 +479,7 @@ impl Face for ProfessionalFace {
                         y,
                         &temp_val,
                         FONT_SMALL,
-                        colors.text,
+                        theme.alert_color(data.alerts.cpu_temp, colors.text),
                     );
                 }
             }
@@ -495,7 +495,7 @@ impl Face for ProfessionalFace {
                 bar_width,
                 BAR_HEIGHT,
                 data.ram_percent,
-                colors.bar_ram,
+                theme.alert_color(data.alerts.ram_percent, colors.bar_ram),
                 colors.bar_bg,
             );
             y += line_height + 8;
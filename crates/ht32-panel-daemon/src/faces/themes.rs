@@ -0,0 +1,291 @@
+//! User-defined color themes.
+//!
+//! Themes are declared in the configuration file or as one TOML or JSON file
+//! per theme in a themes directory:
+//!
+//! ```toml
+//! id = "midnight"
+//! display_name = "Midnight"
+//! primary = "#7AA2F7"
+//! secondary = "#9ECE6A"
+//! text = "#C0CAF5"
+//! background = "#16161E"
+//! warning = "#E0AF68"
+//! ```
+
+use std::path::Path;
+use std::sync::RwLock;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::{parse_rgb, Theme, ThemeInfo, DEFAULT_CRITICAL, DEFAULT_WARNING};
+
+/// Minimum text/background contrast ratio before a warning is logged (WCAG AA).
+const MIN_CONTRAST: f64 = 4.5;
+
+/// User themes registered at startup, sorted by id.
+static REGISTRY: RwLock<Vec<ThemeInfo>> = RwLock::new(Vec::new());
+
+/// A theme declared in the configuration or a theme file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeDefinition {
+    /// Theme id; in a theme file it defaults to the file name.
+    #[serde(default)]
+    pub id: String,
+
+    /// Human-readable name; defaults to the id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// Primary color (`#RRGGBB`).
+    pub primary: String,
+
+    /// Secondary color (`#RRGGBB`).
+    pub secondary: String,
+
+    /// Text color (`#RRGGBB`).
+    pub text: String,
+
+    /// Background color (`#RRGGBB`).
+    pub background: String,
+
+    /// Extra accent color; defaults to the secondary color.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+
+    /// Warning color; defaults to amber.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+
+    /// Critical color; defaults to red.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<String>,
+}
+
+impl ThemeDefinition {
+    /// Parses a theme from a TOML or JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut def: ThemeDefinition = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => toml::from_str(&content)?,
+        };
+        if def.id.is_empty() {
+            def.id = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+        }
+        def.to_theme()?;
+        Ok(def)
+    }
+
+    /// Validates the id and colors and builds the theme.
+    pub fn to_theme(&self) -> Result<Theme> {
+        let id = &self.id;
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            bail!(
+                "Invalid theme id '{}': use lowercase letters, digits, '-' and '_'",
+                id
+            );
+        }
+        if Theme::from_preset(id).is_some() {
+            bail!("Theme id '{}' is already used by a built-in theme", id);
+        }
+
+        let color = |key: &str, value: &str| {
+            parse_rgb(value).with_context(|| {
+                format!(
+                    "Theme '{}': invalid {} color '{}', expected #RRGGBB",
+                    id, key, value
+                )
+            })
+        };
+        let optional = |key: &str, value: &Option<String>, default: u32| match value {
+            Some(value) => color(key, value),
+            None => Ok(default),
+        };
+
+        let secondary = color("secondary", &self.secondary)?;
+        Ok(Theme {
+            primary: color("primary", &self.primary)?,
+            secondary,
+            text: color("text", &self.text)?,
+            background: color("background", &self.background)?,
+            accent: optional("accent", &self.accent, secondary)?,
+            warning: optional("warning", &self.warning, DEFAULT_WARNING)?,
+            critical: optional("critical", &self.critical, DEFAULT_CRITICAL)?,
+        })
+    }
+
+    /// Returns the human-readable name.
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Returns the WCAG contrast ratio between two RGB888 colors.
+fn contrast_ratio(a: u32, b: u32) -> f64 {
+    let luminance = |color: u32| {
+        let channel = |shift: u32| {
+            let c = ((color >> shift) & 0xFF) as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(16) + 0.7152 * channel(8) + 0.0722 * channel(0)
+    };
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// Registers a theme, replacing any earlier theme with the same id.
+pub fn register(def: &ThemeDefinition) -> Result<()> {
+    let theme = def.to_theme()?;
+    let contrast = contrast_ratio(theme.text, theme.background);
+    if contrast < MIN_CONTRAST {
+        warn!(
+            "Theme '{}' has low text contrast ({:.1}:1, {:.1}:1 recommended)",
+            def.id, contrast, MIN_CONTRAST
+        );
+    }
+
+    let mut registry = REGISTRY.write().unwrap();
+    registry.retain(|t| t.id != def.id);
+    registry.push(ThemeInfo {
+        id: def.id.clone(),
+        display_name: def.display_name().to_string(),
+        theme,
+        builtin: false,
+    });
+    registry.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(())
+}
+
+/// Loads every `.toml` and `.json` theme in a directory.
+///
+/// Invalid files are logged and skipped. Returns the number of themes loaded.
+pub fn load_dir(dir: &Path) -> usize {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("toml" | "json")
+            )
+        })
+        .collect();
+    paths.sort();
+
+    let mut loaded = 0;
+    for path in paths {
+        match ThemeDefinition::load(&path).and_then(|def| register(&def).map(|_| def)) {
+            Ok(def) => {
+                info!("Loaded theme '{}' from {:?}", def.id, path);
+                loaded += 1;
+            }
+            Err(e) => warn!("Skipping theme {:?}: {:#}", path, e),
+        }
+    }
+    loaded
+}
+
+/// Looks up a registered user theme.
+pub fn find(id: &str) -> Option<Theme> {
+    let id = id.to_lowercase();
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|t| t.id == id)
+        .map(|t| t.theme)
+}
+
+/// Returns all registered user themes, sorted by id.
+pub fn all() -> Vec<ThemeInfo> {
+    REGISTRY.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(id: &str) -> ThemeDefinition {
+        ThemeDefinition {
+            id: id.to_string(),
+            display_name: Some("Test".to_string()),
+            primary: "#112233".to_string(),
+            secondary: "#445566".to_string(),
+            text: "#FFFFFF".to_string(),
+            background: "#000000".to_string(),
+            accent: None,
+            warning: Some("#ffaa00".to_string()),
+            critical: None,
+        }
+    }
+
+    #[test]
+    fn test_registered_theme_is_named() {
+        assert!(Theme::named("test-registered").is_none());
+        register(&definition("test-registered")).unwrap();
+
+        let theme = Theme::named("Test-Registered").unwrap();
+        assert_eq!(theme.primary, 0x112233);
+        assert_eq!(theme.accent, 0x445566);
+        assert_eq!(theme.warning, 0xFFAA00);
+        assert_eq!(theme.critical, DEFAULT_CRITICAL);
+        assert!(super::super::available_themes()
+            .iter()
+            .any(|t| t.id == "test-registered" && !t.builtin));
+    }
+
+    #[test]
+    fn test_rejects_invalid_themes() {
+        let mut bad_color = definition("test-bad-color");
+        bad_color.text = "white".to_string();
+        assert!(bad_color.to_theme().is_err());
+
+        assert!(definition("nord").to_theme().is_err());
+        assert!(definition("Has Spaces").to_theme().is_err());
+        assert!(Theme::from_preset("no-such-theme").is_none());
+    }
+
+    #[test]
+    fn test_load_dir_defaults_id_to_file_name() {
+        let dir = std::env::temp_dir().join(format!("ht32-panel-themes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("test-dusk.toml"),
+            "primary = \"#FF8800\"\nsecondary = \"#8800FF\"\ntext = \"#EEEEEE\"\nbackground = \"#101010\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("test-broken.json"), "{\"primary\": \"#FF8800\"}").unwrap();
+
+        assert_eq!(load_dir(&dir), 1);
+        assert_eq!(Theme::named("test-dusk").unwrap().background, 0x101010);
+        assert!(Theme::named("test-broken").is_none());
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((contrast_ratio(0xFFFFFF, 0x000000) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(0x777777, 0x777777) - 1.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,361 @@
+//! Hot-plug detection for panel hardware.
+//!
+//! Listens for kernel uevents on a netlink socket and attaches or detaches
+//! LCDs as they appear and disappear. Opening a device (including its
+//! initialization cooldown) happens on a blocking thread, off the render path.
+//! Disconnected panels are also retried periodically, which covers virtual
+//! targets and systems where the netlink socket is unavailable.
+
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ht32_panel_hw::{LCD_PID, LCD_VID};
+use tokio::io::unix::AsyncFd;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::dbus::DaemonSignals;
+use crate::state::{AppState, PanelState};
+
+/// Interval between attach attempts for panels that are still disconnected.
+const POLL_INTERVAL_SECS: u64 = 30;
+
+/// Kernel uevent multicast group.
+const KERNEL_UEVENT_GROUP: u32 = 1;
+
+/// Hardware a uevent refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeviceKind {
+    /// The LCD's HID interface.
+    Lcd,
+    /// A USB serial adapter, such as the LED controller's CH340.
+    Led,
+}
+
+/// A parsed kernel uevent.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Uevent {
+    action: String,
+    subsystem: String,
+    devpath: String,
+    devname: Option<String>,
+    hid_id: Option<String>,
+}
+
+impl Uevent {
+    /// Returns the panel hardware this event refers to, if any.
+    fn device(&self) -> Option<DeviceKind> {
+        match self.subsystem.as_str() {
+            // hidraw nodes live below the HID device, named BUS:VID:PID.N
+            "hidraw" => self
+                .devpath
+                .contains(&format!(":{:04X}:{:04X}.", LCD_VID, LCD_PID))
+                .then_some(DeviceKind::Lcd),
+            "hid" => self
+                .hid_id
+                .as_deref()
+                .filter(|id| id.ends_with(&format!("{:08X}:{:08X}", LCD_VID, LCD_PID)))
+                .map(|_| DeviceKind::Lcd),
+            "tty" => self
+                .devname
+                .as_deref()
+                .filter(|name| name.starts_with("ttyUSB") || name.starts_with("ttyACM"))
+                .map(|_| DeviceKind::Led),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a kernel uevent message.
+///
+/// Messages start with an `action@devpath` header followed by
+/// NUL-separated `KEY=value` pairs.
+fn parse_uevent(buf: &[u8]) -> Option<Uevent> {
+    let mut fields = buf.split(|&b| b == 0).filter(|f| !f.is_empty());
+    let header = std::str::from_utf8(fields.next()?).ok()?;
+    if !header.contains('@') {
+        // Not a kernel message (e.g. a udev daemon broadcast)
+        return None;
+    }
+
+    let mut event = Uevent::default();
+    for field in fields {
+        let Ok(field) = std::str::from_utf8(field) else {
+            continue;
+        };
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        match key {
+            "ACTION" => event.action = value.to_string(),
+            "SUBSYSTEM" => event.subsystem = value.to_string(),
+            "DEVPATH" => event.devpath = value.to_string(),
+            "DEVNAME" => event.devname = Some(value.to_string()),
+            "HID_ID" => event.hid_id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    (!event.action.is_empty()).then_some(event)
+}
+
+/// Opens a non-blocking netlink socket subscribed to kernel uevents.
+fn open_uevent_socket() -> std::io::Result<AsyncFd<OwnedFd>> {
+    // SAFETY: socket/bind are called with valid arguments, and the returned
+    // descriptor is immediately owned so it is closed on every path.
+    unsafe {
+        let fd = libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+            libc::NETLINK_KOBJECT_UEVENT,
+        );
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let fd = OwnedFd::from_raw_fd(fd);
+
+        let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = KERNEL_UEVENT_GROUP;
+        if libc::bind(
+            fd.as_raw_fd(),
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        ) != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        AsyncFd::new(fd)
+    }
+}
+
+/// Receives the next uevent from the socket.
+async fn recv_uevent(socket: &AsyncFd<OwnedFd>, buf: &mut [u8]) -> std::io::Result<Option<Uevent>> {
+    loop {
+        let mut guard = socket.readable().await?;
+        let result = guard.try_io(|fd| {
+            // SAFETY: buf is valid for writes of buf.len() bytes.
+            let n = unsafe {
+                libc::recv(
+                    fd.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            };
+            if n < 0 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(n as usize)
+            }
+        });
+        match result {
+            Ok(Ok(n)) => return Ok(parse_uevent(&buf[..n])),
+            // The kernel drops messages when our buffer overflows; keep going
+            Ok(Err(e)) if e.raw_os_error() == Some(libc::ENOBUFS) => return Ok(None),
+            Ok(Err(e)) => return Err(e),
+            Err(_would_block) => continue,
+        }
+    }
+}
+
+/// Watches for panel hardware and keeps each panel's LCD attached.
+pub async fn run_hotplug_watcher(
+    state: Arc<AppState>,
+    signal_tx: broadcast::Sender<DaemonSignals>,
+) {
+    let socket = match open_uevent_socket() {
+        Ok(socket) => Some(socket),
+        Err(e) => {
+            warn!(
+                "Failed to listen for device uevents: {}. Polling every {}s instead.",
+                e, POLL_INTERVAL_SECS
+            );
+            None
+        }
+    };
+
+    let mut poll = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut buf = vec![0u8; 8192];
+
+    loop {
+        let event = match &socket {
+            Some(socket) => tokio::select! {
+                result = recv_uevent(socket, &mut buf) => match result {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Device uevent socket failed: {}", e);
+                        None
+                    }
+                },
+                _ = poll.tick() => {
+                    poll_panels(&state, &signal_tx).await;
+                    continue;
+                }
+            },
+            None => {
+                poll.tick().await;
+                poll_panels(&state, &signal_tx).await;
+                continue;
+            }
+        };
+
+        let Some(event) = event else {
+            continue;
+        };
+        let Some(device) = event.device() else {
+            continue;
+        };
+        debug!(
+            "Device uevent: {} {} ({:?})",
+            event.action, event.devpath, device
+        );
+
+        match (event.action.as_str(), device) {
+            ("add", DeviceKind::Lcd) => attach_panels(&state, &signal_tx).await,
+            ("remove", DeviceKind::Lcd) => probe_panels(&state, &signal_tx).await,
+            ("add", DeviceKind::Led) => {
+                for panel in state.panels() {
+                    panel.restore_led();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Detaches dead LCDs, then attaches any disconnected panel.
+async fn poll_panels(state: &AppState, signal_tx: &broadcast::Sender<DaemonSignals>) {
+    probe_panels(state, signal_tx).await;
+    attach_panels(state, signal_tx).await;
+}
+
+/// Tries to attach the LCD of every disconnected panel.
+async fn attach_panels(state: &AppState, signal_tx: &broadcast::Sender<DaemonSignals>) {
+    for panel in state.panels() {
+        if panel.is_lcd_connected() {
+            continue;
+        }
+        if run_blocking(panel, PanelState::attach_lcd).await {
+            notify(panel, signal_tx);
+        }
+    }
+}
+
+/// Detaches the LCD of every panel whose device stopped responding.
+async fn probe_panels(state: &AppState, signal_tx: &broadcast::Sender<DaemonSignals>) {
+    for panel in state.panels() {
+        if !panel.is_lcd_connected() {
+            continue;
+        }
+        if run_blocking(panel, PanelState::probe_lcd).await {
+            notify(panel, signal_tx);
+        }
+    }
+}
+
+/// Runs a blocking panel operation on the blocking thread pool.
+async fn run_blocking(panel: &Arc<PanelState>, op: fn(&PanelState) -> bool) -> bool {
+    let panel = panel.clone();
+    match tokio::task::spawn_blocking(move || op(&panel)).await {
+        Ok(changed) => changed,
+        Err(e) => {
+            warn!("Device hot-plug task failed: {}", e);
+            false
+        }
+    }
+}
+
+fn notify(panel: &PanelState, signal_tx: &broadcast::Sender<DaemonSignals>) {
+    info!(
+        "Panel '{}': LCD {}",
+        panel.id(),
+        if panel.is_lcd_connected() {
+            "attached"
+        } else {
+            "detached"
+        }
+    );
+    let _ = signal_tx.send(DaemonSignals::ConnectionChanged {
+        panel: panel.id().to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(header: &str, fields: &[&str]) -> Vec<u8> {
+        let mut buf = header.as_bytes().to_vec();
+        for field in fields {
+            buf.push(0);
+            buf.extend_from_slice(field.as_bytes());
+        }
+        buf.push(0);
+        buf
+    }
+
+    #[test]
+    fn test_parse_hidraw_add() {
+        let devpath =
+            "/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.1/0003:04D9:FD01.0005/hidraw/hidraw3";
+        let buf = message(
+            &format!("add@{}", devpath),
+            &[
+                "ACTION=add",
+                &format!("DEVPATH={}", devpath),
+                "SUBSYSTEM=hidraw",
+                "DEVNAME=hidraw3",
+                "SEQNUM=4242",
+            ],
+        );
+
+        let event = parse_uevent(&buf).unwrap();
+        assert_eq!(event.action, "add");
+        assert_eq!(event.devname.as_deref(), Some("hidraw3"));
+        assert_eq!(event.device(), Some(DeviceKind::Lcd));
+    }
+
+    #[test]
+    fn test_parse_hid_remove() {
+        let buf = message(
+            "remove@/devices/usb1/1-2/1-2:1.1/0003:04D9:FD01.0005",
+            &[
+                "ACTION=remove",
+                "SUBSYSTEM=hid",
+                "HID_ID=0003:000004D9:0000FD01",
+            ],
+        );
+        let event = parse_uevent(&buf).unwrap();
+        assert_eq!(event.action, "remove");
+        assert_eq!(event.device(), Some(DeviceKind::Lcd));
+    }
+
+    #[test]
+    fn test_parse_tty_add() {
+        let buf = message(
+            "add@/devices/usb1/1-3/1-3:1.0/ttyUSB0/tty/ttyUSB0",
+            &["ACTION=add", "SUBSYSTEM=tty", "DEVNAME=ttyUSB0"],
+        );
+        assert_eq!(parse_uevent(&buf).unwrap().device(), Some(DeviceKind::Led));
+    }
+
+    #[test]
+    fn test_ignores_other_devices() {
+        let buf = message(
+            "add@/devices/usb1/1-4/1-4:1.0/0003:046D:C52B.0006/hidraw/hidraw4",
+            &[
+                "ACTION=add",
+                "DEVPATH=/devices/usb1/1-4/1-4:1.0/0003:046D:C52B.0006/hidraw/hidraw4",
+                "SUBSYSTEM=hidraw",
+            ],
+        );
+        assert_eq!(parse_uevent(&buf).unwrap().device(), None);
+
+        // udev daemon broadcasts carry a binary header instead of action@devpath
+        assert_eq!(parse_uevent(b"libudev\0\xfe\xed\xca\xfe"), None);
+    }
+}
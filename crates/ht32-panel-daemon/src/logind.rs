@@ -0,0 +1,619 @@
+//! Session and power management through logind.
+//!
+//! Watches `org.freedesktop.login1` on the system bus and blanks every panel
+//! (black frames, LEDs off) while the session is locked or idle and while
+//! the machine suspends. Unless a session is configured, the session in the
+//! foreground on seat0 is followed across logins and user switches. A delay
+//! inhibitor gives the panels time to go dark before the machine sleeps. On
+//! resume the LCDs are re-opened, which sends the heartbeat and orientation
+//! again, and the LED theme is restored once the panels are unblanked.
+
+use std::sync::Arc;
+
+use futures::stream::{self, BoxStream, StreamExt};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use zbus::proxy;
+use zbus::zvariant::{OwnedFd, OwnedObjectPath};
+use zbus::Connection;
+
+use crate::config::LogindConfig;
+use crate::dbus::DaemonSignals;
+use crate::state::{AppState, PanelState};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    /// Returns the object path of a session.
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// Takes an inhibitor lock, held until the returned descriptor is closed.
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
+    /// Emitted before suspending (`true`) and after resuming (`false`).
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Seat",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/seat/seat0"
+)]
+trait Seat {
+    /// Id and object path of the session in the foreground.
+    #[zbus(property)]
+    fn active_session(&self) -> zbus::Result<(String, OwnedObjectPath)>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    /// Emitted when the session should be locked.
+    #[zbus(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+
+    /// Emitted when the session should be unlocked.
+    #[zbus(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+}
+
+/// A change reported by logind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    /// The machine is about to suspend (`true`) or has resumed (`false`).
+    Sleep(bool),
+    /// The session was locked or unlocked.
+    Locked(bool),
+    /// The session became idle or active.
+    Idle(bool),
+    /// Another session came to the foreground on seat0.
+    ActiveSession(OwnedObjectPath),
+}
+
+/// What logind last told us about the machine and session.
+#[derive(Debug, Default, Clone, Copy)]
+struct SessionStatus {
+    asleep: bool,
+    locked: bool,
+    idle: bool,
+}
+
+impl SessionStatus {
+    /// Returns true if the panels should be blanked.
+    fn blank(&self, config: &LogindConfig) -> bool {
+        self.asleep || (config.blank_on_lock && self.locked) || (config.blank_on_idle && self.idle)
+    }
+}
+
+/// Watches logind and blanks the panels while the session is away.
+pub async fn run_logind_watcher(state: Arc<AppState>, signal_tx: broadcast::Sender<DaemonSignals>) {
+    let connection = match Connection::system().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!(
+                "Failed to connect to the system bus: {}. Panels won't blank on lock or suspend.",
+                e
+            );
+            return;
+        }
+    };
+    // A service started at boot runs outside any session
+    let session_id = state
+        .config()
+        .logind
+        .session
+        .clone()
+        .or_else(|| std::env::var("XDG_SESSION_ID").ok());
+    if let Err(e) = watch(&connection, &state, &signal_tx, session_id).await {
+        warn!("logind watcher stopped: {}", e);
+    }
+}
+
+/// Follows logind on a connection until its signal streams end.
+///
+/// Watches the session with the given id, or else whichever session is in
+/// the foreground on seat0.
+async fn watch(
+    connection: &Connection,
+    state: &AppState,
+    signal_tx: &broadcast::Sender<DaemonSignals>,
+    session_id: Option<String>,
+) -> zbus::Result<()> {
+    let config = &state.config().logind;
+    let manager = ManagerProxy::new(connection).await?;
+
+    let mut events: Vec<BoxStream<'static, Event>> = vec![manager
+        .receive_prepare_for_sleep()
+        .await?
+        .filter_map(|signal| async move { signal.args().ok().map(|args| Event::Sleep(args.start)) })
+        .boxed()];
+
+    let mut status = SessionStatus::default();
+    let mut session_events = stream::pending().boxed();
+    let mut session_path = None;
+    match session_id {
+        Some(id) => match manager.get_session(&id).await {
+            Ok(path) => {
+                (session_events, session_path) =
+                    follow_session(connection, path, &mut status).await?;
+            }
+            Err(e) => warn!("logind session '{}' not found: {}", id, e),
+        },
+        None => match SeatProxy::new(connection).await {
+            Ok(seat) => {
+                // The stream starts with the current value
+                events.push(
+                    seat.receive_active_session_changed()
+                        .await
+                        .filter_map(|change| async move {
+                            change
+                                .get()
+                                .await
+                                .ok()
+                                .map(|(_, path)| Event::ActiveSession(path))
+                        })
+                        .boxed(),
+                );
+            }
+            Err(e) => debug!("No seat0: {}", e),
+        },
+    }
+
+    let mut inhibitor = take_inhibitor(&manager).await;
+    let mut blanked = false;
+    update(state, &status, &mut blanked).await;
+
+    let mut events = stream::select_all(events);
+    loop {
+        let event = tokio::select! {
+            event = events.next() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            Some(event) = session_events.next() => event,
+        };
+        debug!("logind: {:?}", event);
+        match event {
+            Event::Sleep(true) => {
+                status.asleep = true;
+                update(state, &status, &mut blanked).await;
+                // Push the black frame now; the render loop may be sleeping
+                for panel in state.panels() {
+                    if let Err(e) = panel.render_frame().await {
+                        debug!(
+                            "Panel '{}': blanking before suspend failed: {}",
+                            panel.id(),
+                            e
+                        );
+                    }
+                }
+                // Let the machine suspend
+                inhibitor = None;
+            }
+            Event::Sleep(false) => {
+                info!("Resumed from suspend, re-opening LCDs");
+                status.asleep = false;
+                for panel in state.panels() {
+                    if reopen_lcd(panel).await {
+                        let _ = signal_tx.send(DaemonSignals::ConnectionChanged {
+                            panel: panel.id().to_string(),
+                        });
+                    }
+                }
+                // The LED controller may have lost its theme, so switch the
+                // LEDs off again if still locked, or restore the theme
+                blanked = status.blank(config);
+                for panel in state.panels() {
+                    if blanked {
+                        panel.blank().await;
+                    } else {
+                        panel.unblank();
+                    }
+                }
+                if inhibitor.is_none() {
+                    inhibitor = take_inhibitor(&manager).await;
+                }
+            }
+            Event::Locked(locked) => {
+                status.locked = locked;
+                update(state, &status, &mut blanked).await;
+            }
+            Event::Idle(idle) => {
+                status.idle = idle;
+                update(state, &status, &mut blanked).await;
+            }
+            Event::ActiveSession(path) => {
+                if session_path.as_ref() == Some(&path) {
+                    continue;
+                }
+                status.locked = false;
+                status.idle = false;
+                match follow_session(connection, path.clone(), &mut status).await {
+                    Ok(followed) => (session_events, session_path) = followed,
+                    Err(e) => {
+                        warn!("Failed to watch logind session {}: {}", path.as_str(), e);
+                        session_events = stream::pending().boxed();
+                        session_path = Some(path);
+                    }
+                }
+                update(state, &status, &mut blanked).await;
+            }
+        }
+    }
+    drop(inhibitor);
+    Ok(())
+}
+
+/// Blanks or unblanks the panels when the status calls for it.
+async fn update(state: &AppState, status: &SessionStatus, blanked: &mut bool) {
+    let blank = status.blank(&state.config().logind);
+    if blank == *blanked {
+        return;
+    }
+    *blanked = blank;
+    for panel in state.panels() {
+        if blank {
+            panel.blank().await;
+        } else {
+            panel.unblank();
+        }
+    }
+}
+
+/// Subscribes to a session's lock and idle changes and reads its current
+/// state. The path "/" stands for no session, e.g. before anyone logs in.
+async fn follow_session(
+    connection: &Connection,
+    path: OwnedObjectPath,
+    status: &mut SessionStatus,
+) -> zbus::Result<(BoxStream<'static, Event>, Option<OwnedObjectPath>)> {
+    if path.as_str() == "/" {
+        info!("No logind session in the foreground; panels only blank on suspend");
+        return Ok((stream::pending().boxed(), Some(path)));
+    }
+    let session = SessionProxy::builder(connection)
+        .path(path.clone())?
+        .build()
+        .await?;
+    info!("Watching logind session {}", path.as_str());
+    let events = stream::select_all([
+        session
+            .receive_lock()
+            .await?
+            .map(|_| Event::Locked(true))
+            .boxed(),
+        session
+            .receive_unlock()
+            .await?
+            .map(|_| Event::Locked(false))
+            .boxed(),
+        session
+            .receive_idle_hint_changed()
+            .await
+            .filter_map(|change| async move { change.get().await.ok().map(Event::Idle) })
+            .boxed(),
+    ])
+    .boxed();
+    // Read after subscribing so no change is missed in between
+    status.locked = session.locked_hint().await.unwrap_or(false);
+    status.idle = session.idle_hint().await.unwrap_or(false);
+    Ok((events, Some(path)))
+}
+
+/// Delays suspend until the panels are blanked.
+async fn take_inhibitor(manager: &ManagerProxy<'_>) -> Option<OwnedFd> {
+    match manager
+        .inhibit(
+            "sleep",
+            "ht32paneld",
+            "Blank the panel before suspending",
+            "delay",
+        )
+        .await
+    {
+        Ok(fd) => Some(fd),
+        Err(e) => {
+            debug!("Failed to take a sleep inhibitor: {}", e);
+            None
+        }
+    }
+}
+
+/// Re-opens a panel's LCD on the blocking thread pool.
+async fn reopen_lcd(panel: &Arc<PanelState>) -> bool {
+    let panel = panel.clone();
+    match tokio::task::spawn_blocking(move || panel.reopen_lcd()).await {
+        Ok(changed) => changed,
+        Err(e) => {
+            warn!("Re-opening LCD failed: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Bus};
+    use ht32_panel_hw::lcd::LcdEvent;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use zbus::interface;
+    use zbus::object_server::SignalEmitter;
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_31";
+    const OTHER_SESSION_PATH: &str = "/org/freedesktop/login1/session/_32";
+    const SEAT_PATH: &str = "/org/freedesktop/login1/seat/seat0";
+
+    /// Notes when the watcher takes its inhibitor, the last step of setting up.
+    struct MockManager {
+        inhibited: Arc<AtomicBool>,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Manager")]
+    impl MockManager {
+        fn get_session(&self, session_id: &str) -> zbus::fdo::Result<OwnedObjectPath> {
+            match session_id {
+                "1" => Ok(OwnedObjectPath::try_from(SESSION_PATH).unwrap()),
+                _ => Err(zbus::fdo::Error::Failed("No such session".to_string())),
+            }
+        }
+
+        fn inhibit(
+            &self,
+            _what: &str,
+            _who: &str,
+            _why: &str,
+            _mode: &str,
+        ) -> zbus::fdo::Result<OwnedFd> {
+            let file = std::fs::File::open("/dev/null")
+                .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+            self.inhibited.store(true, Ordering::SeqCst);
+            Ok(std::os::fd::OwnedFd::from(file).into())
+        }
+
+        #[zbus(signal)]
+        async fn prepare_for_sleep(emitter: &SignalEmitter<'_>, start: bool) -> zbus::Result<()>;
+    }
+
+    struct MockSeat {
+        active: OwnedObjectPath,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Seat")]
+    impl MockSeat {
+        #[zbus(property)]
+        fn active_session(&self) -> (String, OwnedObjectPath) {
+            let id = self.active.as_str().rsplit('_').next().unwrap_or("");
+            (id.to_string(), self.active.clone())
+        }
+    }
+
+    struct MockSession {
+        locked: bool,
+        idle: bool,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Session")]
+    impl MockSession {
+        #[zbus(signal)]
+        async fn lock(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn unlock(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn locked_hint(&self) -> bool {
+            self.locked
+        }
+
+        #[zbus(property)]
+        fn idle_hint(&self) -> bool {
+            self.idle
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon"]
+    async fn test_blanks_on_lock_idle_and_suspend() {
+        let bus = Bus::start();
+        let inhibited = Arc::new(AtomicBool::new(false));
+        let login1 = bus
+            .connect()
+            .name("org.freedesktop.login1")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/login1",
+                MockManager {
+                    inhibited: inhibited.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                SESSION_PATH,
+                MockSession {
+                    locked: false,
+                    idle: false,
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (state, recording) = test_support::app_state("logind", |_| {});
+        let panel = state.default_panel().clone();
+
+        let client = bus.connect().build().await.unwrap();
+        let (signal_tx, _) = broadcast::channel(16);
+        let watcher_state = state.clone();
+        tokio::spawn(async move {
+            watch(&client, &watcher_state, &signal_tx, Some("1".to_string())).await
+        });
+        test_support::wait_for("subscriptions", || inhibited.load(Ordering::SeqCst)).await;
+
+        let session = login1
+            .object_server()
+            .interface::<_, MockSession>(SESSION_PATH)
+            .await
+            .unwrap();
+        MockSession::lock(session.signal_emitter()).await.unwrap();
+        test_support::wait_for("lock", || panel.is_blanked()).await;
+        MockSession::unlock(session.signal_emitter()).await.unwrap();
+        test_support::wait_for("unlock", || !panel.is_blanked()).await;
+
+        session.get_mut().await.idle = true;
+        session
+            .get()
+            .await
+            .idle_hint_changed(session.signal_emitter())
+            .await
+            .unwrap();
+        test_support::wait_for("idle", || panel.is_blanked()).await;
+        session.get_mut().await.idle = false;
+        session
+            .get()
+            .await
+            .idle_hint_changed(session.signal_emitter())
+            .await
+            .unwrap();
+        test_support::wait_for("active", || !panel.is_blanked()).await;
+
+        let emitter = SignalEmitter::new(&login1, "/org/freedesktop/login1").unwrap();
+        MockManager::prepare_for_sleep(&emitter, true)
+            .await
+            .unwrap();
+        test_support::wait_for("suspend", || panel.is_blanked()).await;
+        recording.take_events();
+        MockManager::prepare_for_sleep(&emitter, false)
+            .await
+            .unwrap();
+        test_support::wait_for("resume", || !panel.is_blanked()).await;
+
+        // The LCD was opened again and initialized
+        let events = recording.take_events();
+        assert!(matches!(events[0], LcdEvent::Heartbeat { .. }));
+        assert_eq!(events[1], LcdEvent::Orientation { portrait: false });
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon"]
+    async fn test_follows_active_session() {
+        let bus = Bus::start();
+        let inhibited = Arc::new(AtomicBool::new(false));
+        let login1 = bus
+            .connect()
+            .name("org.freedesktop.login1")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/login1",
+                MockManager {
+                    inhibited: inhibited.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                SEAT_PATH,
+                MockSeat {
+                    active: OwnedObjectPath::try_from("/").unwrap(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                SESSION_PATH,
+                MockSession {
+                    locked: true,
+                    idle: false,
+                },
+            )
+            .unwrap()
+            .serve_at(
+                OTHER_SESSION_PATH,
+                MockSession {
+                    locked: false,
+                    idle: false,
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (state, _recording) = test_support::app_state("logind-seat", |_| {});
+        let panel = state.default_panel().clone();
+
+        let client = bus.connect().build().await.unwrap();
+        let (signal_tx, _) = broadcast::channel(16);
+        let watcher_state = state.clone();
+        tokio::spawn(async move { watch(&client, &watcher_state, &signal_tx, None).await });
+        test_support::wait_for("subscriptions", || inhibited.load(Ordering::SeqCst)).await;
+        assert!(!panel.is_blanked());
+
+        let server = login1.object_server();
+        let seat = server.interface::<_, MockSeat>(SEAT_PATH).await.unwrap();
+        let switch_to = |path: &'static str| {
+            let seat = seat.clone();
+            async move {
+                seat.get_mut().await.active = OwnedObjectPath::try_from(path).unwrap();
+                seat.get()
+                    .await
+                    .active_session_changed(seat.signal_emitter())
+                    .await
+                    .unwrap();
+            }
+        };
+        let first = server
+            .interface::<_, MockSession>(SESSION_PATH)
+            .await
+            .unwrap();
+        let other = server
+            .interface::<_, MockSession>(OTHER_SESSION_PATH)
+            .await
+            .unwrap();
+
+        // A login at the locked greeter, then unlocking it
+        switch_to(SESSION_PATH).await;
+        test_support::wait_for("locked session", || panel.is_blanked()).await;
+        MockSession::unlock(first.signal_emitter()).await.unwrap();
+        test_support::wait_for("unlock", || !panel.is_blanked()).await;
+
+        // Switching users follows the new session
+        MockSession::lock(first.signal_emitter()).await.unwrap();
+        test_support::wait_for("lock", || panel.is_blanked()).await;
+        switch_to(OTHER_SESSION_PATH).await;
+        test_support::wait_for("user switch", || !panel.is_blanked()).await;
+        MockSession::lock(other.signal_emitter()).await.unwrap();
+        test_support::wait_for("lock of new session", || panel.is_blanked()).await;
+        MockSession::unlock(other.signal_emitter()).await.unwrap();
+        test_support::wait_for("unlock of new session", || !panel.is_blanked()).await;
+    }
+
+    #[test]
+    fn test_blank_follows_config() {
+        let mut config = LogindConfig::default();
+        let locked = SessionStatus {
+            locked: true,
+            ..Default::default()
+        };
+        assert!(locked.blank(&config));
+        config.blank_on_lock = false;
+        assert!(!locked.blank(&config));
+        let asleep = SessionStatus {
+            asleep: true,
+            ..Default::default()
+        };
+        assert!(asleep.blank(&config));
+        assert!(!SessionStatus::default().blank(&config));
+    }
+}
@@ -2,25 +2,35 @@
 //!
 //! Background service with HTMX web UI and D-Bus interface for LCD and LED control.
 
+mod alerts;
+mod carousel;
 mod config;
+mod custom_values;
 mod dbus;
 mod faces;
+mod hotplug;
+mod logind;
+mod metrics;
+mod notification_monitor;
+mod notifications;
 mod rendering;
+mod schedule;
 mod sensors;
 mod state;
+mod systemd;
+#[cfg(test)]
+mod test_support;
 mod web;
 
 use anyhow::{Context, Result};
-use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpListener;
 use tokio::sync::broadcast;
 use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
 use config::Config;
 use dbus::DaemonSignals;
-use state::AppState;
+use state::{AppState, PanelState};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -40,6 +50,14 @@ async fn main() -> Result<()> {
     // Initialize application state
     let state = Arc::new(AppState::new(config)?);
 
+    // Open the web server's socket first, so a bad one stops the daemon
+    // before it starts driving the panels
+    let web_listener = if state.config().web.enable {
+        Some(web::Listener::open(&state.config().web)?)
+    } else {
+        None
+    };
+
     // Create channels for D-Bus signals and shutdown
     let (signal_tx, _signal_rx) = broadcast::channel::<DaemonSignals>(16);
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
@@ -66,38 +84,86 @@ async fn main() -> Result<()> {
             }
         };
 
-    // Start render loop
-    let render_state = state.clone();
-    tokio::spawn(async move {
-        render_loop(render_state).await;
-    });
-
-    // Start heartbeat loop
-    let heartbeat_state = state.clone();
+    // Start render and heartbeat loops for every panel
+    let liveness = systemd::Liveness::default();
     let heartbeat_interval = state.config().heartbeat;
+    for panel in state.panels() {
+        let render_panel = panel.clone();
+        let render_signal_tx = signal_tx.clone();
+        let render_beat = liveness.register(format!("render loop of '{}'", panel.id()));
+        tokio::spawn(async move {
+            render_loop(render_panel, render_signal_tx, render_beat).await;
+        });
+
+        let heartbeat_panel = panel.clone();
+        let heartbeat_beat = liveness.register(format!("heartbeat loop of '{}'", panel.id()));
+        tokio::spawn(async move {
+            heartbeat_loop(heartbeat_panel, heartbeat_interval, heartbeat_beat).await;
+        });
+    }
+
+    // Report readiness, status and liveness to systemd
+    let notifier = systemd::Notifier::from_env().map(Arc::new);
+    if let Some(notifier) = &notifier {
+        systemd::notify_ready(notifier, &state).await;
+        if let Some(timeout) = systemd::watchdog_timeout() {
+            tokio::spawn(systemd::run_watchdog(
+                notifier.clone(),
+                liveness.clone(),
+                timeout,
+            ));
+        }
+        tokio::spawn(systemd::run_status(
+            notifier.clone(),
+            state.clone(),
+            signal_tx.clone(),
+        ));
+    }
+
+    // Attach and detach LCDs as they are plugged in and removed
+    let hotplug_state = state.clone();
+    let hotplug_signal_tx = signal_tx.clone();
     tokio::spawn(async move {
-        heartbeat_loop(heartbeat_state, heartbeat_interval).await;
+        hotplug::run_hotplug_watcher(hotplug_state, hotplug_signal_tx).await;
     });
 
+    // Run the commands that set custom values
+    if !state.config().custom_values.is_empty() {
+        let custom_state = state.clone();
+        tokio::spawn(async move {
+            custom_values::run_custom_commands(custom_state).await;
+        });
+    }
+
+    // Mirror desktop notifications onto the panels
+    if state.config().desktop_notifications.enable {
+        let monitor_state = state.clone();
+        let monitor_signal_tx = signal_tx.clone();
+        tokio::spawn(async move {
+            notification_monitor::run_notification_monitor(monitor_state, monitor_signal_tx).await;
+        });
+    }
+
+    // Blank panels while the session is idle, locked or suspended
+    if state.config().logind.enable {
+        let logind_state = state.clone();
+        let logind_signal_tx = signal_tx.clone();
+        tokio::spawn(async move {
+            logind::run_logind_watcher(logind_state, logind_signal_tx).await;
+        });
+    }
+
     // Setup Unix signal handlers
     let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
     let mut sigint = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?;
 
     // Optionally start web server
-    if state.config().web.enable {
+    if let Some(listener) = web_listener {
         let app = web::create_router(state.clone(), signal_tx.clone());
-        let addr: SocketAddr = state
-            .config()
-            .web
-            .listen
-            .parse()
-            .context("Invalid listen address")?;
-        let listener = TcpListener::bind(addr).await?;
-        info!("Web server listening on http://{}", addr);
 
         // Run server with shutdown handling
         tokio::select! {
-            result = axum::serve(listener, app) => {
+            result = web::serve(app, listener, &state.config().web) => {
                 result?;
             }
             _ = shutdown_rx.recv() => {
@@ -126,41 +192,104 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(notifier) = &notifier {
+        notifier.notify("STOPPING=1");
+    }
     Ok(())
 }
 
-async fn render_loop(state: Arc<AppState>) {
+/// Time between frames while the display is switched off.
+const DISPLAY_OFF_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+async fn render_loop(
+    state: Arc<PanelState>,
+    signal_tx: broadcast::Sender<DaemonSignals>,
+    beat: systemd::Beat,
+) {
     let mut consecutive_errors: u32 = 0;
     let mut last_error_log = std::time::Instant::now();
 
     loop {
-        if let Err(e) = state.render_frame().await {
+        if state.apply_schedule().await {
+            let panel = state.id().to_string();
+            let _ = signal_tx.send(DaemonSignals::ScheduleChanged {
+                panel: panel.clone(),
+            });
+            let _ = signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: panel.clone(),
+            });
+            let _ = signal_tx.send(DaemonSignals::LedChanged { panel });
+        }
+        if state.advance_carousel() {
+            let _ = signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: state.id().to_string(),
+            });
+        }
+        let rendered = state.render_frame().await;
+        if let Err(e) = &rendered {
             consecutive_errors += 1;
             let elapsed = last_error_log.elapsed();
             if consecutive_errors == 1 || elapsed >= std::time::Duration::from_secs(60) {
                 if consecutive_errors > 1 {
                     warn!(
-                        "Render error (repeated {} times in {:?}): {}",
-                        consecutive_errors, elapsed, e
+                        "Panel '{}': render error (repeated {} times in {:?}): {}",
+                        state.id(),
+                        consecutive_errors,
+                        elapsed,
+                        e
                     );
                 } else {
-                    warn!("Render error: {}", e);
+                    warn!("Panel '{}': render error: {}", state.id(), e);
                 }
                 last_error_log = std::time::Instant::now();
             }
         } else {
             consecutive_errors = 0;
         }
-        let ms = state.refresh_interval_ms();
-        tokio::time::sleep(std::time::Duration::from_millis(ms as u64)).await;
+        for alert in state.take_raised_alerts() {
+            info!(
+                "Panel '{}': {} reached {} level ({:.1})",
+                state.id(),
+                alert.metric,
+                alert.level,
+                alert.value
+            );
+            let _ = signal_tx.send(DaemonSignals::AlertRaised {
+                panel: state.id().to_string(),
+                metric: alert.metric.to_string(),
+                level: alert.level.to_string(),
+                value: alert.value,
+            });
+        }
+        // Wake up early when the carousel is due to switch faces or a
+        // notification goes away
+        let mut interval = if state.is_display_off() || state.is_blanked() {
+            DISPLAY_OFF_INTERVAL
+        } else {
+            std::time::Duration::from_millis(state.refresh_interval_ms() as u64)
+        };
+        for remaining in [state.carousel_remaining(), state.notification_remaining()]
+            .into_iter()
+            .flatten()
+        {
+            interval = interval.min(remaining);
+        }
+        // A loop whose frames keep failing counts as stalled, so the
+        // watchdog restarts the daemon
+        if rendered.is_ok() {
+            beat.completed(interval);
+        }
+        state.wait_for_frame(interval).await;
     }
 }
 
-async fn heartbeat_loop(state: Arc<AppState>, interval_ms: u64) {
+async fn heartbeat_loop(state: Arc<PanelState>, interval_ms: u64, beat: systemd::Beat) {
     let interval = std::time::Duration::from_millis(interval_ms);
     let mut consecutive_errors: u32 = 0;
     let mut last_error_log = std::time::Instant::now();
 
+    // The first heartbeat goes out after one interval
+    beat.completed(interval);
     loop {
         tokio::time::sleep(interval).await;
         if let Err(e) = state.send_heartbeat() {
@@ -169,16 +298,20 @@ async fn heartbeat_loop(state: Arc<AppState>, interval_ms: u64) {
             if consecutive_errors == 1 || elapsed >= std::time::Duration::from_secs(60) {
                 if consecutive_errors > 1 {
                     warn!(
-                        "Heartbeat error (repeated {} times in {:?}): {}",
-                        consecutive_errors, elapsed, e
+                        "Panel '{}': heartbeat error (repeated {} times in {:?}): {}",
+                        state.id(),
+                        consecutive_errors,
+                        elapsed,
+                        e
                     );
                 } else {
-                    warn!("Heartbeat error: {}", e);
+                    warn!("Panel '{}': heartbeat error: {}", state.id(), e);
                 }
                 last_error_log = std::time::Instant::now();
             }
         } else {
             consecutive_errors = 0;
+            beat.completed(interval);
         }
     }
 }
@@ -0,0 +1,314 @@
+//! Prometheus metrics.
+//!
+//! Exposes the latest sensor sample and the health of each panel in the
+//! Prometheus text format, served by the web server at `/metrics`.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::sensors::data::SystemData;
+use crate::state::AppState;
+
+/// Content type of the Prometheus text format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prefix of every metric name.
+const PREFIX: &str = "ht32_panel_";
+
+/// Render and heartbeat counters of a panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PanelStats {
+    /// Frames rendered and sent
+    pub frames: u64,
+    /// Frames that failed to render or send
+    pub render_errors: u64,
+    /// Heartbeats that failed to send
+    pub heartbeat_errors: u64,
+    /// Time taken by the last frame
+    pub last_frame: Option<Duration>,
+}
+
+impl PanelStats {
+    /// Records a frame that took `duration`.
+    pub fn record_frame(&mut self, duration: Duration, ok: bool) {
+        if ok {
+            self.frames += 1;
+        } else {
+            self.render_errors += 1;
+        }
+        self.last_frame = Some(duration);
+    }
+}
+
+/// Kind of a metric family.
+#[derive(Clone, Copy)]
+enum Kind {
+    Gauge,
+    Counter,
+}
+
+/// Builds a page of metrics in the Prometheus text format.
+#[derive(Default)]
+struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    /// Starts a metric family.
+    fn family(&mut self, name: &str, help: &str, kind: Kind) {
+        let kind = match kind {
+            Kind::Gauge => "gauge",
+            Kind::Counter => "counter",
+        };
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.out, "# HELP {}{} {}", PREFIX, name, help);
+        let _ = writeln!(self.out, "# TYPE {}{} {}", PREFIX, name, kind);
+    }
+
+    /// Adds a sample to the current family.
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let _ = write!(self.out, "{}{}", PREFIX, name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {}", value);
+    }
+
+    /// Adds a family with a single sample.
+    fn single(&mut self, name: &str, help: &str, kind: Kind, value: f64) {
+        self.family(name, help, kind);
+        self.sample(name, &[], value);
+    }
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Renders the metrics of the daemon.
+pub fn render(app: &AppState) -> String {
+    let mut page = Exposition::default();
+
+    // Every panel samples the same machine; use the first panel's sample
+    if let Some(data) = app.panels().iter().find_map(|p| p.latest_data()) {
+        system_metrics(&mut page, &data);
+    }
+
+    let custom = app.custom_values().snapshot();
+    if !custom.is_empty() {
+        // Units go in the help text, so a changed unit keeps the series
+        let mut help = "Custom values set over D-Bus, HTTP or by commands.".to_string();
+        let units: Vec<String> = custom
+            .iter()
+            .filter(|(_, value)| !value.unit.is_empty())
+            .map(|(key, value)| format!("{} in {}", key, value.unit))
+            .collect();
+        if !units.is_empty() {
+            let _ = write!(help, " Units: {}.", units.join(", "));
+        }
+        page.family("custom_value", &help, Kind::Gauge);
+        for (key, value) in &custom {
+            page.sample("custom_value", &[("key", key)], value.value);
+        }
+    }
+
+    let panels: Vec<_> = app
+        .panels()
+        .iter()
+        .map(|p| (p.id().to_string(), p.is_lcd_connected(), p.stats()))
+        .collect();
+    let per_panel =
+        |page: &mut Exposition, name, help, kind, value: fn(bool, &PanelStats) -> f64| {
+            page.family(name, help, kind);
+            for (id, connected, stats) in &panels {
+                page.sample(name, &[("panel", id)], value(*connected, stats));
+            }
+        };
+    per_panel(
+        &mut page,
+        "lcd_connected",
+        "Whether the panel's LCD is connected.",
+        Kind::Gauge,
+        |connected, _| f64::from(u8::from(connected)),
+    );
+    per_panel(
+        &mut page,
+        "frames_rendered_total",
+        "Frames rendered.",
+        Kind::Counter,
+        |_, stats| stats.frames as f64,
+    );
+    per_panel(
+        &mut page,
+        "render_errors_total",
+        "Frames that failed to render or send.",
+        Kind::Counter,
+        |_, stats| stats.render_errors as f64,
+    );
+    per_panel(
+        &mut page,
+        "heartbeat_errors_total",
+        "Heartbeats that failed to send.",
+        Kind::Counter,
+        |_, stats| stats.heartbeat_errors as f64,
+    );
+    per_panel(
+        &mut page,
+        "last_frame_duration_seconds",
+        "Time taken to render and send the last frame.",
+        Kind::Gauge,
+        |_, stats| stats.last_frame.map_or(0.0, |d| d.as_secs_f64()),
+    );
+
+    page.out
+}
+
+/// Adds the metrics of a sensor sample.
+fn system_metrics(page: &mut Exposition, data: &SystemData) {
+    page.single(
+        "cpu_usage_percent",
+        "CPU usage in percent.",
+        Kind::Gauge,
+        data.cpu_percent,
+    );
+    if !data.cpu_cores.is_empty() {
+        page.family(
+            "cpu_core_usage_percent",
+            "Usage of each CPU core in percent.",
+            Kind::Gauge,
+        );
+        for (core, percent) in data.cpu_cores.iter().enumerate() {
+            page.sample(
+                "cpu_core_usage_percent",
+                &[("core", &core.to_string())],
+                *percent,
+            );
+        }
+    }
+    if data.cpu_freq_mhz.iter().any(Option::is_some) {
+        page.family(
+            "cpu_core_frequency_hertz",
+            "Current frequency of each CPU core in hertz.",
+            Kind::Gauge,
+        );
+        for (core, mhz) in data.cpu_freq_mhz.iter().enumerate() {
+            if let Some(mhz) = mhz {
+                page.sample(
+                    "cpu_core_frequency_hertz",
+                    &[("core", &core.to_string())],
+                    f64::from(*mhz) * 1e6,
+                );
+            }
+        }
+    }
+    if let Some(load) = data.load_avg {
+        page.family("load_average", "System load average.", Kind::Gauge);
+        for (period, value) in ["1m", "5m", "15m"].iter().zip(load) {
+            page.sample("load_average", &[("period", period)], value);
+        }
+    }
+    if let Some(temp) = data.cpu_temp {
+        page.single(
+            "cpu_temperature_celsius",
+            "CPU temperature from the selected sensor.",
+            Kind::Gauge,
+            temp,
+        );
+    }
+    if !data.temperatures.is_empty() {
+        page.family(
+            "temperature_celsius",
+            "Temperature of each hwmon sensor.",
+            Kind::Gauge,
+        );
+        for (sensor, temp) in &data.temperatures {
+            page.sample("temperature_celsius", &[("sensor", sensor)], *temp);
+        }
+    }
+    page.single(
+        "memory_usage_percent",
+        "RAM usage in percent.",
+        Kind::Gauge,
+        data.ram_percent,
+    );
+    page.single(
+        "disk_read_bytes_per_second",
+        "Disk read rate.",
+        Kind::Gauge,
+        data.disk_read_rate,
+    );
+    page.single(
+        "disk_write_bytes_per_second",
+        "Disk write rate.",
+        Kind::Gauge,
+        data.disk_write_rate,
+    );
+    let interface = [("interface", data.net_interface.as_str())];
+    page.family(
+        "network_receive_bytes_per_second",
+        "Network receive rate of the monitored interface.",
+        Kind::Gauge,
+    );
+    page.sample(
+        "network_receive_bytes_per_second",
+        &interface,
+        data.net_rx_rate,
+    );
+    page.family(
+        "network_transmit_bytes_per_second",
+        "Network transmit rate of the monitored interface.",
+        Kind::Gauge,
+    );
+    page.sample(
+        "network_transmit_bytes_per_second",
+        &interface,
+        data.net_tx_rate,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::app_state;
+
+    #[tokio::test]
+    async fn test_render_exposes_samples_and_health() {
+        let (app, _) = app_state("metrics", |_| {});
+        let panel = app.default_panel();
+        panel.render_frame().await.unwrap();
+        app.custom_values().set("fan", 1200.0, "RPM").unwrap();
+
+        let page = render(&app);
+        assert!(page.contains("# TYPE ht32_panel_cpu_usage_percent gauge\n"));
+        assert!(page.contains("# TYPE ht32_panel_frames_rendered_total counter\n"));
+        assert!(page.contains("ht32_panel_frames_rendered_total{panel=\"default\"} 1\n"));
+        assert!(page.contains("ht32_panel_lcd_connected{panel=\"default\"} 1\n"));
+        assert!(page.contains("ht32_panel_custom_value{key=\"fan\"} 1200\n"));
+        assert!(page.contains(" Units: fan in RPM.\n"));
+        assert!(page.contains("ht32_panel_network_receive_bytes_per_second{interface="));
+
+        // Every sample belongs to a family declared before it
+        let mut families = Vec::new();
+        for line in page.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                families.push(rest.split(' ').next().unwrap().to_string());
+            } else if !line.starts_with('#') {
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert_eq!(families.last().map(String::as_str), Some(name), "{}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_escape_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,434 @@
+//! Mirrors desktop notifications onto the panels.
+//!
+//! Becomes a monitor on the session bus and watches for
+//! `org.freedesktop.Notifications.Notify` calls, whoever handles them. Each
+//! notification goes through the configured rules, which can drop it or
+//! change its severity and display time, and is then drawn as an overlay
+//! like notifications pushed over D-Bus or HTTP.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedValue;
+use zbus::{Connection, Message, MessageStream};
+
+use crate::dbus::DaemonSignals;
+use crate::notifications::{Severity, MAX_SECONDS};
+use crate::state::AppState;
+
+/// Match rule for the calls we mirror.
+const NOTIFY_MATCH_RULE: &str =
+    "type='method_call',interface='org.freedesktop.Notifications',member='Notify'";
+
+/// Urgency of a desktop notification, from its `urgency` hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl std::str::FromStr for Urgency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Urgency::Low),
+            "normal" => Ok(Urgency::Normal),
+            "critical" => Ok(Urgency::Critical),
+            _ => anyhow::bail!("Unknown urgency '{}' (expected low, normal or critical)", s),
+        }
+    }
+}
+
+impl Urgency {
+    fn from_hint(value: u8) -> Self {
+        match value {
+            0 => Urgency::Low,
+            2 => Urgency::Critical,
+            _ => Urgency::Normal,
+        }
+    }
+
+    /// Severity shown when no rule sets one.
+    fn severity(self) -> Severity {
+        match self {
+            Urgency::Low | Urgency::Normal => Severity::Info,
+            Urgency::Critical => Severity::Critical,
+        }
+    }
+}
+
+/// A rule deciding how matching desktop notifications are shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationRule {
+    /// Application name to match (case-insensitive); any when unset
+    #[serde(default)]
+    pub app: Option<String>,
+
+    /// Urgency to match (low, normal or critical); any when unset
+    #[serde(default)]
+    pub urgency: Option<String>,
+
+    /// Whether matching notifications are shown
+    #[serde(default = "default_show")]
+    pub show: bool,
+
+    /// Severity to show them with (info, warning or critical)
+    #[serde(default)]
+    pub severity: Option<String>,
+
+    /// Seconds to show them for
+    #[serde(default)]
+    pub seconds: Option<u32>,
+}
+
+fn default_show() -> bool {
+    true
+}
+
+impl NotificationRule {
+    /// Checks the urgency, severity and seconds.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref urgency) = self.urgency {
+            urgency
+                .parse::<Urgency>()
+                .map_err(|e| anyhow::anyhow!("desktop_notifications.rules: {}", e))?;
+        }
+        if let Some(ref severity) = self.severity {
+            severity
+                .parse::<Severity>()
+                .map_err(|e| anyhow::anyhow!("desktop_notifications.rules: {}", e))?;
+        }
+        if self
+            .seconds
+            .is_some_and(|s| !(1..=MAX_SECONDS).contains(&s))
+        {
+            anyhow::bail!(
+                "desktop_notifications.rules: seconds must be between 1 and {}",
+                MAX_SECONDS
+            );
+        }
+        Ok(())
+    }
+
+    fn matches(&self, app: &str, urgency: Urgency) -> bool {
+        self.app
+            .as_deref()
+            .is_none_or(|a| a.eq_ignore_ascii_case(app))
+            && self
+                .urgency
+                .as_deref()
+                .is_none_or(|u| u.parse::<Urgency>().is_ok_and(|u| u == urgency))
+    }
+}
+
+/// A desktop notification seen on the bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DesktopNotification {
+    app: String,
+    summary: String,
+    body: String,
+    urgency: Urgency,
+}
+
+/// Arguments of `org.freedesktop.Notifications.Notify`.
+type NotifyArgs = (
+    String,
+    u32,
+    String,
+    String,
+    String,
+    Vec<String>,
+    HashMap<String, OwnedValue>,
+    i32,
+);
+
+impl DesktopNotification {
+    /// Parses the arguments of a `Notify` call.
+    fn from_message(message: &Message) -> Option<Self> {
+        let header = message.header();
+        if header.message_type() != MessageType::MethodCall
+            || header.member().map(|m| m.as_str()) != Some("Notify")
+        {
+            return None;
+        }
+        let (app, _replaces_id, _icon, summary, body, _actions, hints, _timeout): NotifyArgs =
+            message.body().deserialize().ok()?;
+        let urgency = hints
+            .get("urgency")
+            .and_then(|v| u8::try_from(&**v).ok())
+            .map_or(Urgency::Normal, Urgency::from_hint);
+        Some(Self {
+            app,
+            summary,
+            body,
+            urgency,
+        })
+    }
+
+    /// Returns the text to show: the summary and the body without markup.
+    fn text(&self) -> String {
+        let body = strip_markup(&self.body);
+        let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+        match (self.summary.trim(), body.as_str()) {
+            (summary, "") => summary.to_string(),
+            ("", body) => body.to_string(),
+            (summary, body) => format!("{}: {}", summary, body),
+        }
+    }
+}
+
+/// Removes the tags of the markup notification bodies may contain.
+fn strip_markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Decides how a notification is shown: its severity and seconds, or `None`
+/// to drop it. The first matching rule wins.
+fn decide(
+    rules: &[NotificationRule],
+    default_seconds: u32,
+    notification: &DesktopNotification,
+) -> Option<(Severity, u32)> {
+    let rule = rules
+        .iter()
+        .find(|r| r.matches(&notification.app, notification.urgency));
+    if rule.is_some_and(|r| !r.show) {
+        return None;
+    }
+    let severity = rule
+        .and_then(|r| r.severity.as_deref())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(notification.urgency.severity());
+    let seconds = rule.and_then(|r| r.seconds).unwrap_or(default_seconds);
+    Some((severity, seconds))
+}
+
+/// Monitors the session bus and mirrors desktop notifications.
+pub async fn run_notification_monitor(
+    state: Arc<AppState>,
+    signal_tx: broadcast::Sender<DaemonSignals>,
+) {
+    let connection = match Connection::session().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!(
+                "Failed to connect to the session bus: {}. Desktop notifications won't be mirrored.",
+                e
+            );
+            return;
+        }
+    };
+    if let Err(e) = monitor(connection, &state, &signal_tx).await {
+        warn!("Desktop notification monitor stopped: {}", e);
+    }
+}
+
+/// Turns the connection into a monitor and mirrors notifications until it
+/// closes.
+async fn monitor(
+    connection: Connection,
+    state: &AppState,
+    signal_tx: &broadcast::Sender<DaemonSignals>,
+) -> zbus::Result<()> {
+    connection
+        .call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus.Monitoring"),
+            "BecomeMonitor",
+            &(&[NOTIFY_MATCH_RULE] as &[&str], 0u32),
+        )
+        .await?;
+    info!("Mirroring desktop notifications");
+
+    let config = &state.config().desktop_notifications;
+    let mut stream = MessageStream::from(connection);
+    while let Some(message) = stream.try_next().await? {
+        let Some(notification) = DesktopNotification::from_message(&message) else {
+            continue;
+        };
+        let Some((severity, seconds)) = decide(&config.rules, config.seconds, &notification) else {
+            debug!("Dropped desktop notification from '{}'", notification.app);
+            continue;
+        };
+        let text = notification.text();
+        for panel in state
+            .panels()
+            .iter()
+            .filter(|p| config.panels.is_empty() || config.panels.iter().any(|id| id == p.id()))
+        {
+            match panel.show_notification(&text, severity, seconds) {
+                Ok(shown) => {
+                    let _ = signal_tx.send(DaemonSignals::NotificationPosted {
+                        panel: panel.id().to_string(),
+                        id: shown.id,
+                        text: shown.text,
+                        severity: severity.to_string(),
+                    });
+                }
+                Err(e) => debug!(
+                    "Panel '{}': desktop notification skipped: {}",
+                    panel.id(),
+                    e
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Bus};
+    use std::time::Duration;
+    use zbus::interface;
+
+    struct MockNotifications;
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl MockNotifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            _replaces_id: u32,
+            _app_icon: &str,
+            _summary: &str,
+            _body: &str,
+            _actions: Vec<String>,
+            _hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            1
+        }
+    }
+
+    fn notification(app: &str, urgency: Urgency) -> DesktopNotification {
+        DesktopNotification {
+            app: app.to_string(),
+            summary: "Build failed".to_string(),
+            body: String::new(),
+            urgency,
+        }
+    }
+
+    fn rule(app: Option<&str>, urgency: Option<&str>) -> NotificationRule {
+        NotificationRule {
+            app: app.map(str::to_string),
+            urgency: urgency.map(str::to_string),
+            show: true,
+            severity: None,
+            seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_rules_filter_and_override() {
+        let rules = vec![
+            NotificationRule {
+                show: false,
+                ..rule(None, Some("low"))
+            },
+            NotificationRule {
+                severity: Some("warning".to_string()),
+                seconds: Some(10),
+                ..rule(Some("ci"), None)
+            },
+        ];
+        assert_eq!(decide(&rules, 5, &notification("mail", Urgency::Low)), None);
+        assert_eq!(
+            decide(&rules, 5, &notification("CI", Urgency::Normal)),
+            Some((Severity::Warning, 10))
+        );
+        assert_eq!(
+            decide(&rules, 5, &notification("mail", Urgency::Critical)),
+            Some((Severity::Critical, 5))
+        );
+        assert!(rule(None, Some("urgent")).validate().is_err());
+    }
+
+    #[test]
+    fn test_text_joins_summary_and_plain_body() {
+        let mut n = notification("mail", Urgency::Normal);
+        n.body = "<b>3 new</b> messages &amp; a\ninvite".to_string();
+        assert_eq!(n.text(), "Build failed: 3 new messages & a invite");
+        n.summary.clear();
+        assert_eq!(n.text(), "3 new messages & a invite");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon"]
+    async fn test_mirrors_notify_calls_from_the_bus() {
+        let bus = Bus::start();
+
+        let (state, _recording) = test_support::app_state("notification-monitor", |config| {
+            config.desktop_notifications.rules = vec![NotificationRule {
+                show: false,
+                ..rule(Some("spam"), None)
+            }];
+        });
+        let panel = state.default_panel().clone();
+
+        let monitor_connection = bus.connect().build().await.unwrap();
+        let (signal_tx, _) = broadcast::channel(16);
+        let monitor_state = state.clone();
+        tokio::spawn(async move { monitor(monitor_connection, &monitor_state, &signal_tx).await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let _server = bus
+            .connect()
+            .name("org.freedesktop.Notifications")
+            .unwrap()
+            .serve_at("/org/freedesktop/Notifications", MockNotifications)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = bus.connect().build().await.unwrap();
+        for (app, summary) in [("spam", "Buy now"), ("backup", "Backup finished")] {
+            let mut hints: HashMap<&str, zbus::zvariant::Value> = HashMap::new();
+            hints.insert("urgency", 2u8.into());
+            client
+                .call_method(
+                    Some("org.freedesktop.Notifications"),
+                    "/org/freedesktop/Notifications",
+                    Some("org.freedesktop.Notifications"),
+                    "Notify",
+                    &(app, 0u32, "", summary, "", Vec::<&str>::new(), hints, -1i32),
+                )
+                .await
+                .unwrap();
+        }
+
+        test_support::wait_for("notification", || !panel.notification_history().is_empty()).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let history = panel.notification_history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].text, "Backup finished");
+        assert_eq!(history[0].severity, Severity::Critical);
+    }
+}
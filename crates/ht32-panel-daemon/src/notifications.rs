@@ -0,0 +1,332 @@
+//! On-screen notifications.
+//!
+//! Short messages pushed over D-Bus, HTTP or the CLI are queued per panel
+//! and drawn as a banner over the face, one at a time, for their number of
+//! seconds. Every notification is also kept in a bounded history.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+
+use crate::faces::Theme;
+use crate::rendering::Canvas;
+
+/// Longest time a notification can stay on screen, in seconds.
+pub const MAX_SECONDS: u32 = 3600;
+
+/// Seconds a notification stays on screen unless told otherwise.
+pub const DEFAULT_SECONDS: u32 = 5;
+
+/// Notifications waiting to be shown before new ones are rejected.
+const MAX_PENDING: usize = 32;
+
+/// Notifications kept in the history.
+const HISTORY_SIZE: usize = 50;
+
+/// Font size of the banner text.
+const FONT_SIZE: f32 = 18.0;
+
+/// Space around the banner text, in pixels.
+const PADDING: i32 = 6;
+
+/// Lines of text the banner wraps to.
+const MAX_LINES: usize = 2;
+
+/// Severity of a notification, which picks the banner color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "critical" => Ok(Severity::Critical),
+            _ => anyhow::bail!(
+                "Unknown severity '{}' (expected info, warning or critical)",
+                s
+            ),
+        }
+    }
+}
+
+impl Severity {
+    /// Returns the banner color for this severity.
+    fn color(self, theme: &Theme) -> u32 {
+        match self {
+            Severity::Info => theme.primary,
+            Severity::Warning => theme.warning,
+            Severity::Critical => theme.critical,
+        }
+    }
+}
+
+/// A notification pushed to a panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Id, unique per panel
+    pub id: u32,
+    /// Message text
+    pub text: String,
+    /// Severity
+    pub severity: Severity,
+    /// Seconds the notification stays on screen
+    pub seconds: u32,
+    /// When the notification was pushed
+    pub time: DateTime<Local>,
+}
+
+/// Queue and history of a panel's notifications.
+#[derive(Debug, Default)]
+pub struct NotificationQueue {
+    /// Notification on screen and when it goes away
+    current: Option<(Notification, Instant)>,
+    /// Since when the panel shows no notifications, e.g. while blanked
+    hidden_since: Option<Instant>,
+    pending: VecDeque<Notification>,
+    history: VecDeque<Notification>,
+    next_id: u32,
+}
+
+impl NotificationQueue {
+    /// Queues a notification.
+    pub fn push(&mut self, text: &str, severity: Severity, seconds: u32) -> Result<Notification> {
+        let text = text.trim();
+        if text.is_empty() {
+            anyhow::bail!("Notification text is empty");
+        }
+        if !(1..=MAX_SECONDS).contains(&seconds) {
+            anyhow::bail!("Seconds must be between 1 and {}", MAX_SECONDS);
+        }
+        if self.pending.len() >= MAX_PENDING {
+            anyhow::bail!("Too many notifications waiting to be shown");
+        }
+
+        self.next_id += 1;
+        let notification = Notification {
+            id: self.next_id,
+            text: text.to_string(),
+            severity,
+            seconds,
+            time: Local::now(),
+        };
+        self.pending.push_back(notification.clone());
+        if self.history.len() == HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification.clone());
+        Ok(notification)
+    }
+
+    /// Returns the notification to show at `now`, moving on to the next one
+    /// once the current one has expired.
+    pub fn current(&mut self, now: Instant) -> Option<&Notification> {
+        // The time spent hidden doesn't count against the one on screen
+        if let Some(since) = self.hidden_since.take() {
+            if let Some((_, until)) = &mut self.current {
+                *until += now.saturating_duration_since(since);
+            }
+        }
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(_, until)| now >= *until)
+        {
+            self.current = None;
+        }
+        if self.current.is_none() {
+            self.current = self.pending.pop_front().map(|n| {
+                let until = now + Duration::from_secs(n.seconds as u64);
+                (n, until)
+            });
+        }
+        self.current.as_ref().map(|(n, _)| n)
+    }
+
+    /// Stops the clock while nothing can be seen, so notifications wait
+    /// rather than expire unseen. [`current`](Self::current) resumes it.
+    pub fn hide(&mut self, now: Instant) {
+        self.hidden_since.get_or_insert(now);
+    }
+
+    /// Returns the time until the notification on screen goes away.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        if self.hidden_since.is_some() {
+            return None;
+        }
+        self.current
+            .as_ref()
+            .map(|(_, until)| until.saturating_duration_since(now))
+    }
+
+    /// Returns the history, oldest first.
+    pub fn history(&self) -> Vec<Notification> {
+        self.history.iter().cloned().collect()
+    }
+}
+
+/// Draws a notification as a banner along the bottom of the canvas.
+pub fn draw(canvas: &mut Canvas, theme: &Theme, notification: &Notification) {
+    let (width, height) = canvas.dimensions();
+    let max_width = width as i32 - 2 * PADDING;
+    let lines = wrap(canvas, &notification.text, max_width);
+    let line_height = canvas.line_height(FONT_SIZE);
+    let banner_height = (lines.len() as i32 * line_height + 2 * PADDING).min(height as i32);
+    let top = height as i32 - banner_height;
+
+    canvas.fill_rect(
+        0,
+        top,
+        width,
+        banner_height as u32,
+        notification.severity.color(theme),
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + PADDING + i as i32 * line_height;
+        if y + line_height > height as i32 {
+            break;
+        }
+        canvas.draw_text(PADDING, y, line, FONT_SIZE, theme.background);
+    }
+}
+
+/// Word-wraps text to at most [`MAX_LINES`] lines, ending with "..." if it
+/// doesn't fit.
+fn wrap(canvas: &Canvas, text: &str, max_width: i32) -> Vec<String> {
+    let fits = |s: &str| canvas.text_width(s, FONT_SIZE) <= max_width;
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < words.len() {
+        if lines.len() + 1 == MAX_LINES {
+            // The last line takes the rest of the text
+            lines.push(truncate(&words[start..].join(" "), &fits));
+            break;
+        }
+        let mut end = start + 1;
+        while end < words.len() && fits(&words[start..=end].join(" ")) {
+            end += 1;
+        }
+        lines.push(truncate(&words[start..end].join(" "), &fits));
+        start = end;
+    }
+    lines
+}
+
+/// Shortens a line until it fits, ending it with "...".
+fn truncate(line: &str, fits: &impl Fn(&str) -> bool) -> String {
+    if fits(line) {
+        return line.to_string();
+    }
+    let mut chars: Vec<char> = line.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let shortened = format!("{}...", chars.iter().collect::<String>().trim_end());
+        if fits(&shortened) {
+            return shortened;
+        }
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_shows_one_at_a_time() {
+        let mut queue = NotificationQueue::default();
+        let start = Instant::now();
+        queue.push("Backup finished", Severity::Info, 5).unwrap();
+        queue.push("Build failed", Severity::Critical, 2).unwrap();
+
+        assert_eq!(queue.current(start).unwrap().text, "Backup finished");
+        assert_eq!(queue.remaining(start), Some(Duration::from_secs(5)));
+        let later = start + Duration::from_secs(5);
+        assert_eq!(queue.current(later).unwrap().text, "Build failed");
+        assert!(queue.current(later + Duration::from_secs(2)).is_none());
+
+        let history = queue.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].id, 2);
+        assert_eq!(history[1].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_hidden_notifications_wait() {
+        let mut queue = NotificationQueue::default();
+        let start = Instant::now();
+        queue.push("Backup finished", Severity::Info, 5).unwrap();
+        queue.push("Build failed", Severity::Critical, 2).unwrap();
+        assert_eq!(queue.current(start).unwrap().text, "Backup finished");
+
+        // Hidden after two seconds for a minute
+        queue.hide(start + Duration::from_secs(2));
+        queue.hide(start + Duration::from_secs(30));
+        assert_eq!(queue.remaining(start + Duration::from_secs(30)), None);
+        let back = start + Duration::from_secs(62);
+        assert_eq!(queue.current(back).unwrap().text, "Backup finished");
+        assert_eq!(queue.remaining(back), Some(Duration::from_secs(3)));
+
+        // Nothing queued is taken while hidden
+        queue.hide(back + Duration::from_secs(3));
+        let later = back + Duration::from_secs(600);
+        assert_eq!(queue.current(later).unwrap().text, "Build failed");
+    }
+
+    #[test]
+    fn test_push_validates_and_bounds_history() {
+        let mut queue = NotificationQueue::default();
+        assert!(queue.push("  ", Severity::Info, 5).is_err());
+        assert!(queue.push("x", Severity::Info, 0).is_err());
+        assert!(queue.push("x", Severity::Info, MAX_SECONDS + 1).is_err());
+        assert_eq!("Warning".parse::<Severity>().unwrap(), Severity::Warning);
+        assert!("loud".parse::<Severity>().is_err());
+
+        for i in 0..MAX_PENDING {
+            queue.push(&i.to_string(), Severity::Info, 1).unwrap();
+        }
+        assert!(queue.push("one too many", Severity::Info, 1).is_err());
+
+        let mut now = Instant::now();
+        for _ in 0..HISTORY_SIZE {
+            queue.current(now);
+            now += Duration::from_secs(1);
+            queue.push("more", Severity::Info, 1).unwrap();
+        }
+        assert_eq!(queue.history().len(), HISTORY_SIZE);
+    }
+
+    #[test]
+    fn test_long_text_wraps_and_truncates() {
+        let canvas = Canvas::new(320, 170);
+        let text = "The nightly backup of the home directory finished after three hours \
+                    and copied a great many files to the remote server";
+        let lines = wrap(&canvas, text, 300);
+        assert_eq!(lines.len(), MAX_LINES);
+        assert!(lines[1].ends_with("..."));
+        for line in &lines {
+            assert!(canvas.text_width(line, FONT_SIZE) <= 300);
+        }
+        assert_eq!(wrap(&canvas, "Short", 300), vec!["Short".to_string()]);
+    }
+}
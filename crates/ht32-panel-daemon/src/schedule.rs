@@ -0,0 +1,385 @@
+//! Time-of-day schedules.
+//!
+//! Schedule rules switch a panel's face, color theme, brightness and LEDs at
+//! set times of the week, e.g. a dimmed display with the LEDs off at night.
+//! A rule lasts from its start time until its end time, or until another
+//! rule starts if it has no end. While no rule is active, the panel shows
+//! the settings it had before the first rule took over.
+
+use anyhow::Result;
+use chrono::{Datelike, Local, Timelike};
+use ht32_panel_hw::led::LedTheme;
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+/// A scheduled change of display and LED settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleRule {
+    /// Name reported as the active rule
+    pub name: String,
+
+    /// Time the rule starts ("HH:MM")
+    pub start: String,
+
+    /// Time the rule ends ("HH:MM"); without one it lasts until another
+    /// rule starts
+    #[serde(default)]
+    pub end: Option<String>,
+
+    /// Days the rule starts on ("mon" to "sun", "weekdays" or "weekends");
+    /// every day when empty
+    #[serde(default)]
+    pub days: Vec<String>,
+
+    /// Ids of the panels the rule applies to; all panels when empty
+    #[serde(default)]
+    pub panels: Vec<String>,
+
+    /// Face to show
+    #[serde(default)]
+    pub face: Option<String>,
+
+    /// Color theme to switch to
+    #[serde(default)]
+    pub theme: Option<String>,
+
+    /// LED theme (rainbow, breathing, colors, off or auto)
+    #[serde(default)]
+    pub led_theme: Option<String>,
+
+    /// LED intensity (1-5)
+    #[serde(default)]
+    pub led_intensity: Option<u8>,
+
+    /// LED speed (1-5)
+    #[serde(default)]
+    pub led_speed: Option<u8>,
+
+    /// Display brightness in percent (0-100)
+    #[serde(default)]
+    pub brightness: Option<u8>,
+
+    /// Switch the display off (`true`) or on (`false`)
+    #[serde(default)]
+    pub display_off: Option<bool>,
+}
+
+impl ScheduleRule {
+    /// Checks times, days and LED settings. Faces and themes are checked
+    /// once they are loaded.
+    pub fn validate(&self) -> Result<()> {
+        let context = |what: &str| format!("schedule '{}': {}", self.name, what);
+        if self.name.is_empty() {
+            anyhow::bail!("schedule rules need a name");
+        }
+        parse_time(&self.start)
+            .ok_or_else(|| anyhow::anyhow!(context(&format!("invalid start '{}'", self.start))))?;
+        if let Some(ref end) = self.end {
+            parse_time(end)
+                .ok_or_else(|| anyhow::anyhow!(context(&format!("invalid end '{}'", end))))?;
+        }
+        parse_days(&self.days)
+            .ok_or_else(|| anyhow::anyhow!(context(&format!("invalid days {:?}", self.days))))?;
+        if let Some(ref theme) = self.led_theme {
+            theme
+                .parse::<LedTheme>()
+                .map_err(|_| anyhow::anyhow!(context(&format!("unknown LED theme '{}'", theme))))?;
+        }
+        for (what, value) in [
+            ("led_intensity", self.led_intensity),
+            ("led_speed", self.led_speed),
+        ] {
+            if value.is_some_and(|v| !(1..=5).contains(&v)) {
+                anyhow::bail!(context(&format!("{} must be between 1 and 5", what)));
+            }
+        }
+        if self.brightness.is_some_and(|b| b > 100) {
+            anyhow::bail!(context("brightness must be between 0 and 100"));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the rule applies to the panel.
+    pub fn applies_to(&self, panel: &str) -> bool {
+        self.panels.is_empty() || self.panels.iter().any(|p| p == panel)
+    }
+
+    /// Returns true if the rule changes the LEDs.
+    pub fn sets_led(&self) -> bool {
+        self.led_theme.is_some() || self.led_intensity.is_some() || self.led_speed.is_some()
+    }
+
+    /// Returns the LED theme byte, if the rule sets one.
+    pub fn led_theme_byte(&self) -> Option<u8> {
+        self.led_theme
+            .as_deref()
+            .and_then(|t| t.parse::<LedTheme>().ok())
+            .map(|t| t as u8)
+    }
+}
+
+/// Parses "HH:MM" into minutes since midnight.
+fn parse_time(s: &str) -> Option<u32> {
+    let (hours, minutes) = s.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Parses day names into a Sunday-first mask.
+fn parse_days(days: &[String]) -> Option<[bool; 7]> {
+    if days.is_empty() {
+        return Some([true; 7]);
+    }
+    let mut mask = [false; 7];
+    for day in days {
+        match day.to_lowercase().as_str() {
+            "weekdays" => mask[1..6].fill(true),
+            "weekends" => {
+                mask[0] = true;
+                mask[6] = true;
+            }
+            name => {
+                let index = [
+                    "sunday",
+                    "monday",
+                    "tuesday",
+                    "wednesday",
+                    "thursday",
+                    "friday",
+                    "saturday",
+                ]
+                .iter()
+                .position(|d| name == *d || name == &d[..3])?;
+                mask[index] = true;
+            }
+        }
+    }
+    Some(mask)
+}
+
+/// A point in the week, in local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekTime {
+    /// Day of the week (0=Sunday, 6=Saturday)
+    pub day: u8,
+    /// Minutes since midnight
+    pub minute: u16,
+}
+
+impl WeekTime {
+    /// Returns the current local time.
+    pub fn now() -> Self {
+        let now = Local::now();
+        Self {
+            day: now.weekday().num_days_from_sunday() as u8,
+            minute: (now.hour() * 60 + now.minute()) as u16,
+        }
+    }
+
+    fn minute_of_week(self) -> u32 {
+        self.day as u32 * MINUTES_PER_DAY + self.minute as u32
+    }
+}
+
+/// A rule with its parsed times.
+#[derive(Debug)]
+struct Entry {
+    rule: ScheduleRule,
+    start: u32,
+    /// Length of the rule in minutes; `None` lasts until another rule starts
+    length: Option<u32>,
+    days: [bool; 7],
+}
+
+/// The schedule rules of one panel.
+#[derive(Debug, Default)]
+pub struct Schedule {
+    entries: Vec<Entry>,
+}
+
+impl Schedule {
+    /// Creates a schedule from validated rules, skipping invalid ones.
+    pub fn new(rules: Vec<ScheduleRule>) -> Self {
+        let entries = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let start = parse_time(&rule.start)?;
+                let length = match rule.end {
+                    Some(ref end) => {
+                        let end = parse_time(end)?;
+                        // An end equal to the start lasts a whole day
+                        Some((end + MINUTES_PER_DAY - start - 1) % MINUTES_PER_DAY + 1)
+                    }
+                    None => None,
+                };
+                let days = parse_days(&rule.days)?;
+                Some(Entry {
+                    rule,
+                    start,
+                    length,
+                    days,
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns the rules in order.
+    pub fn rules(&self) -> impl Iterator<Item = &ScheduleRule> {
+        self.entries.iter().map(|e| &e.rule)
+    }
+
+    /// Returns the rule at an index.
+    pub fn rule(&self, index: usize) -> Option<&ScheduleRule> {
+        self.entries.get(index).map(|e| &e.rule)
+    }
+
+    /// Returns the index of the rule with this name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|e| e.rule.name == name)
+    }
+
+    /// Returns the index of the rule active at the given time.
+    ///
+    /// The rule that started most recently wins, later rules breaking ties.
+    /// It is only active if its end hasn't passed yet.
+    pub fn active(&self, time: WeekTime) -> Option<usize> {
+        let now = time.minute_of_week();
+        let mut latest: Option<(u32, usize)> = None;
+        for (index, entry) in self.entries.iter().enumerate() {
+            for day in (0..7).filter(|&d| entry.days[d as usize]) {
+                let start = day * MINUTES_PER_DAY + entry.start;
+                let elapsed = (now + MINUTES_PER_WEEK - start) % MINUTES_PER_WEEK;
+                if latest.is_none_or(|(e, _)| elapsed <= e) {
+                    latest = Some((elapsed, index));
+                }
+            }
+        }
+        let (elapsed, index) = latest?;
+        match self.entries[index].length {
+            Some(length) if elapsed >= length => None,
+            _ => Some(index),
+        }
+    }
+}
+
+/// Settings a panel had before a schedule rule took over, persisted so they
+/// can be restored after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleRestore {
+    /// Name of the rule that is active
+    pub rule: String,
+    /// Face shown before the rule
+    pub face: String,
+    /// Color theme before the rule
+    pub theme: String,
+    /// LED theme before the rule
+    pub led_theme: u8,
+    /// LED intensity before the rule
+    pub led_intensity: u8,
+    /// LED speed before the rule
+    pub led_speed: u8,
+    /// Whether the carousel was rotating before the rule
+    #[serde(default)]
+    pub carousel: bool,
+    /// Display brightness before the rule
+    #[serde(default = "default_brightness")]
+    pub brightness: u8,
+    /// Whether the display was off before the rule
+    #[serde(default)]
+    pub display_off: bool,
+}
+
+fn default_brightness() -> u8 {
+    100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, start: &str, end: Option<&str>, days: &[&str]) -> ScheduleRule {
+        ScheduleRule {
+            name: name.to_string(),
+            start: start.to_string(),
+            end: end.map(str::to_string),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            panels: Vec::new(),
+            face: None,
+            theme: None,
+            led_theme: None,
+            led_intensity: None,
+            led_speed: None,
+            brightness: None,
+            display_off: None,
+        }
+    }
+
+    fn at(day: u8, time: &str) -> WeekTime {
+        WeekTime {
+            day,
+            minute: parse_time(time).unwrap() as u16,
+        }
+    }
+
+    fn schedule() -> Schedule {
+        Schedule::new(vec![
+            rule("night", "22:00", Some("07:00"), &[]),
+            rule("work", "09:00", None, &["weekdays"]),
+        ])
+    }
+
+    #[test]
+    fn test_parses_times_and_days() {
+        assert_eq!(parse_time("07:30"), Some(450));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("7"), None);
+
+        let days =
+            |names: &[&str]| parse_days(&names.iter().map(|d| d.to_string()).collect::<Vec<_>>());
+        assert_eq!(days(&[]), Some([true; 7]));
+        assert_eq!(
+            days(&["weekends", "Wednesday"]),
+            Some([true, false, false, true, false, false, true])
+        );
+        assert_eq!(days(&["someday"]), None);
+    }
+
+    #[test]
+    fn test_night_rule_spans_midnight() {
+        let schedule = schedule();
+        assert_eq!(schedule.active(at(1, "21:59")), Some(1));
+        assert_eq!(schedule.active(at(1, "22:00")), Some(0));
+        assert_eq!(schedule.active(at(2, "03:00")), Some(0));
+        assert_eq!(schedule.active(at(2, "07:00")), None);
+    }
+
+    #[test]
+    fn test_open_ended_rule_lasts_until_next_rule() {
+        let schedule = schedule();
+        assert_eq!(schedule.active(at(3, "09:00")), Some(1));
+        assert_eq!(schedule.active(at(3, "18:00")), Some(1));
+        // Weekends have no work rule, so nothing follows the night
+        assert_eq!(schedule.active(at(6, "12:00")), None);
+        assert_eq!(schedule.active(at(1, "08:59")), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_rules() {
+        assert!(rule("ok", "22:00", Some("07:00"), &["mon"])
+            .validate()
+            .is_ok());
+        assert!(rule("", "22:00", None, &[]).validate().is_err());
+        assert!(rule("bad", "25:00", None, &[]).validate().is_err());
+        assert!(rule("bad", "22:00", Some("x"), &[]).validate().is_err());
+        let led = ScheduleRule {
+            led_theme: Some("disco".to_string()),
+            ..rule("bad", "22:00", None, &[])
+        };
+        assert!(led.validate().is_err());
+    }
+}
@@ -1,16 +1,90 @@
 //! CPU usage sensor.
 
+use super::data::HISTORY_SIZE;
 use super::Sensor;
+use std::collections::VecDeque;
 use std::fs;
 use std::time::Instant;
 
+/// Idle and total jiffies from one `cpu` line of /proc/stat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Parses the counters of a `cpu`/`cpuN` line (without the label).
+    fn parse<'a>(fields: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let parts: Vec<u64> = fields.filter_map(|s| s.parse().ok()).collect();
+        if parts.len() >= 4 {
+            Some(Self {
+                idle: parts[3],
+                total: parts.iter().sum(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the usage percentage since an earlier reading, or `None` if
+    /// no time has passed.
+    fn usage_since(&self, earlier: &CpuTimes) -> Option<f64> {
+        let idle_delta = self.idle.saturating_sub(earlier.idle);
+        let total_delta = self.total.saturating_sub(earlier.total);
+        (total_delta > 0).then(|| 100.0 * (1.0 - (idle_delta as f64 / total_delta as f64)))
+    }
+}
+
+/// Usage tracking for one core.
+#[derive(Debug, Clone)]
+struct CoreState {
+    /// Core number, as in `cpuN`
+    id: usize,
+    times: CpuTimes,
+    percent: f64,
+}
+
+/// Parses /proc/stat into the aggregate times and the times of each online
+/// core, keyed by core number.
+fn parse_stat(content: &str) -> Option<(CpuTimes, Vec<(usize, CpuTimes)>)> {
+    let mut aggregate = None;
+    let mut cores = Vec::new();
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(label) = fields.next() else {
+            continue;
+        };
+        let Some(suffix) = label.strip_prefix("cpu") else {
+            // The cpu lines come first
+            break;
+        };
+        if suffix.is_empty() {
+            aggregate = CpuTimes::parse(fields);
+        } else if let (Ok(id), Some(times)) = (suffix.parse(), CpuTimes::parse(fields)) {
+            cores.push((id, times));
+        }
+    }
+
+    aggregate.map(|aggregate| (aggregate, cores))
+}
+
+/// Parses the 1, 5 and 15 minute load averages from /proc/loadavg.
+fn parse_loadavg(content: &str) -> Option<[f64; 3]> {
+    let mut fields = content.split_whitespace().map(|s| s.parse().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
 /// CPU usage sensor.
 pub struct CpuSensor {
     name: String,
-    last_idle: u64,
-    last_total: u64,
+    last_times: Option<CpuTimes>,
     last_sample: f64,
     last_time: Option<Instant>,
+    cores: Vec<CoreState>,
+    /// History of overall usage percentages
+    history: VecDeque<f64>,
 }
 
 impl CpuSensor {
@@ -18,29 +92,49 @@ impl CpuSensor {
     pub fn new() -> Self {
         Self {
             name: "cpu_usage".to_string(),
-            last_idle: 0,
-            last_total: 0,
+            last_times: None,
             last_sample: 0.0,
             last_time: None,
+            cores: Vec::new(),
+            history: VecDeque::with_capacity(HISTORY_SIZE),
         }
     }
 
-    fn read_cpu_stats(&self) -> Option<(u64, u64)> {
+    fn read_cpu_stats(&self) -> Option<(CpuTimes, Vec<(usize, CpuTimes)>)> {
         let content = fs::read_to_string("/proc/stat").ok()?;
-        let line = content.lines().next()?;
-        let parts: Vec<u64> = line
-            .split_whitespace()
-            .skip(1)
-            .filter_map(|s| s.parse().ok())
-            .collect();
+        parse_stat(&content)
+    }
 
-        if parts.len() >= 4 {
-            let idle = parts[3];
-            let total: u64 = parts.iter().sum();
-            Some((idle, total))
-        } else {
-            None
-        }
+    /// Returns the usage percentage of each online core.
+    pub fn core_percents(&self) -> Vec<f64> {
+        self.cores.iter().map(|c| c.percent).collect()
+    }
+
+    /// Returns the current frequency of each online core in MHz, or `None`
+    /// for cores without cpufreq support.
+    pub fn core_frequencies(&self) -> Vec<Option<u32>> {
+        self.cores
+            .iter()
+            .map(|c| {
+                let path = format!(
+                    "/sys/devices/system/cpu/cpu{}/cpufreq/scaling_cur_freq",
+                    c.id
+                );
+                let khz: u64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+                Some((khz / 1000) as u32)
+            })
+            .collect()
+    }
+
+    /// Returns the 1, 5 and 15 minute load averages.
+    pub fn load_average(&self) -> Option<[f64; 3]> {
+        let content = fs::read_to_string("/proc/loadavg").ok()?;
+        parse_loadavg(&content)
+    }
+
+    /// Returns the overall usage history (percent).
+    pub fn history(&self) -> &VecDeque<f64> {
+        &self.history
     }
 }
 
@@ -56,18 +150,30 @@ impl Sensor for CpuSensor {
     }
 
     fn sample(&mut self) -> f64 {
-        if let Some((idle, total)) = self.read_cpu_stats() {
-            if self.last_total > 0 {
-                let idle_delta = idle.saturating_sub(self.last_idle);
-                let total_delta = total.saturating_sub(self.last_total);
-
-                if total_delta > 0 {
-                    self.last_sample = 100.0 * (1.0 - (idle_delta as f64 / total_delta as f64));
+        if let Some((times, cores)) = self.read_cpu_stats() {
+            if let Some(usage) = self.last_times.and_then(|last| times.usage_since(&last)) {
+                self.last_sample = usage;
+                if self.history.len() >= HISTORY_SIZE {
+                    self.history.pop_front();
                 }
+                self.history.push_back(usage);
             }
 
-            self.last_idle = idle;
-            self.last_total = total;
+            // Cores can go offline and come back, so match them by number
+            self.cores = cores
+                .into_iter()
+                .map(|(id, times)| {
+                    let percent = self
+                        .cores
+                        .iter()
+                        .find(|c| c.id == id)
+                        .map(|c| times.usage_since(&c.times).unwrap_or(c.percent))
+                        .unwrap_or(0.0);
+                    CoreState { id, times, percent }
+                })
+                .collect();
+
+            self.last_times = Some(times);
             self.last_time = Some(Instant::now());
         }
 
@@ -86,3 +192,46 @@ impl Sensor for CpuSensor {
         "%"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "\
+cpu  400 0 100 500 0 0 0 0 0 0
+cpu0 300 0 50 150 0 0 0 0 0 0
+cpu2 100 0 50 350 0 0 0 0 0 0
+intr 12345 0 0
+ctxt 67890
+";
+
+    #[test]
+    fn test_parse_stat() {
+        let (aggregate, cores) = parse_stat(STAT).unwrap();
+        assert_eq!(
+            aggregate,
+            CpuTimes {
+                idle: 500,
+                total: 1000
+            }
+        );
+        let ids: Vec<_> = cores.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [0, 2]);
+
+        let later = CpuTimes {
+            idle: 150 + 25,
+            total: 500 + 100,
+        };
+        assert_eq!(later.usage_since(&cores[0].1), Some(75.0));
+        assert_eq!(cores[0].1.usage_since(&cores[0].1), None);
+    }
+
+    #[test]
+    fn test_parse_loadavg() {
+        assert_eq!(
+            parse_loadavg("0.52 0.61 0.70 2/1234 5678\n"),
+            Some([0.52, 0.61, 0.70])
+        );
+        assert_eq!(parse_loadavg("0.52"), None);
+    }
+}
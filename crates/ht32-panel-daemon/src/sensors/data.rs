@@ -1,6 +1,10 @@
 //! System data aggregation for faces.
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::Serialize;
+
+use crate::custom_values::CustomValue;
 
 /// Number of history samples to keep for graphs.
 pub const HISTORY_SIZE: usize = 60;
@@ -66,8 +70,49 @@ impl std::str::FromStr for IpDisplayPreference {
     }
 }
 
+/// Severity of a metric relative to its configured thresholds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertLevel {
+    /// Below every threshold (or no thresholds configured).
+    #[default]
+    Normal,
+    /// Past the warning threshold.
+    Warning,
+    /// Past the critical threshold.
+    Critical,
+}
+
+impl std::fmt::Display for AlertLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertLevel::Normal => write!(f, "normal"),
+            AlertLevel::Warning => write!(f, "warning"),
+            AlertLevel::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Alert level of each metric that supports thresholds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MetricAlerts {
+    /// CPU usage alert level
+    pub cpu_percent: AlertLevel,
+    /// CPU temperature alert level
+    pub cpu_temp: AlertLevel,
+    /// RAM usage alert level
+    pub ram_percent: AlertLevel,
+}
+
+impl MetricAlerts {
+    /// Returns the highest alert level across all metrics.
+    pub fn max(&self) -> AlertLevel {
+        self.cpu_percent.max(self.cpu_temp).max(self.ram_percent)
+    }
+}
+
 /// Aggregated system data from all sensors.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SystemData {
     /// Hostname of the system
     pub hostname: String,
@@ -89,8 +134,18 @@ pub struct SystemData {
     pub uptime: String,
     /// CPU usage percentage (0-100)
     pub cpu_percent: f64,
-    /// CPU temperature in Celsius (None if unavailable)
+    /// Usage percentage of each online core (0-100)
+    pub cpu_cores: Vec<f64>,
+    /// Current frequency of each online core in MHz (None without cpufreq)
+    pub cpu_freq_mhz: Vec<Option<u32>>,
+    /// CPU usage history (percent, newest last)
+    pub cpu_history: VecDeque<f64>,
+    /// 1, 5 and 15 minute load averages (None if unavailable)
+    pub load_avg: Option<[f64; 3]>,
+    /// CPU temperature in Celsius, from the selected sensor (None if unavailable)
     pub cpu_temp: Option<f64>,
+    /// Every hwmon temperature in Celsius, keyed by `<device>/<label>`
+    pub temperatures: BTreeMap<String, f64>,
     /// RAM usage percentage (0-100)
     pub ram_percent: f64,
     /// Disk read rate in bytes/second
@@ -117,6 +172,10 @@ pub struct SystemData {
     pub net_tx_history: VecDeque<f64>,
     /// IP address to display (based on preference)
     pub display_ip: Option<String>,
+    /// Custom values set by other programs, keyed by name
+    pub custom: BTreeMap<String, CustomValue>,
+    /// Threshold alert levels for CPU, temperature and RAM
+    pub alerts: MetricAlerts,
 }
 
 impl SystemData {
@@ -195,6 +254,24 @@ impl SystemData {
         }
     }
 
+    /// Returns the average frequency of the cores that report one, in MHz.
+    pub fn cpu_freq_avg_mhz(&self) -> Option<u32> {
+        let known: Vec<u32> = self.cpu_freq_mhz.iter().flatten().copied().collect();
+        if known.is_empty() {
+            return None;
+        }
+        Some((known.iter().map(|&f| f as u64).sum::<u64>() / known.len() as u64) as u32)
+    }
+
+    /// Formats a frequency in MHz compactly (e.g., "2.4G", "800M")
+    pub fn format_freq_compact(mhz: u32) -> String {
+        if mhz >= 1000 {
+            format!("{:.1}G", mhz as f64 / 1000.0)
+        } else {
+            format!("{}M", mhz)
+        }
+    }
+
     /// Formats a byte rate as a human-readable string (e.g., "1.2 MB/s")
     pub fn format_rate(bytes_per_sec: f64) -> String {
         if bytes_per_sec >= 1_000_000_000.0 {
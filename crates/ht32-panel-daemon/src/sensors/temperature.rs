@@ -1,17 +1,99 @@
-//! CPU temperature sensor.
+//! Temperature sensors.
+//!
+//! Reads every hwmon temperature input (CPU, NVMe, GPU, chipset, ...) and
+//! keeps a CPU temperature picked automatically for faces that show one.
 
 use super::Sensor;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tracing::debug;
 
-/// CPU temperature sensor.
+/// Directory with one entry per hardware monitoring device.
+const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// Lists the temperature inputs below a hwmon root as `(id, path)` pairs.
+///
+/// Ids are `<device name>/<label>`, e.g. `nvme/Composite` or
+/// `coretemp/Package id 0`. Inputs without a label use `temp<N>`, and
+/// duplicate ids (such as two NVMe drives) get the hwmon directory appended.
+fn enumerate_inputs(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut inputs: Vec<(String, PathBuf)> = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else {
+        return inputs;
+    };
+
+    // hwmon10 sorts after hwmon9
+    let mut devices: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let index = e
+                .file_name()
+                .to_str()?
+                .strip_prefix("hwmon")?
+                .parse()
+                .ok()?;
+            Some((index, e.path()))
+        })
+        .collect();
+    devices.sort();
+
+    for (index, device) in devices {
+        let name = fs::read_to_string(device.join("name"))
+            .map(|n| n.trim().to_string())
+            .unwrap_or_else(|_| format!("hwmon{}", index));
+
+        let Ok(files) = fs::read_dir(&device) else {
+            continue;
+        };
+        let mut temps: Vec<u32> = files
+            .flatten()
+            .filter_map(|f| {
+                f.file_name()
+                    .to_str()?
+                    .strip_prefix("temp")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        temps.sort();
+
+        for n in temps {
+            let label = fs::read_to_string(device.join(format!("temp{}_label", n)))
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|_| format!("temp{}", n));
+            let mut id = format!("{}/{}", name, label);
+            if inputs.iter().any(|(existing, _)| *existing == id) {
+                id = format!("{} (hwmon{})", id, index);
+            }
+            inputs.push((id, device.join(format!("temp{}_input", n))));
+        }
+    }
+
+    inputs
+}
+
+/// Reads a temperature file in millidegrees Celsius.
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    let content = fs::read_to_string(path).ok()?;
+    let millidegrees: f64 = content.trim().parse().ok()?;
+    Some(millidegrees / 1000.0)
+}
+
+/// Temperature sensor.
 pub struct TemperatureSensor {
     name: String,
-    /// Path to the temperature file (millidegrees Celsius)
+    /// Path to the automatically detected CPU temperature file
     temp_path: Option<PathBuf>,
-    /// Last sampled temperature in Celsius
+    /// Last sampled CPU temperature in Celsius
     last_temp: Option<f64>,
+    /// Last sampled temperature of every hwmon input, by id
+    readings: BTreeMap<String, f64>,
+    /// hwmon inputs as `(id, path)` pairs
+    inputs: Vec<(String, PathBuf)>,
+    last_scan: Option<Instant>,
 }
 
 impl TemperatureSensor {
@@ -28,15 +110,28 @@ impl TemperatureSensor {
             name: "cpu_temperature".to_string(),
             temp_path,
             last_temp: None,
+            readings: BTreeMap::new(),
+            inputs: Vec::new(),
+            last_scan: None,
         }
     }
 
+    /// Lists the ids of all temperature inputs, sorted.
+    pub fn list_sensors() -> Vec<String> {
+        let mut ids: Vec<String> = enumerate_inputs(Path::new(HWMON_ROOT))
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
     /// Detects the best temperature sensor path.
     /// Prefers CPU-specific sensors over generic thermal zones.
     fn detect_temp_path() -> Option<PathBuf> {
         // Try hwmon first - look for CPU-related sensors
         // Common labels: "coretemp", "k10temp", "cpu_thermal"
-        if let Ok(entries) = fs::read_dir("/sys/class/hwmon") {
+        if let Ok(entries) = fs::read_dir(HWMON_ROOT) {
             for entry in entries.flatten() {
                 let hwmon_path = entry.path();
 
@@ -92,18 +187,34 @@ impl TemperatureSensor {
         None
     }
 
-    /// Returns the last sampled temperature in Celsius.
+    /// Returns the last sampled CPU temperature in Celsius.
     pub fn temperature(&self) -> Option<f64> {
         self.last_temp
     }
 
-    /// Reads the current temperature from the sensor.
+    /// Returns the last sampled temperature of every input, by id.
+    pub fn readings(&self) -> &BTreeMap<String, f64> {
+        &self.readings
+    }
+
+    /// Rescans the hwmon inputs if the list is stale (older than 30
+    /// seconds), so hot-plugged drives show up without walking the tree
+    /// every frame.
+    fn refresh_inputs(&mut self) {
+        let should_refresh = self
+            .last_scan
+            .map(|t| t.elapsed().as_secs() > 30)
+            .unwrap_or(true);
+
+        if should_refresh {
+            self.inputs = enumerate_inputs(Path::new(HWMON_ROOT));
+            self.last_scan = Some(Instant::now());
+        }
+    }
+
+    /// Reads the current CPU temperature from the sensor.
     fn read_temp(&self) -> Option<f64> {
-        let path = self.temp_path.as_ref()?;
-        let content = fs::read_to_string(path).ok()?;
-        let millidegrees: f64 = content.trim().parse().ok()?;
-        // Temperature is in millidegrees Celsius
-        Some(millidegrees / 1000.0)
+        read_millidegrees(self.temp_path.as_ref()?)
     }
 }
 
@@ -120,6 +231,13 @@ impl Sensor for TemperatureSensor {
 
     fn sample(&mut self) -> f64 {
         self.last_temp = self.read_temp();
+        self.refresh_inputs();
+        // Inputs of removed devices fail to read until the next scan
+        self.readings = self
+            .inputs
+            .iter()
+            .filter_map(|(id, path)| Some((id.clone(), read_millidegrees(path)?)))
+            .collect();
         self.last_temp.unwrap_or(0.0)
     }
 
@@ -135,3 +253,50 @@ impl Sensor for TemperatureSensor {
         "°C"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hwmon(root: &Path, index: u32, name: &str, inputs: &[(u32, Option<&str>, &str)]) {
+        let dir = root.join(format!("hwmon{}", index));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("name"), format!("{}\n", name)).unwrap();
+        for (n, label, value) in inputs {
+            fs::write(dir.join(format!("temp{}_input", n)), value).unwrap();
+            if let Some(label) = label {
+                fs::write(dir.join(format!("temp{}_label", n)), label).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_enumerate_inputs() {
+        let root = std::env::temp_dir().join(format!("ht32-panel-hwmon-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        hwmon(&root, 10, "nvme", &[(1, Some("Composite\n"), "38850\n")]);
+        hwmon(&root, 2, "nvme", &[(1, Some("Composite\n"), "41000\n")]);
+        hwmon(
+            &root,
+            3,
+            "coretemp",
+            &[(2, Some("Core 0"), "52000"), (1, None, "55000")],
+        );
+
+        let inputs = enumerate_inputs(&root);
+        let ids: Vec<_> = inputs.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "nvme/Composite",
+                "coretemp/temp1",
+                "coretemp/Core 0",
+                "nvme/Composite (hwmon10)"
+            ]
+        );
+        assert_eq!(read_millidegrees(&inputs[0].1), Some(41.0));
+        assert_eq!(read_millidegrees(&inputs[3].1), Some(38.85));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
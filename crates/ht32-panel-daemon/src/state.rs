@@ -7,16 +7,23 @@ use ht32_panel_hw::{
     Orientation,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Mutex, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::alerts::{Alert, AlertTracker, LedAlert};
+use crate::carousel::{self, Carousel, Playlist, PlaylistEntry};
+use crate::config::{self, AlertsConfig, Config, LcdTarget, PanelConfig};
+use crate::custom_values::CustomValues;
 use crate::faces::{self, EnabledComplications, Face, Theme};
+use crate::metrics::PanelStats;
+use crate::notifications::{self, Notification, NotificationQueue, Severity};
 use crate::rendering::Canvas;
+use crate::schedule::{Schedule, ScheduleRestore, ScheduleRule, WeekTime};
 use crate::sensors::{
-    data::{IpDisplayPreference, SystemData},
+    data::{IpDisplayPreference, MetricAlerts, SystemData},
     CpuSensor, DiskSensor, MemorySensor, NetworkSensor, Sensor, SystemInfo, TemperatureSensor,
 };
 
@@ -47,9 +54,10 @@ pub struct DisplaySettings {
     #[serde(default = "default_led_value")]
     pub led_speed: u8,
 
-    /// Refresh interval in milliseconds (500-10000).
-    #[serde(default = "default_refresh_interval")]
-    pub refresh_interval: u32,
+    /// Refresh interval in milliseconds (500-10000), if set at runtime.
+    /// The configured `refresh_interval` applies otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<u32>,
 
     /// Network interface to monitor (legacy - migrated to complications).
     #[serde(default, skip_serializing)]
@@ -62,6 +70,22 @@ pub struct DisplaySettings {
     /// Enabled complications per face.
     #[serde(default)]
     pub complications: EnabledComplications,
+
+    /// Face carousel playlist.
+    #[serde(default, skip_serializing_if = "Playlist::is_empty")]
+    pub playlist: Playlist,
+
+    /// Settings to restore once the active schedule rule ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_restore: Option<ScheduleRestore>,
+
+    /// Display brightness in percent (0-100).
+    #[serde(default = "default_brightness")]
+    pub brightness: u8,
+
+    /// Whether the display is switched off.
+    #[serde(default)]
+    pub display_off: bool,
 }
 
 fn default_face() -> String {
@@ -72,6 +96,10 @@ fn default_theme() -> String {
     "default".to_string()
 }
 
+fn default_brightness() -> u8 {
+    100
+}
+
 fn default_led_theme() -> u8 {
     2 // Breathing
 }
@@ -80,10 +108,6 @@ fn default_led_value() -> u8 {
     3
 }
 
-fn default_refresh_interval() -> u32 {
-    2500 // 2.5s default
-}
-
 impl Default for DisplaySettings {
     fn default() -> Self {
         Self {
@@ -93,10 +117,14 @@ impl Default for DisplaySettings {
             led_theme: default_led_theme(),
             led_intensity: default_led_value(),
             led_speed: default_led_value(),
-            refresh_interval: default_refresh_interval(),
+            refresh_interval: None,
             network_interface: None,
             ip_display: None,
             complications: EnabledComplications::new(),
+            playlist: Playlist::default(),
+            schedule_restore: None,
+            brightness: default_brightness(),
+            display_off: false,
         }
     }
 }
@@ -134,10 +162,17 @@ impl Sensors {
         }
     }
 
-    fn sample(&mut self, ip_preference: IpDisplayPreference) -> SystemData {
+    fn sample(
+        &mut self,
+        ip_preference: IpDisplayPreference,
+        temp_sensor: Option<&str>,
+    ) -> SystemData {
         let cpu_percent = self.cpu.sample();
         let _ = self.temperature.sample();
-        let cpu_temp = self.temperature.temperature();
+        let cpu_temp = match temp_sensor {
+            Some(id) => self.temperature.readings().get(id).copied(),
+            None => self.temperature.temperature(),
+        };
         let ram_percent = self.memory.sample();
         let _ = self.network.sample();
         let _ = self.disk.sample();
@@ -162,7 +197,12 @@ impl Sensors {
             day_of_week,
             uptime: self.system.uptime(),
             cpu_percent,
+            cpu_cores: self.cpu.core_percents(),
+            cpu_freq_mhz: self.cpu.core_frequencies(),
+            cpu_history: self.cpu.history().clone(),
+            load_avg: self.cpu.load_average(),
             cpu_temp,
+            temperatures: self.temperature.readings().clone(),
             ram_percent,
             disk_read_rate: self.disk.read_rate(),
             disk_write_rate: self.disk.write_rate(),
@@ -176,6 +216,8 @@ impl Sensors {
             net_rx_history: self.network.rx_history().clone(),
             net_tx_history: self.network.tx_history().clone(),
             display_ip,
+            custom: BTreeMap::new(),
+            alerts: MetricAlerts::default(),
         }
     }
 }
@@ -185,8 +227,16 @@ struct DisplayState {
     orientation: Orientation,
     face: Box<dyn Face>,
     theme_name: String,
-    refresh_interval: u32,
+    /// Refresh interval set at runtime, overriding the configured one
+    refresh_interval: Option<u32>,
     complications: EnabledComplications,
+    carousel: Carousel,
+    /// Brightness in percent, applied when converting to the framebuffer
+    brightness: u8,
+    /// Push black frames instead of the face
+    display_off: bool,
+    /// Blanked while the session is idle, locked or suspending; not persisted
+    blanked: bool,
     needs_redraw: bool,
 }
 
@@ -195,32 +245,240 @@ struct LedState {
     theme: u8,
     intensity: u8,
     speed: u8,
+    /// Theme shown instead of the user's while a metric is critical
+    alert: Option<LedTheme>,
     needs_update: bool,
 }
 
+impl LedState {
+    /// Returns the theme, intensity and speed the LEDs should show.
+    fn effective(&self) -> Result<(LedTheme, u8, u8)> {
+        let theme = match self.alert {
+            Some(theme) => theme,
+            None => LedTheme::from_byte(self.theme)?,
+        };
+        Ok((theme, self.intensity, self.speed))
+    }
+}
+
+/// Schedule state.
+struct ScheduleState {
+    schedule: Schedule,
+    /// Index of the active rule
+    active: Option<usize>,
+    /// Settings from before the active rule took over
+    restore: Option<ScheduleRestore>,
+}
+
 /// Render pipeline state (canvas, framebuffer, PNG cache).
 struct RenderState {
     canvas: Canvas,
     framebuffer: Framebuffer,
+    /// Framebuffer last sent to the LCD, used for partial updates.
+    /// `None` forces a full redraw on the next frame.
+    last_sent: Option<Framebuffer>,
     cached_png: Option<Vec<u8>>,
 }
 
+/// A drawn frame and the sensor sample it shows.
+#[derive(Debug, Serialize)]
+pub struct Frame {
+    /// Frames drawn by the panel since the daemon started, counting from 1
+    pub frame: u64,
+    /// Sensor sample shown by the frame
+    pub data: SystemData,
+}
+
 /// Minimum interval between disk writes for display settings.
 const SAVE_DEBOUNCE_SECS: u64 = 5;
 
+/// Opens the LCD device. Replaceable so tests can supply a mock transport.
+pub type LcdOpener = Box<dyn Fn() -> ht32_panel_hw::Result<LcdDevice> + Send + Sync>;
+
+/// Returns the opener for the LCD configured for a panel.
+fn lcd_opener(panel: &PanelConfig) -> LcdOpener {
+    match panel.lcd_target() {
+        LcdTarget::Auto => Box::new(LcdDevice::open),
+        LcdTarget::Path(path) => Box::new(move || LcdDevice::open_path(&path)),
+        LcdTarget::Serial(serial) => Box::new(move || LcdDevice::open_serial(&serial)),
+        LcdTarget::Virtual(path) => Box::new(move || LcdDevice::open_virtual(&path)),
+    }
+}
+
 /// Shared application state.
 pub struct AppState {
     /// Configuration (immutable after init)
     config: Config,
 
-    /// State directory for persisting runtime state
-    state_dir: PathBuf,
+    /// Panels driven by this daemon; the first one is the default
+    panels: Vec<Arc<PanelState>>,
+
+    /// Custom values shared by all panels
+    custom_values: Arc<CustomValues>,
+}
+
+impl AppState {
+    /// Creates a new application state.
+    pub fn new(config: Config) -> Result<Self> {
+        Self::with_lcd_opener(config, lcd_opener)
+    }
+
+    /// Creates a new application state using a custom LCD opener per panel.
+    pub fn with_lcd_opener(
+        config: Config,
+        make_opener: impl Fn(&PanelConfig) -> LcdOpener,
+    ) -> Result<Self> {
+        // Setup state directory
+        let state_dir = PathBuf::from(&config.state_dir);
+        if let Err(e) = std::fs::create_dir_all(&state_dir) {
+            warn!("Failed to create state directory {:?}: {}", state_dir, e);
+        }
+        info!("State directory: {:?}", state_dir);
+
+        // User themes and custom faces must be known before saved names are resolved
+        let themes_dir = config.themes_dir();
+        let loaded = faces::load_user_themes(&themes_dir);
+        if loaded > 0 {
+            info!("Loaded {} theme(s) from {:?}", loaded, themes_dir);
+        }
+        for theme in &config.themes {
+            if let Err(e) = faces::register_theme(theme) {
+                warn!("Skipping theme '{}': {:#}", theme.id, e);
+            }
+        }
+
+        let faces_dir = config.faces_dir();
+        let loaded = faces::load_custom_faces(&faces_dir);
+        if loaded > 0 {
+            info!("Loaded {} custom face(s) from {:?}", loaded, faces_dir);
+        }
+
+        let custom_values = Arc::new(CustomValues::default());
+        let named = config.devices.has_named_panels();
+        let panels = config
+            .devices
+            .panels()
+            .iter()
+            .map(|panel| {
+                // A single unnamed panel keeps the original settings file
+                let settings_file = if named {
+                    state_dir.join(format!("display-{}.toml", panel.id))
+                } else {
+                    state_dir.join("display.toml")
+                };
+                PanelState::new(
+                    panel,
+                    settings_file,
+                    make_opener(panel),
+                    &config.alerts,
+                    &config.schedule,
+                    config.refresh_interval as u32,
+                    custom_values.clone(),
+                )
+                .map(Arc::new)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            config,
+            panels,
+            custom_values,
+        })
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns true if the web UI is enabled.
+    pub fn is_web_enabled(&self) -> bool {
+        self.config.web.enable
+    }
 
-    /// LCD device (mutable Option for reconnection)
+    /// Returns all panels, default first.
+    pub fn panels(&self) -> &[Arc<PanelState>] {
+        &self.panels
+    }
+
+    /// Returns the default panel.
+    pub fn default_panel(&self) -> &Arc<PanelState> {
+        &self.panels[0]
+    }
+
+    /// Looks up a panel by id.
+    pub fn panel(&self, id: &str) -> Option<&Arc<PanelState>> {
+        self.panels.iter().find(|p| p.id() == id)
+    }
+
+    /// Returns the ids of all panels, default first.
+    pub fn panel_ids(&self) -> Vec<String> {
+        self.panels.iter().map(|p| p.id().to_string()).collect()
+    }
+
+    /// Returns a list of available themes.
+    pub fn available_themes(&self) -> Vec<faces::ThemeInfo> {
+        faces::available_themes()
+    }
+
+    /// Lists all available network interfaces.
+    pub fn list_network_interfaces(&self) -> Vec<String> {
+        NetworkSensor::list_interfaces()
+    }
+
+    /// Lists all available temperature sensors.
+    pub fn list_temperature_sensors(&self) -> Vec<String> {
+        TemperatureSensor::list_sensors()
+    }
+
+    /// Lists the choices of a complication's choice option. Network
+    /// interfaces and temperature sensors are listed from the system, after
+    /// an "auto" choice; other options offer their fixed `choices`.
+    pub fn complication_choices(
+        &self,
+        complication: &str,
+        option: &str,
+        choices: &[faces::ComplicationChoice],
+    ) -> Vec<faces::ComplicationChoice> {
+        let detected = if complication == faces::complication_names::NETWORK
+            && option == faces::complication_options::INTERFACE
+        {
+            self.list_network_interfaces()
+        } else if complication == faces::complication_names::CPU_TEMP
+            && option == faces::complication_options::SENSOR
+        {
+            self.list_temperature_sensors()
+        } else {
+            return choices.to_vec();
+        };
+        std::iter::once(faces::ComplicationChoice::new("auto", "Auto-detect"))
+            .chain(
+                detected
+                    .iter()
+                    .map(|name| faces::ComplicationChoice::new(name, name)),
+            )
+            .collect()
+    }
+
+    /// Returns the custom values shared by all panels.
+    pub fn custom_values(&self) -> &Arc<CustomValues> {
+        &self.custom_values
+    }
+}
+
+/// State of a single LCD/LED panel.
+pub struct PanelState {
+    /// Panel id
+    id: String,
+
+    /// File the display settings are persisted to
+    settings_file: PathBuf,
+
+    /// LCD device (`None` while detached)
     lcd: Mutex<Option<LcdDevice>>,
 
-    /// Timestamp of last LCD reconnection attempt
-    last_lcd_reconnect: Mutex<std::time::Instant>,
+    /// Opens the LCD device on startup and when it is plugged in
+    lcd_opener: LcdOpener,
 
     /// LED device path
     led_device_path: String,
@@ -228,6 +486,9 @@ pub struct AppState {
     /// Display state
     display: RwLock<DisplayState>,
 
+    /// Configured refresh interval, used until one is set at runtime
+    default_refresh_interval: u32,
+
     /// LED state
     led: RwLock<LedState>,
 
@@ -237,6 +498,27 @@ pub struct AppState {
     /// System sensors
     sensors: Mutex<Sensors>,
 
+    /// Metric alert levels
+    alerts: Mutex<AlertTracker>,
+
+    /// Time-of-day schedule
+    schedule: Mutex<ScheduleState>,
+
+    /// Custom values, shared with the other panels
+    custom_values: Arc<CustomValues>,
+
+    /// Last drawn frame; subscribers always see the newest one
+    frames: tokio::sync::watch::Sender<Option<Arc<Frame>>>,
+
+    /// Render and heartbeat counters
+    stats: Mutex<PanelStats>,
+
+    /// On-screen notifications
+    notifications: Mutex<NotificationQueue>,
+
+    /// Wakes the render loop early
+    wake: tokio::sync::Notify,
+
     /// Save debouncing: set when a save is needed
     save_pending: AtomicBool,
 
@@ -244,23 +526,33 @@ pub struct AppState {
     last_save: Mutex<std::time::Instant>,
 }
 
-impl AppState {
-    /// Creates a new application state.
-    pub fn new(config: Config) -> Result<Self> {
-        // Setup state directory
-        let state_dir = PathBuf::from(&config.state_dir);
-        if let Err(e) = std::fs::create_dir_all(&state_dir) {
-            warn!("Failed to create state directory {:?}: {}", state_dir, e);
-        }
-
-        // Load display settings from state
-        let settings = Self::load_display_settings(&state_dir);
+impl PanelState {
+    /// Creates the state for a panel, restoring saved settings if present.
+    fn new(
+        panel: &PanelConfig,
+        settings_file: PathBuf,
+        lcd_opener: LcdOpener,
+        alerts: &AlertsConfig,
+        schedule: &[ScheduleRule],
+        default_refresh_interval: u32,
+        custom_values: Arc<CustomValues>,
+    ) -> Result<Self> {
+        // Load display settings from state, seeded from the panel config
+        let mut settings = Self::load_display_settings(&settings_file).unwrap_or_else(|| {
+            let defaults = DisplaySettings::default();
+            DisplaySettings {
+                face: panel.face.clone().unwrap_or(defaults.face),
+                theme: panel.theme.clone().unwrap_or(defaults.theme),
+                orientation: panel.orientation.clone().unwrap_or(defaults.orientation),
+                ..DisplaySettings::default()
+            }
+        });
 
         // Parse orientation from settings
         let orientation: Orientation = settings.orientation.parse().unwrap_or_default();
 
         // Try to open LCD device
-        let lcd = match LcdDevice::open() {
+        let lcd = match lcd_opener() {
             Ok(device) => {
                 if let Err(e) = device.heartbeat() {
                     warn!("Failed to send initial heartbeat: {}", e);
@@ -268,11 +560,14 @@ impl AppState {
                 if let Err(e) = device.set_orientation(Orientation::Landscape) {
                     warn!("Failed to set initial orientation: {}", e);
                 }
-                info!("LCD device opened successfully");
+                info!("Panel '{}': LCD device opened successfully", panel.id);
                 Some(device)
             }
             Err(e) => {
-                warn!("LCD device not found: {}. Running in headless mode.", e);
+                warn!(
+                    "Panel '{}': LCD device not found: {}. Running in headless mode.",
+                    panel.id, e
+                );
                 None
             }
         };
@@ -334,60 +629,118 @@ impl AppState {
             _ => Sensors::new_auto(),
         };
 
-        let theme = Theme::from_preset(&settings.theme);
+        let theme = Theme::named(&settings.theme).unwrap_or_else(|| {
+            warn!(
+                "Panel '{}': unknown theme '{}', falling back to 'default'",
+                panel.id, settings.theme
+            );
+            settings.theme = "default".to_string();
+            Theme::default()
+        });
         canvas.set_background(theme.background);
 
-        info!("State directory: {:?}", state_dir);
-        info!("Display orientation: {}", orientation);
-        info!("Theme: {}", settings.theme);
+        let playlist = std::mem::take(&mut settings.playlist);
+        let entries = playlist
+            .entries
+            .into_iter()
+            .filter(|entry| match Self::validate_playlist_entry(entry) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Panel '{}': dropping playlist entry: {}", panel.id, e);
+                    false
+                }
+            })
+            .collect();
+
+        let rules = schedule
+            .iter()
+            .filter(|rule| rule.applies_to(&panel.id))
+            .filter(|rule| match Self::validate_schedule_rule(rule) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Panel '{}': ignoring schedule rule: {}", panel.id, e);
+                    false
+                }
+            })
+            .cloned()
+            .collect();
+        let schedule = Schedule::new(rules);
+        let schedule_restore = settings.schedule_restore.take();
+        let active_rule = schedule_restore
+            .as_ref()
+            .and_then(|restore| schedule.position(&restore.rule));
+
+        info!("Panel '{}': orientation {}", panel.id, orientation);
+        info!("Panel '{}': theme {}", panel.id, settings.theme);
 
         let now = std::time::Instant::now();
 
-        let app_state = Self {
-            led_device_path: config.devices.led.clone(),
-            config,
-            state_dir,
+        let panel_state = Self {
+            id: panel.id.clone(),
+            settings_file,
+            led_device_path: panel.led.clone(),
             lcd: Mutex::new(lcd),
-            last_lcd_reconnect: Mutex::new(now),
+            lcd_opener,
             display: RwLock::new(DisplayState {
                 orientation,
                 face,
                 theme_name: settings.theme,
-                refresh_interval: settings.refresh_interval,
+                refresh_interval: settings
+                    .refresh_interval
+                    .map(|ms| ms.clamp(config::MIN_REFRESH_INTERVAL, config::MAX_REFRESH_INTERVAL)),
                 complications,
+                carousel: Carousel::new(entries),
+                brightness: settings.brightness.min(100),
+                display_off: settings.display_off,
+                blanked: false,
                 needs_redraw: true,
             }),
+            default_refresh_interval,
             led: RwLock::new(LedState {
                 theme: settings.led_theme,
                 intensity: settings.led_intensity,
                 speed: settings.led_speed,
+                alert: None,
                 needs_update: true,
             }),
             render: RwLock::new(RenderState {
                 canvas,
                 framebuffer,
+                last_sent: None,
                 cached_png: None,
             }),
             sensors: Mutex::new(sensors),
+            alerts: Mutex::new(AlertTracker::new(alerts.clone())),
+            notifications: Mutex::new(NotificationQueue::default()),
+            schedule: Mutex::new(ScheduleState {
+                schedule,
+                active: active_rule,
+                restore: schedule_restore,
+            }),
+            custom_values,
+            frames: tokio::sync::watch::Sender::new(None),
+            stats: Mutex::new(PanelStats::default()),
+            wake: tokio::sync::Notify::new(),
             save_pending: AtomicBool::new(false),
             last_save: Mutex::new(now),
         };
 
+        if playlist.running {
+            if let Err(e) = panel_state.start_carousel() {
+                warn!("Panel '{}': carousel not resumed: {}", panel.id, e);
+            }
+        }
+
         // Save initial state so the file always exists
-        app_state.flush_display_settings();
+        panel_state.flush_display_settings();
 
-        Ok(app_state)
+        Ok(panel_state)
     }
 
-    /// Loads display settings from state directory.
-    fn load_display_settings(state_dir: &Path) -> DisplaySettings {
-        let settings_file = state_dir.join("display.toml");
-        if let Ok(content) = std::fs::read_to_string(&settings_file) {
-            if let Ok(settings) = toml::from_str(&content) {
-                return settings;
-            }
-        }
-        DisplaySettings::default()
+    /// Loads display settings from the state directory.
+    fn load_display_settings(settings_file: &Path) -> Option<DisplaySettings> {
+        let content = std::fs::read_to_string(settings_file).ok()?;
+        toml::from_str(&content).ok()
     }
 
     /// Marks that display settings need to be saved (debounced).
@@ -400,6 +753,7 @@ impl AppState {
         self.save_pending.store(false, Ordering::Relaxed);
         *self.last_save.lock().unwrap() = std::time::Instant::now();
 
+        let schedule_restore = self.schedule.lock().unwrap().restore.clone();
         let display = self.display.read().unwrap();
         let led = self.led.read().unwrap();
 
@@ -414,16 +768,19 @@ impl AppState {
             network_interface: None,
             ip_display: None,
             complications: display.complications.clone(),
+            playlist: display.carousel.playlist(),
+            schedule_restore,
+            brightness: display.brightness,
+            display_off: display.display_off,
         };
 
         // Drop locks before disk I/O
         drop(display);
         drop(led);
 
-        let settings_file = self.state_dir.join("display.toml");
         match toml::to_string_pretty(&settings) {
             Ok(content) => {
-                if let Err(e) = std::fs::write(&settings_file, content) {
+                if let Err(e) = std::fs::write(&self.settings_file, content) {
                     warn!("Failed to save display settings: {}", e);
                 }
             }
@@ -444,9 +801,9 @@ impl AppState {
         }
     }
 
-    /// Returns the configuration.
-    pub fn config(&self) -> &Config {
-        &self.config
+    /// Returns the panel id.
+    pub fn id(&self) -> &str {
+        &self.id
     }
 
     /// Gets the current orientation.
@@ -459,40 +816,60 @@ impl AppState {
         self.lcd.lock().unwrap().is_some()
     }
 
-    /// Returns true if the web UI is enabled.
-    pub fn is_web_enabled(&self) -> bool {
-        self.config.web.enable
-    }
+    /// Opens and attaches the LCD device if it is disconnected.
+    ///
+    /// Blocks for the device's initialization cooldown, so call it off the
+    /// render path. Returns true if a device was attached.
+    pub fn attach_lcd(&self) -> bool {
+        if self.is_lcd_connected() {
+            return false;
+        }
+
+        // Open without holding the lock so rendering continues meanwhile
+        let device = match (self.lcd_opener)() {
+            Ok(device) => device,
+            Err(e) => {
+                debug!("Panel '{}': LCD not available: {}", self.id, e);
+                return false;
+            }
+        };
+        if let Err(e) = device.heartbeat() {
+            warn!("Attached LCD but heartbeat failed: {}", e);
+        }
+        if let Err(e) = device.set_orientation(Orientation::Landscape) {
+            warn!("Attached LCD but orientation set failed: {}", e);
+        }
 
-    /// Attempts to reconnect the LCD device if it's disconnected.
-    /// Returns true if connected (either already or newly).
-    fn try_lcd_reconnect(&self) -> bool {
         let mut lcd = self.lcd.lock().unwrap();
         if lcd.is_some() {
-            return true;
+            return false;
         }
+        *lcd = Some(device);
+        drop(lcd);
 
-        // Only attempt reconnection every 30 seconds
-        let mut last_attempt = self.last_lcd_reconnect.lock().unwrap();
-        if last_attempt.elapsed() < std::time::Duration::from_secs(30) {
-            return false;
+        self.force_redraw();
+        info!("Panel '{}': LCD device attached", self.id);
+        true
+    }
+
+    /// Drops the LCD device. Returns true if one was attached.
+    pub fn detach_lcd(&self) -> bool {
+        let detached = self.lcd.lock().unwrap().take().is_some();
+        if detached {
+            info!("Panel '{}': LCD device detached", self.id);
         }
-        *last_attempt = std::time::Instant::now();
-        drop(last_attempt);
+        detached
+    }
 
-        match LcdDevice::open() {
-            Ok(device) => {
-                if let Err(e) = device.heartbeat() {
-                    warn!("Reconnected LCD but heartbeat failed: {}", e);
-                }
-                if let Err(e) = device.set_orientation(Orientation::Landscape) {
-                    warn!("Reconnected LCD but orientation set failed: {}", e);
-                }
-                info!("LCD device reconnected successfully");
-                *lcd = Some(device);
-                true
+    /// Sends a heartbeat and detaches the LCD if it no longer responds.
+    /// Returns true if the LCD was detached.
+    pub fn probe_lcd(&self) -> bool {
+        match self.send_heartbeat() {
+            Ok(()) => false,
+            Err(e) => {
+                debug!("Panel '{}': LCD probe failed: {}", self.id, e);
+                self.detach_lcd()
             }
-            Err(_) => false,
         }
     }
 
@@ -522,13 +899,125 @@ impl AppState {
         }
 
         self.save_display_settings();
-        info!("Orientation set to: {}", orientation);
+        info!("Panel '{}': orientation set to {}", self.id, orientation);
         Ok(())
     }
 
     /// Gets the current refresh interval in milliseconds.
     pub fn refresh_interval_ms(&self) -> u32 {
-        self.display.read().unwrap().refresh_interval
+        self.display
+            .read()
+            .unwrap()
+            .refresh_interval
+            .unwrap_or(self.default_refresh_interval)
+    }
+
+    /// Sets the refresh interval in milliseconds (500-10000).
+    pub fn set_refresh_interval(&self, ms: u32) -> Result<()> {
+        if !config::is_valid_refresh_interval(ms as u64) {
+            anyhow::bail!(
+                "Refresh interval must be between {} and {} ms",
+                config::MIN_REFRESH_INTERVAL,
+                config::MAX_REFRESH_INTERVAL
+            );
+        }
+        self.display.write().unwrap().refresh_interval = Some(ms);
+        self.save_display_settings();
+        info!("Panel '{}': refresh interval set to {} ms", self.id, ms);
+        Ok(())
+    }
+
+    /// Gets the display brightness in percent.
+    pub fn brightness(&self) -> u8 {
+        self.display.read().unwrap().brightness
+    }
+
+    /// Sets the display brightness in percent (0-100).
+    pub fn set_brightness(&self, percent: u8) -> Result<()> {
+        if percent > 100 {
+            anyhow::bail!("Brightness must be between 0 and 100%");
+        }
+        self.display.write().unwrap().brightness = percent;
+        self.save_display_settings();
+        self.wake.notify_one();
+        info!("Panel '{}': brightness set to {}%", self.id, percent);
+        Ok(())
+    }
+
+    /// Returns true if the display is switched off.
+    pub fn is_display_off(&self) -> bool {
+        self.display.read().unwrap().display_off
+    }
+
+    /// Switches the display off or back on. While off, black frames are
+    /// pushed and the render loop slows down.
+    pub fn set_display_off(&self, off: bool) {
+        self.display.write().unwrap().display_off = off;
+        self.save_display_settings();
+        self.wake.notify_one();
+        info!(
+            "Panel '{}': display switched {}",
+            self.id,
+            if off { "off" } else { "on" }
+        );
+    }
+
+    /// Returns true if the panel is blanked for the session.
+    pub fn is_blanked(&self) -> bool {
+        self.display.read().unwrap().blanked
+    }
+
+    /// Blanks the display and turns the LEDs off while the session is idle,
+    /// locked or suspending. Unlike switching the display off, nothing is
+    /// persisted, so [`unblank`](Self::unblank) brings back the same settings.
+    ///
+    /// The LEDs are switched off even if the panel is already blanked, as
+    /// the controller may have reset during a suspend.
+    pub async fn blank(&self) {
+        let was_blanked = std::mem::replace(&mut self.display.write().unwrap().blanked, true);
+        let led = LedDevice::new(&self.led_device_path);
+        if let Err(e) = led.set_off().await {
+            debug!("Panel '{}': LED off failed: {}", self.id, e);
+        }
+        if !was_blanked {
+            self.wake.notify_one();
+            info!("Panel '{}': display blanked", self.id);
+        }
+    }
+
+    /// Shows the face again and restores the LED theme after
+    /// [`blank`](Self::blank).
+    pub fn unblank(&self) {
+        {
+            let mut display = self.display.write().unwrap();
+            if !display.blanked {
+                return;
+            }
+            display.blanked = false;
+            display.needs_redraw = true;
+        }
+        self.led.write().unwrap().needs_update = true;
+        self.wake.notify_one();
+        info!("Panel '{}': display unblanked", self.id);
+    }
+
+    /// Re-opens the LCD device, e.g. after resuming from suspend, which
+    /// sends the heartbeat and orientation again.
+    ///
+    /// Blocks like [`attach_lcd`](Self::attach_lcd). Returns true if the
+    /// connection state changed.
+    pub fn reopen_lcd(&self) -> bool {
+        let was_connected = self.detach_lcd();
+        self.attach_lcd() != was_connected
+    }
+
+    /// Sleeps until the next frame is due, or until the display is switched
+    /// on or its brightness changes.
+    pub async fn wait_for_frame(&self, interval: std::time::Duration) {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = self.wake.notified() => {}
+        }
     }
 
     /// Gets the current LED settings.
@@ -538,24 +1027,33 @@ impl AppState {
     }
 
     /// Sets the LED theme and parameters.
+    ///
+    /// While an alert overrides the LEDs, the settings are kept and shown
+    /// once it clears.
     pub async fn set_led(&self, theme: u8, intensity: u8, speed: u8) -> Result<()> {
-        {
+        LedTheme::from_byte(theme)?;
+        let overridden = {
             let mut led = self.led.write().unwrap();
             led.theme = theme;
             led.intensity = intensity;
             led.speed = speed;
-        }
+            led.alert.is_some()
+        };
         self.save_display_settings();
 
-        let led = LedDevice::new(&self.led_device_path);
-        let led_theme = LedTheme::from_byte(theme)?;
-        if let Err(e) = led.set_theme(led_theme, intensity, speed).await {
-            warn!(
-                "Failed to send LED command to {}: {}",
-                self.led_device_path, e
+        if self.is_blanked() {
+            // Sent once the panel is unblanked
+            self.led.write().unwrap().needs_update = true;
+            return Ok(());
+        }
+        if overridden {
+            info!(
+                "Panel '{}': LED settings saved; shown once the alert clears",
+                self.id
             );
-            return Err(e.into());
+            return Ok(());
         }
+        self.send_led().await?;
 
         info!(
             "LED set to theme {} (intensity: {}, speed: {})",
@@ -564,10 +1062,64 @@ impl AppState {
         Ok(())
     }
 
-    /// Turns off the LEDs.
-    pub async fn led_off(&self) -> Result<()> {
+    /// Sends the LED settings, or the alert theme while one is active.
+    async fn send_led(&self) -> Result<()> {
+        let (theme, intensity, speed) = self.led.read().unwrap().effective()?;
         let led = LedDevice::new(&self.led_device_path);
-        led.set_off().await?;
+        if let Err(e) = led.set_theme(theme, intensity, speed).await {
+            warn!(
+                "Failed to send LED command to {}: {}",
+                self.led_device_path, e
+            );
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Applies alert overrides and pending LED updates.
+    async fn update_led(&self) {
+        // Track the override even while blanked, so unblanking shows the
+        // right theme
+        match self.alerts.lock().unwrap().led_change() {
+            Some(LedAlert::Show(theme)) => {
+                let mut led = self.led.write().unwrap();
+                led.alert = Some(theme);
+                led.needs_update = true;
+            }
+            Some(LedAlert::Restore) => {
+                let mut led = self.led.write().unwrap();
+                led.alert = None;
+                led.needs_update = true;
+            }
+            None => {}
+        }
+
+        // Leave the LEDs off while blanked; updates are sent on unblank
+        if self.is_blanked() || !self.led.read().unwrap().needs_update {
+            return;
+        }
+        if let Err(e) = self.send_led().await {
+            warn!("LED update failed: {}", e);
+        }
+        self.led.write().unwrap().needs_update = false;
+    }
+
+    /// Re-sends the LED settings on the next frame if the LED port exists,
+    /// e.g. after the controller was plugged back in.
+    pub fn restore_led(&self) {
+        if Path::new(&self.led_device_path).exists() {
+            self.led.write().unwrap().needs_update = true;
+        }
+    }
+
+    /// Turns off the LEDs, or once the alert clears while one overrides
+    /// them.
+    pub async fn led_off(&self) -> Result<()> {
+        let overridden = self.led.read().unwrap().alert.is_some();
+        if !overridden {
+            let led = LedDevice::new(&self.led_device_path);
+            led.set_off().await?;
+        }
         {
             let mut state = self.led.write().unwrap();
             state.theme = 4; // Off
@@ -581,17 +1133,54 @@ impl AppState {
     pub fn send_heartbeat(&self) -> Result<()> {
         let lcd = self.lcd.lock().unwrap();
         if let Some(ref device) = *lcd {
-            device.heartbeat()?;
+            device
+                .heartbeat()
+                .inspect_err(|_| self.stats.lock().unwrap().heartbeat_errors += 1)?;
             debug!("Heartbeat sent");
         }
         Ok(())
     }
 
+    /// Returns the render and heartbeat counters.
+    pub fn stats(&self) -> PanelStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Returns the sensor sample of the last frame, if any was rendered.
+    pub fn latest_data(&self) -> Option<SystemData> {
+        self.frames.borrow().as_ref().map(|f| f.data.clone())
+    }
+
+    /// Subscribes to drawn frames. A slow subscriber skips to the newest
+    /// frame rather than falling behind.
+    pub fn subscribe_frames(&self) -> tokio::sync::watch::Receiver<Option<Arc<Frame>>> {
+        self.frames.subscribe()
+    }
+
     /// Samples all sensors and returns the current system data.
     fn sample_sensors(&self) -> SystemData {
         let mut sensors = self.sensors.lock().unwrap();
         let ip_preference = self.get_ip_display_from_complications();
-        sensors.sample(ip_preference)
+        let temp_sensor = self.get_temp_sensor_from_complications();
+        let mut data = sensors.sample(ip_preference, temp_sensor.as_deref());
+        data.custom = self.custom_values.snapshot();
+        data
+    }
+
+    /// Gets the temperature sensor shown by the CPU temperature complication,
+    /// or `None` for auto-detection.
+    fn get_temp_sensor_from_complications(&self) -> Option<String> {
+        let display = self.display.read().unwrap();
+        let face_name = display.face.name().to_string();
+        display
+            .complications
+            .get_option(
+                &face_name,
+                faces::complication_names::CPU_TEMP,
+                faces::complication_options::SENSOR,
+            )
+            .filter(|s| !s.is_empty() && *s != "auto")
+            .cloned()
     }
 
     /// Gets the IP display preference from complications.
@@ -609,14 +1198,26 @@ impl AppState {
             .unwrap_or(IpDisplayPreference::Ipv6Gua)
     }
 
-    /// Renders a frame and updates the display.
+    /// Renders a frame and updates the display, counting it in the stats.
     pub async fn render_frame(&self) -> Result<()> {
-        let system_data = self.sample_sensors();
+        let start = std::time::Instant::now();
+        let result = self.draw_frame().await;
+        self.stats
+            .lock()
+            .unwrap()
+            .record_frame(start.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Renders a frame and updates the display.
+    async fn draw_frame(&self) -> Result<()> {
+        let mut system_data = self.sample_sensors();
+        self.alerts.lock().unwrap().update(&mut system_data);
 
         // Render face to canvas
         {
             let display = self.display.read().unwrap();
-            let theme = Theme::from_preset(&display.theme_name);
+            let theme = Theme::named(&display.theme_name).unwrap_or_default();
             let mut render = self.render.write().unwrap();
 
             render.canvas.clear();
@@ -626,41 +1227,59 @@ impl AppState {
                 &theme,
                 &display.complications,
             );
+            self.alerts
+                .lock()
+                .unwrap()
+                .draw_flash(&mut render.canvas, theme.critical);
+            let mut queue = self.notifications.lock().unwrap();
+            let now = std::time::Instant::now();
+            if display.display_off || display.blanked {
+                queue.hide(now);
+            } else if let Some(notification) = queue.current(now) {
+                notifications::draw(&mut render.canvas, &theme, notification);
+            }
+            drop(queue);
 
             // Invalidate PNG cache
             render.cached_png = None;
         }
+        // Before the LCD send, which may fail
+        self.update_led().await;
+
+        self.frames.send_modify(|last| {
+            let frame = last.as_ref().map_or(1, |f| f.frame + 1);
+            *last = Some(Arc::new(Frame {
+                frame,
+                data: system_data,
+            }));
+        });
 
         // Transform canvas to framebuffer and send to LCD
         {
-            let orientation = self.display.read().unwrap().orientation;
+            let (orientation, full_redraw, level) = {
+                let mut display = self.display.write().unwrap();
+                let level = if display.display_off || display.blanked {
+                    0
+                } else {
+                    display.brightness
+                };
+                (
+                    display.orientation,
+                    std::mem::take(&mut display.needs_redraw),
+                    level,
+                )
+            };
             let mut render = self.render.write().unwrap();
-            Self::render_to_framebuffer(&mut render, orientation)?;
+            Self::render_to_framebuffer(&mut render, orientation, level)?;
 
-            // Try to reconnect LCD if disconnected, then send
+            // Send to the LCD if attached; the hot-plug watcher attaches it
             let lcd = self.lcd.lock().unwrap();
             if let Some(ref device) = *lcd {
-                device.redraw(&render.framebuffer)?;
+                Self::send_framebuffer(device, &mut render, full_redraw)?;
             } else {
-                drop(lcd);
-                // Try reconnection (will rate-limit internally)
-                if self.try_lcd_reconnect() {
-                    let lcd = self.lcd.lock().unwrap();
-                    if let Some(ref device) = *lcd {
-                        device.redraw(&render.framebuffer)?;
-                    }
-                }
-            }
-        }
-
-        // Handle LED updates
-        let needs_led = self.led.read().unwrap().needs_update;
-        if needs_led {
-            let (theme, intensity, speed) = self.led_settings();
-            if let Err(e) = self.set_led(theme, intensity, speed).await {
-                warn!("LED update failed: {}", e);
+                // Nothing on a freshly attached panel matches what we sent before
+                render.last_sent = None;
             }
-            self.led.write().unwrap().needs_update = false;
         }
 
         // Flush settings if debounce timer has elapsed
@@ -669,10 +1288,50 @@ impl AppState {
         Ok(())
     }
 
+    /// Returns the metric alerts raised since the last call.
+    pub fn take_raised_alerts(&self) -> Vec<Alert> {
+        self.alerts.lock().unwrap().take_raised()
+    }
+
+    /// Sends the rendered framebuffer to the LCD.
+    ///
+    /// Only changed regions are sent unless a full redraw is requested or
+    /// there is no previously sent frame to compare against.
+    fn send_framebuffer(
+        device: &LcdDevice,
+        render: &mut RenderState,
+        full_redraw: bool,
+    ) -> Result<()> {
+        // Cleared up front so a failed send forces a full redraw next frame
+        let previous = render.last_sent.take();
+        match previous {
+            Some(ref previous) if !full_redraw => {
+                device.update(previous, &render.framebuffer)?;
+            }
+            _ => device.redraw(&render.framebuffer)?,
+        }
+
+        let mut sent = previous.unwrap_or_default();
+        sent.clone_from(&render.framebuffer);
+        render.last_sent = Some(sent);
+        Ok(())
+    }
+
     /// Renders canvas to framebuffer with orientation transformation.
-    fn render_to_framebuffer(render: &mut RenderState, orientation: Orientation) -> Result<()> {
+    /// Converts the canvas to the framebuffer, scaling colors to the
+    /// brightness level in percent. The panel has no brightness command.
+    fn render_to_framebuffer(
+        render: &mut RenderState,
+        orientation: Orientation,
+        level: u8,
+    ) -> Result<()> {
         use ht32_panel_hw::lcd::rgb888_to_rgb565;
 
+        let dim = |c: u8| (c as u32 * level as u32 / 100) as u8;
+        let to_rgb565 = |pixel: &tiny_skia::PremultipliedColorU8| {
+            rgb888_to_rgb565(dim(pixel.red()), dim(pixel.green()), dim(pixel.blue()))
+        };
+
         let pixels = render.canvas.pixmap_pixels();
         let fb_data = render.framebuffer.data_mut();
         let (cw, ch) = render.canvas.dimensions();
@@ -681,7 +1340,7 @@ impl AppState {
             Orientation::Landscape => {
                 for (i, pixel) in pixels.iter().enumerate() {
                     if i < fb_data.len() {
-                        fb_data[i] = rgb888_to_rgb565(pixel.red(), pixel.green(), pixel.blue());
+                        fb_data[i] = to_rgb565(pixel);
                     }
                 }
             }
@@ -689,8 +1348,7 @@ impl AppState {
                 let len = fb_data.len();
                 for (i, pixel) in pixels.iter().enumerate() {
                     if i < len {
-                        fb_data[len - 1 - i] =
-                            rgb888_to_rgb565(pixel.red(), pixel.green(), pixel.blue());
+                        fb_data[len - 1 - i] = to_rgb565(pixel);
                     }
                 }
             }
@@ -703,8 +1361,7 @@ impl AppState {
                         let dst_idx = (dst_y * 320 + dst_x) as usize;
                         if src_idx < pixels.len() && dst_idx < fb_data.len() {
                             let pixel = &pixels[src_idx];
-                            fb_data[dst_idx] =
-                                rgb888_to_rgb565(pixel.red(), pixel.green(), pixel.blue());
+                            fb_data[dst_idx] = to_rgb565(pixel);
                         }
                     }
                 }
@@ -718,8 +1375,7 @@ impl AppState {
                         let dst_idx = (dst_y * 320 + dst_x) as usize;
                         if src_idx < pixels.len() && dst_idx < fb_data.len() {
                             let pixel = &pixels[src_idx];
-                            fb_data[dst_idx] =
-                                rgb888_to_rgb565(pixel.red(), pixel.green(), pixel.blue());
+                            fb_data[dst_idx] = to_rgb565(pixel);
                         }
                     }
                 }
@@ -778,8 +1434,18 @@ impl AppState {
         Ok(())
     }
 
-    /// Sets the display face.
+    /// Sets the display face, pausing the carousel.
     pub fn set_face(&self, name: &str) -> Result<()> {
+        self.apply_face(name)?;
+        if self.display.write().unwrap().carousel.stop() {
+            self.save_display_settings();
+            info!("Panel '{}': carousel paused", self.id);
+        }
+        Ok(())
+    }
+
+    /// Switches the display face.
+    fn apply_face(&self, name: &str) -> Result<()> {
         if let Some(new_face) = faces::create_face(name) {
             let mut display = self.display.write().unwrap();
             display.complications.init_from_defaults(new_face.as_ref());
@@ -787,7 +1453,7 @@ impl AppState {
             display.needs_redraw = true;
             drop(display);
             self.save_display_settings();
-            info!("Display face changed to: {}", name);
+            info!("Panel '{}': display face changed to {}", self.id, name);
             Ok(())
         } else {
             Err(anyhow::anyhow!("Unknown face: {}", name))
@@ -799,6 +1465,342 @@ impl AppState {
         self.display.read().unwrap().face.name().to_string()
     }
 
+    /// Checks that a playlist entry names a known face and theme.
+    fn validate_playlist_entry(entry: &PlaylistEntry) -> Result<()> {
+        if !faces::available_faces().iter().any(|f| f.id == entry.face) {
+            anyhow::bail!("Unknown face: {}", entry.face);
+        }
+        if let Some(ref theme) = entry.theme {
+            if Theme::named(theme).is_none() {
+                anyhow::bail!("Unknown theme: {}", theme);
+            }
+        }
+        if !(1..=carousel::MAX_ENTRY_DURATION).contains(&entry.duration) {
+            anyhow::bail!(
+                "Duration of '{}' must be between 1 and {} seconds",
+                entry.face,
+                carousel::MAX_ENTRY_DURATION
+            );
+        }
+        Ok(())
+    }
+
+    /// Gets the carousel playlist.
+    pub fn playlist(&self) -> Vec<PlaylistEntry> {
+        self.display.read().unwrap().carousel.entries().to_vec()
+    }
+
+    /// Gets the index of the current playlist entry.
+    pub fn carousel_index(&self) -> usize {
+        self.display.read().unwrap().carousel.index()
+    }
+
+    /// Returns true if the carousel is rotating.
+    pub fn is_carousel_running(&self) -> bool {
+        self.display.read().unwrap().carousel.is_running()
+    }
+
+    /// Replaces the carousel playlist. A running carousel restarts from the
+    /// first entry; an empty playlist stops it.
+    pub fn set_playlist(&self, entries: Vec<PlaylistEntry>) -> Result<()> {
+        for entry in &entries {
+            Self::validate_playlist_entry(entry)?;
+        }
+        let count = entries.len();
+        let first = self
+            .display
+            .write()
+            .unwrap()
+            .carousel
+            .set_entries(entries, std::time::Instant::now());
+        if let Some(entry) = first {
+            self.apply_playlist_entry(&entry);
+        }
+        self.save_display_settings();
+        info!("Panel '{}': playlist set to {} face(s)", self.id, count);
+        Ok(())
+    }
+
+    /// Starts the carousel from its current entry.
+    pub fn start_carousel(&self) -> Result<()> {
+        let entry = self
+            .display
+            .write()
+            .unwrap()
+            .carousel
+            .start(std::time::Instant::now())
+            .ok_or_else(|| anyhow::anyhow!("Playlist is empty"))?;
+        self.apply_playlist_entry(&entry);
+        self.save_display_settings();
+        info!("Panel '{}': carousel started", self.id);
+        Ok(())
+    }
+
+    /// Stops the carousel on the current face. Returns true if it was running.
+    pub fn stop_carousel(&self) -> bool {
+        let stopped = self.display.write().unwrap().carousel.stop();
+        if stopped {
+            self.save_display_settings();
+            info!("Panel '{}': carousel stopped", self.id);
+        }
+        stopped
+    }
+
+    /// Shows the next face of the playlist.
+    pub fn next_face(&self) -> Result<()> {
+        let entry = self
+            .display
+            .write()
+            .unwrap()
+            .carousel
+            .next(std::time::Instant::now())
+            .ok_or_else(|| anyhow::anyhow!("Playlist is empty"))?;
+        self.apply_playlist_entry(&entry);
+        Ok(())
+    }
+
+    /// Shows the previous face of the playlist.
+    pub fn previous_face(&self) -> Result<()> {
+        let entry = self
+            .display
+            .write()
+            .unwrap()
+            .carousel
+            .previous(std::time::Instant::now())
+            .ok_or_else(|| anyhow::anyhow!("Playlist is empty"))?;
+        self.apply_playlist_entry(&entry);
+        Ok(())
+    }
+
+    /// Moves the carousel on once the current face's time is up.
+    /// Called from the render loop. Returns true if the face changed.
+    pub fn advance_carousel(&self) -> bool {
+        let entry = self
+            .display
+            .write()
+            .unwrap()
+            .carousel
+            .tick(std::time::Instant::now());
+        match entry {
+            Some(entry) => {
+                self.apply_playlist_entry(&entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Queues a notification to draw over the face for a number of seconds.
+    pub fn show_notification(
+        &self,
+        text: &str,
+        severity: Severity,
+        seconds: u32,
+    ) -> Result<Notification> {
+        let notification = self
+            .notifications
+            .lock()
+            .unwrap()
+            .push(text, severity, seconds)?;
+        self.wake.notify_one();
+        info!(
+            "Panel '{}': {} notification: {}",
+            self.id, severity, notification.text
+        );
+        Ok(notification)
+    }
+
+    /// Returns the notifications pushed to this panel, oldest first.
+    pub fn notification_history(&self) -> Vec<Notification> {
+        self.notifications.lock().unwrap().history()
+    }
+
+    /// Returns the time until the notification on screen goes away.
+    pub fn notification_remaining(&self) -> Option<std::time::Duration> {
+        self.notifications
+            .lock()
+            .unwrap()
+            .remaining(std::time::Instant::now())
+    }
+
+    /// Returns the time until the carousel switches faces, if running.
+    pub fn carousel_remaining(&self) -> Option<std::time::Duration> {
+        self.display
+            .read()
+            .unwrap()
+            .carousel
+            .remaining(std::time::Instant::now())
+    }
+
+    /// Shows a playlist entry's face and theme.
+    fn apply_playlist_entry(&self, entry: &PlaylistEntry) {
+        if let Err(e) = self.apply_face(&entry.face) {
+            warn!("Panel '{}': carousel face skipped: {}", self.id, e);
+        }
+        if let Some(ref theme) = entry.theme {
+            if let Err(e) = self.set_theme(theme) {
+                warn!("Panel '{}': carousel theme skipped: {}", self.id, e);
+            }
+        }
+    }
+
+    /// Checks that a schedule rule names a known face and theme.
+    fn validate_schedule_rule(rule: &ScheduleRule) -> Result<()> {
+        if let Some(ref face) = rule.face {
+            if !faces::available_faces().iter().any(|f| f.id == *face) {
+                anyhow::bail!("'{}': unknown face: {}", rule.name, face);
+            }
+        }
+        if let Some(ref theme) = rule.theme {
+            if Theme::named(theme).is_none() {
+                anyhow::bail!("'{}': unknown theme: {}", rule.name, theme);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the schedule rules that apply to this panel.
+    pub fn schedule_rules(&self) -> Vec<ScheduleRule> {
+        self.schedule
+            .lock()
+            .unwrap()
+            .schedule
+            .rules()
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the name of the active schedule rule.
+    pub fn active_schedule(&self) -> Option<String> {
+        let schedule = self.schedule.lock().unwrap();
+        schedule
+            .active
+            .and_then(|index| schedule.schedule.rule(index))
+            .map(|rule| rule.name.clone())
+    }
+
+    /// Applies the schedule rule active at the current local time.
+    /// Called from the render loop. Returns true if the active rule changed.
+    pub async fn apply_schedule(&self) -> bool {
+        self.apply_schedule_at(WeekTime::now()).await
+    }
+
+    /// Switches to the schedule rule active at the given time.
+    ///
+    /// Settings the new rule leaves alone keep their current values, except
+    /// those the previous rule changed, which go back to what they were
+    /// before the first rule took over.
+    async fn apply_schedule_at(&self, time: WeekTime) -> bool {
+        let (led_theme, led_intensity, led_speed) = self.led_settings();
+        let current = ScheduleRestore {
+            rule: String::new(),
+            face: self.face_name(),
+            theme: self.theme_name(),
+            led_theme,
+            led_intensity,
+            led_speed,
+            carousel: self.is_carousel_running(),
+            brightness: self.brightness(),
+            display_off: self.is_display_off(),
+        };
+
+        let (leaving, entering, baseline, restore_all) = {
+            let mut schedule = self.schedule.lock().unwrap();
+            let next = schedule.schedule.active(time);
+            // A saved baseline without an active rule is left over from a
+            // rule that ended or was removed while the daemon was down
+            if next == schedule.active && (next.is_some() || schedule.restore.is_none()) {
+                return false;
+            }
+            let leaving = schedule
+                .active
+                .and_then(|index| schedule.schedule.rule(index))
+                .cloned();
+            let entering = next
+                .and_then(|index| schedule.schedule.rule(index))
+                .cloned();
+            let restore_all = leaving.is_none() && schedule.restore.is_some();
+            let baseline = schedule.restore.take().unwrap_or(current);
+            schedule.active = next;
+            schedule.restore = entering.as_ref().map(|rule| ScheduleRestore {
+                rule: rule.name.clone(),
+                ..baseline.clone()
+            });
+            (leaving, entering, baseline, restore_all)
+        };
+        let left =
+            |changed: fn(&ScheduleRule) -> bool| leaving.as_ref().map_or(restore_all, changed);
+
+        match &entering {
+            Some(rule) => info!("Panel '{}': schedule '{}' active", self.id, rule.name),
+            None => info!("Panel '{}': no schedule active", self.id),
+        }
+
+        if let Some(face) = entering.as_ref().and_then(|rule| rule.face.as_deref()) {
+            if let Err(e) = self.set_face(face) {
+                warn!("Panel '{}': scheduled face skipped: {}", self.id, e);
+            }
+        } else if left(|rule| rule.face.is_some()) {
+            let restored = if baseline.carousel {
+                self.start_carousel()
+            } else {
+                self.set_face(&baseline.face)
+            };
+            if let Err(e) = restored {
+                warn!("Panel '{}': face not restored: {}", self.id, e);
+            }
+        }
+
+        let theme = match entering.as_ref().and_then(|rule| rule.theme.clone()) {
+            Some(theme) => Some(theme),
+            None if left(|rule| rule.theme.is_some()) => Some(baseline.theme.clone()),
+            None => None,
+        };
+        if let Some(theme) = theme {
+            if let Err(e) = self.set_theme(&theme) {
+                warn!("Panel '{}': scheduled theme skipped: {}", self.id, e);
+            }
+        }
+
+        let brightness = match entering.as_ref().and_then(|rule| rule.brightness) {
+            Some(brightness) => Some(brightness),
+            None if left(|rule| rule.brightness.is_some()) => Some(baseline.brightness),
+            None => None,
+        };
+        if let Some(brightness) = brightness {
+            if let Err(e) = self.set_brightness(brightness) {
+                warn!("Panel '{}': scheduled brightness skipped: {}", self.id, e);
+            }
+        }
+
+        match entering.as_ref().and_then(|rule| rule.display_off) {
+            Some(off) => self.set_display_off(off),
+            None if left(|rule| rule.display_off.is_some()) => {
+                self.set_display_off(baseline.display_off)
+            }
+            None => {}
+        }
+
+        if entering.as_ref().is_some_and(ScheduleRule::sets_led) || left(ScheduleRule::sets_led) {
+            let rule = entering.as_ref();
+            let theme = rule
+                .and_then(ScheduleRule::led_theme_byte)
+                .unwrap_or(baseline.led_theme);
+            let intensity = rule
+                .and_then(|rule| rule.led_intensity)
+                .unwrap_or(baseline.led_intensity);
+            let speed = rule
+                .and_then(|rule| rule.led_speed)
+                .unwrap_or(baseline.led_speed);
+            if let Err(e) = self.set_led(theme, intensity, speed).await {
+                warn!("Panel '{}': scheduled LED change failed: {}", self.id, e);
+            }
+        }
+
+        self.save_display_settings();
+        true
+    }
+
     /// Gets available complications for the current face.
     pub fn available_complications(&self) -> Vec<faces::Complication> {
         self.display.read().unwrap().face.available_complications()
@@ -848,9 +1850,7 @@ impl AppState {
 
     /// Sets the theme by name.
     pub fn set_theme(&self, name: &str) -> Result<()> {
-        if !faces::available_themes().iter().any(|t| t.id == name) {
-            return Err(anyhow::anyhow!("Unknown theme: {}", name));
-        }
+        let theme = Theme::named(name).ok_or_else(|| anyhow::anyhow!("Unknown theme: {}", name))?;
 
         {
             let mut display = self.display.write().unwrap();
@@ -858,7 +1858,6 @@ impl AppState {
             display.needs_redraw = true;
         }
 
-        let theme = Theme::from_preset(name);
         {
             let mut render = self.render.write().unwrap();
             render.canvas.set_background(theme.background);
@@ -866,20 +1865,10 @@ impl AppState {
         }
 
         self.save_display_settings();
-        info!("Theme set to: {}", name);
+        info!("Panel '{}': theme set to {}", self.id, name);
         Ok(())
     }
 
-    /// Returns a list of available themes.
-    pub fn available_themes(&self) -> Vec<faces::ThemeInfo> {
-        faces::available_themes()
-    }
-
-    /// Lists all available network interfaces.
-    pub fn list_network_interfaces(&self) -> Vec<String> {
-        NetworkSensor::list_interfaces()
-    }
-
     /// Gets a complication option value.
     pub fn get_complication_option(
         &self,
@@ -941,6 +1930,17 @@ impl AppState {
                             interfaces
                         ));
                     }
+                } else if complication_id == faces::complication_names::CPU_TEMP
+                    && option_id == faces::complication_options::SENSOR
+                {
+                    let sensors = TemperatureSensor::list_sensors();
+                    if value != "auto" && !sensors.contains(&value.to_string()) {
+                        return Err(anyhow::anyhow!(
+                            "Unknown temperature sensor '{}'. Available: auto, {:?}",
+                            value,
+                            sensors
+                        ));
+                    }
                 } else {
                     let valid_values: Vec<_> = choices.iter().map(|c| c.value.as_str()).collect();
                     return Err(anyhow::anyhow!(
@@ -982,3 +1982,476 @@ impl AppState {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{recorded_opener, test_config};
+    use ht32_panel_hw::lcd::{LcdEvent, RecordingTransport, BUFFER_SIZE, CHUNK_COUNT};
+
+    /// Creates a single-panel state whose LCD writes into the given recording.
+    fn recorded_state(name: &str, recording: &RecordingTransport) -> Arc<PanelState> {
+        let app = AppState::with_lcd_opener(test_config(name), |_| recorded_opener(recording));
+        app.unwrap().default_panel().clone()
+    }
+
+    fn sent_framebuffer(state: &PanelState) -> Framebuffer {
+        state.render.read().unwrap().framebuffer.clone()
+    }
+
+    #[tokio::test]
+    async fn test_startup_initializes_panel() {
+        let recording = RecordingTransport::new();
+        let _state = recorded_state("startup", &recording);
+
+        let events = recording.events();
+        assert!(matches!(events[0], LcdEvent::Heartbeat { .. }));
+        assert_eq!(events[1], LcdEvent::Orientation { portrait: false });
+    }
+
+    #[tokio::test]
+    async fn test_render_frame_full_then_partial() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("render", &recording);
+        recording.take_events();
+
+        state.render_frame().await.unwrap();
+        assert_eq!(recording.take_events(), vec![LcdEvent::Redraw]);
+        assert_eq!(recording.screen().data(), sent_framebuffer(&state).data());
+
+        state.render_frame().await.unwrap();
+        let events = recording.take_events();
+        assert!(!events.contains(&LcdEvent::Redraw));
+        assert!(events.iter().all(|e| matches!(e, LcdEvent::Refresh { .. })));
+        assert_eq!(recording.screen().data(), sent_framebuffer(&state).data());
+
+        state.force_redraw();
+        state.render_frame().await.unwrap();
+        assert_eq!(recording.take_events(), vec![LcdEvent::Redraw]);
+    }
+
+    #[tokio::test]
+    async fn test_frames_are_published_newest_first() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("frames", &recording);
+        let mut frames = state.subscribe_frames();
+        assert!(frames.borrow().is_none());
+
+        // A subscriber that misses frames sees only the newest
+        for _ in 0..3 {
+            state.render_frame().await.unwrap();
+        }
+        assert!(frames.has_changed().unwrap());
+        let frame = frames.borrow_and_update().clone().unwrap();
+        assert_eq!(frame.frame, 3);
+        assert!(!frames.has_changed().unwrap());
+
+        let json = serde_json::to_value(&*frame).unwrap();
+        assert_eq!(json["frame"], 3);
+        assert!(json["data"]["cpu_percent"].is_number());
+        assert_eq!(json["data"]["alerts"]["cpu_percent"], "normal");
+    }
+
+    #[tokio::test]
+    async fn test_orientation_change_redraws_rotated() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("orientation", &recording);
+        state.render_frame().await.unwrap();
+        recording.take_events();
+
+        state.set_orientation(Orientation::Portrait).unwrap();
+        state.render_frame().await.unwrap();
+
+        // Hardware stays in landscape; rotation happens in software
+        assert_eq!(
+            recording.take_events(),
+            vec![LcdEvent::Orientation { portrait: false }, LcdEvent::Redraw]
+        );
+        assert_eq!(state.render.read().unwrap().canvas.dimensions(), (170, 320));
+        assert_eq!(recording.screen().data(), sent_framebuffer(&state).data());
+    }
+
+    #[tokio::test]
+    async fn test_attach_after_headless_start() {
+        let recording = RecordingTransport::new();
+        let present = Arc::new(AtomicBool::new(false));
+        let opener_recording = recording.clone();
+        let opener_present = present.clone();
+        let app = AppState::with_lcd_opener(test_config("reconnect"), |_| {
+            let recording = opener_recording.clone();
+            let present = opener_present.clone();
+            Box::new(move || {
+                if present.load(Ordering::Relaxed) {
+                    Ok(LcdDevice::with_transport(recording.clone()))
+                } else {
+                    Err(ht32_panel_hw::Error::LcdNotFound)
+                }
+            })
+        })
+        .unwrap();
+        let state = app.default_panel();
+        assert!(!state.is_lcd_connected());
+
+        // Rendering never opens the device itself
+        state.render_frame().await.unwrap();
+        assert!(!state.attach_lcd());
+        assert_eq!(recording.packet_count(), 0);
+
+        present.store(true, Ordering::Relaxed);
+        assert!(state.attach_lcd());
+        assert!(state.is_lcd_connected());
+        assert!(!state.attach_lcd());
+
+        state.render_frame().await.unwrap();
+        assert_eq!(recording.events().last(), Some(&LcdEvent::Redraw));
+        assert_eq!(recording.screen().data(), sent_framebuffer(state).data());
+    }
+
+    #[tokio::test]
+    async fn test_unplug_detaches_and_replug_redraws() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("unplug", &recording);
+        state.render_frame().await.unwrap();
+
+        // A responsive device survives a probe
+        assert!(!state.probe_lcd());
+        assert!(state.is_lcd_connected());
+
+        recording.set_disconnected(true);
+        assert!(state.probe_lcd());
+        assert!(!state.is_lcd_connected());
+        state.render_frame().await.unwrap();
+
+        recording.set_disconnected(false);
+        assert!(state.attach_lcd());
+        recording.take_events();
+        state.render_frame().await.unwrap();
+        assert_eq!(recording.take_events(), vec![LcdEvent::Redraw]);
+    }
+
+    #[tokio::test]
+    async fn test_alert_led_overrides_user_settings() {
+        let recording = RecordingTransport::new();
+        let mut config = test_config("alert-led");
+        config.alerts.ram_percent = Some(config::Threshold {
+            warning: None,
+            critical: Some(0.0),
+        });
+        config.alerts.led_theme = Some("rainbow".to_string());
+        let app = AppState::with_lcd_opener(config, |_| recorded_opener(&recording)).unwrap();
+        let state = app.default_panel();
+
+        // The override applies even when the frame fails to reach the LCD
+        recording.set_disconnected(true);
+        assert!(state.render_frame().await.is_err());
+        assert_eq!(state.led.read().unwrap().alert, Some(LedTheme::Rainbow));
+
+        // User settings are kept for when the alert clears
+        state.set_led(2, 3, 4).await.unwrap();
+        assert_eq!(state.led_settings(), (2, 3, 4));
+        assert_eq!(
+            state.led.read().unwrap().effective().unwrap(),
+            (LedTheme::Rainbow, 3, 4)
+        );
+        state.led.write().unwrap().alert = None;
+        assert_eq!(
+            state.led.read().unwrap().effective().unwrap(),
+            (LedTheme::Breathing, 3, 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_theme_is_rejected() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("theme", &recording);
+
+        assert!(state.set_theme("no-such-theme").is_err());
+        assert_eq!(state.theme_name(), "default");
+        state.set_theme("ember").unwrap();
+        assert_eq!(state.theme_name(), "ember");
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_forces_full_redraw() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("failed-send", &recording);
+        state.render_frame().await.unwrap();
+
+        recording.set_disconnected(true);
+        assert!(state.render_frame().await.is_err());
+
+        recording.set_disconnected(false);
+        recording.take_events();
+        state.render_frame().await.unwrap();
+        assert_eq!(recording.take_events(), vec![LcdEvent::Redraw]);
+    }
+
+    #[tokio::test]
+    async fn test_virtual_lcd_writes_packets() {
+        let mut config = test_config("virtual");
+        let path = PathBuf::from(&config.state_dir).join("panel.bin");
+        config.devices.lcd = format!("virtual:{}", path.display());
+
+        let app = AppState::new(config).unwrap();
+        let state = app.default_panel();
+        assert!(state.is_lcd_connected());
+        state.render_frame().await.unwrap();
+
+        // Heartbeat, orientation and a full redraw
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(data.len() % BUFFER_SIZE, 0);
+        assert_eq!(data.len() / BUFFER_SIZE, 2 + CHUNK_COUNT);
+    }
+
+    #[tokio::test]
+    async fn test_named_panels_are_independent() {
+        let mut config = test_config("panels");
+        let led = config.devices.led.clone();
+        config.devices.panels = ["left", "right"]
+            .into_iter()
+            .map(|id| PanelConfig {
+                id: id.to_string(),
+                lcd: "auto".to_string(),
+                serial: None,
+                led: led.clone(),
+                face: None,
+                theme: None,
+                orientation: None,
+            })
+            .collect();
+        config.devices.panels[1].face = Some("clock".to_string());
+        let state_dir = PathBuf::from(&config.state_dir);
+
+        let left = RecordingTransport::new();
+        let right = RecordingTransport::new();
+        let app = AppState::with_lcd_opener(config, |panel| match panel.id.as_str() {
+            "left" => recorded_opener(&left),
+            _ => recorded_opener(&right),
+        })
+        .unwrap();
+
+        assert_eq!(app.panel_ids(), vec!["left", "right"]);
+        assert_eq!(app.default_panel().id(), "left");
+        assert!(app.panel("missing").is_none());
+
+        let left_panel = app.panel("left").unwrap();
+        let right_panel = app.panel("right").unwrap();
+        assert_eq!(left_panel.face_name(), "professional");
+        assert_eq!(right_panel.face_name(), "clock");
+
+        left_panel.set_theme("nord").unwrap();
+        assert_eq!(right_panel.theme_name(), "default");
+
+        right_panel.render_frame().await.unwrap();
+        assert_eq!(right.events().last(), Some(&LcdEvent::Redraw));
+        assert!(!left.events().contains(&LcdEvent::Redraw));
+
+        assert!(state_dir.join("display-left.toml").exists());
+        assert!(state_dir.join("display-right.toml").exists());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_interval_defaults_to_config() {
+        let mut config = test_config("refresh");
+        config.refresh_interval = 1000;
+        let recording = RecordingTransport::new();
+        let app = AppState::with_lcd_opener(config, |_| recorded_opener(&recording)).unwrap();
+        let state = app.default_panel();
+        assert_eq!(state.refresh_interval_ms(), 1000);
+
+        state.set_refresh_interval(5000).unwrap();
+        assert_eq!(state.refresh_interval_ms(), 5000);
+
+        assert!(state.set_refresh_interval(499).is_err());
+        assert!(state.set_refresh_interval(10001).is_err());
+        assert_eq!(state.refresh_interval_ms(), 5000);
+    }
+
+    #[tokio::test]
+    async fn test_carousel_rotates_and_manual_face_pauses() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("carousel", &recording);
+        let entry = |face: &str| PlaylistEntry {
+            face: face.to_string(),
+            theme: None,
+            duration: 30,
+        };
+
+        assert!(state.start_carousel().is_err());
+        assert!(state.set_playlist(vec![entry("missing")]).is_err());
+
+        state
+            .set_playlist(vec![
+                entry("arcs"),
+                PlaylistEntry {
+                    theme: Some("nord".to_string()),
+                    ..entry("clock")
+                },
+            ])
+            .unwrap();
+        state.start_carousel().unwrap();
+        assert_eq!(state.face_name(), "arcs");
+        assert!(!state.advance_carousel());
+
+        state.next_face().unwrap();
+        assert_eq!(state.face_name(), "clock");
+        assert_eq!(state.theme_name(), "nord");
+        assert_eq!(state.carousel_index(), 1);
+
+        state.flush_display_settings();
+        let saved = PanelState::load_display_settings(&state.settings_file).unwrap();
+        assert!(saved.playlist.running);
+        assert_eq!(saved.playlist.entries.len(), 2);
+
+        state.set_face("digits").unwrap();
+        assert!(!state.is_carousel_running());
+        assert!(state.carousel_remaining().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_applies_and_restores_settings() {
+        let recording = RecordingTransport::new();
+        let mut config = test_config("schedule");
+        let rules: Config = toml::from_str(
+            r#"
+            [[schedule]]
+            name = "night"
+            start = "22:00"
+            end = "07:00"
+            theme = "nord"
+            led_theme = "off"
+
+            [[schedule]]
+            name = "work"
+            start = "09:00"
+            days = ["weekdays"]
+            face = "arcs"
+            "#,
+        )
+        .unwrap();
+        config.schedule = rules.schedule;
+        let app = AppState::with_lcd_opener(config, |_| recorded_opener(&recording)).unwrap();
+        let state = app.default_panel().clone();
+        let at = |day: u8, hour: u16| WeekTime {
+            day,
+            minute: hour * 60,
+        };
+
+        assert!(state.apply_schedule_at(at(1, 23)).await);
+        assert_eq!(state.active_schedule().as_deref(), Some("night"));
+        assert_eq!(state.theme_name(), "nord");
+        assert_eq!(state.face_name(), "professional");
+        assert_eq!(state.led_settings().0, LedTheme::Off as u8);
+        assert!(!state.apply_schedule_at(at(1, 23)).await);
+
+        // The night ends before work starts
+        assert!(state.apply_schedule_at(at(2, 8)).await);
+        assert_eq!(state.active_schedule(), None);
+        assert_eq!(state.theme_name(), "default");
+        assert_eq!(state.led_settings().0, 2);
+
+        assert!(state.apply_schedule_at(at(2, 10)).await);
+        assert_eq!(state.face_name(), "arcs");
+        state.flush_display_settings();
+        let saved = PanelState::load_display_settings(&state.settings_file).unwrap();
+        let restore = saved.schedule_restore.unwrap();
+        assert_eq!(restore.rule, "work");
+        assert_eq!(restore.face, "professional");
+
+        // Switching rules puts back what the previous rule changed
+        assert!(state.apply_schedule_at(at(2, 22)).await);
+        assert_eq!(state.face_name(), "professional");
+        assert_eq!(state.theme_name(), "nord");
+    }
+
+    #[tokio::test]
+    async fn test_brightness_dims_and_display_off_blanks() {
+        use ht32_panel_hw::lcd::rgb888_to_rgb565;
+
+        let recording = RecordingTransport::new();
+        let state = recorded_state("brightness", &recording);
+        state.set_theme("solarized-light").unwrap();
+        let corner = |state: &PanelState| sent_framebuffer(state).data()[0];
+
+        state.render_frame().await.unwrap();
+        assert_eq!(corner(&state), rgb888_to_rgb565(0xFD, 0xF6, 0xE3));
+
+        assert!(state.set_brightness(101).is_err());
+        state.set_brightness(50).unwrap();
+        state.render_frame().await.unwrap();
+        assert_eq!(corner(&state), rgb888_to_rgb565(0x7E, 0x7B, 0x71));
+
+        state.set_display_off(true);
+        state.render_frame().await.unwrap();
+        assert!(sent_framebuffer(&state).data().iter().all(|&p| p == 0));
+
+        state.flush_display_settings();
+        let saved = PanelState::load_display_settings(&state.settings_file).unwrap();
+        assert_eq!(saved.brightness, 50);
+        assert!(saved.display_off);
+    }
+
+    #[tokio::test]
+    async fn test_notification_draws_banner_over_face() {
+        use ht32_panel_hw::lcd::rgb888_to_rgb565;
+
+        let recording = RecordingTransport::new();
+        let state = recorded_state("notify", &recording);
+        state.set_theme("solarized-light").unwrap();
+        let theme = Theme::named("solarized-light").unwrap();
+        let bottom_left = |state: &PanelState| {
+            let fb = sent_framebuffer(state);
+            fb.data()[fb.data().len() - 320]
+        };
+
+        state.render_frame().await.unwrap();
+        let face = bottom_left(&state);
+        let critical = theme.critical;
+        let critical = rgb888_to_rgb565(
+            (critical >> 16) as u8,
+            (critical >> 8) as u8,
+            critical as u8,
+        );
+        assert_ne!(face, critical);
+
+        let notification = state
+            .show_notification("Build failed", Severity::Critical, 5)
+            .unwrap();
+        state.render_frame().await.unwrap();
+        assert_eq!(bottom_left(&state), critical);
+        assert!(state.notification_remaining().is_some());
+        assert_eq!(state.notification_history(), vec![notification]);
+    }
+
+    #[tokio::test]
+    async fn test_blank_is_not_persisted_and_reopen_initializes_lcd() {
+        let recording = RecordingTransport::new();
+        let state = recorded_state("blank", &recording);
+        state.set_theme("solarized-light").unwrap();
+
+        state.blank().await;
+        state.render_frame().await.unwrap();
+        assert!(sent_framebuffer(&state).data().iter().all(|&p| p == 0));
+
+        // LED changes while blanked are kept for later
+        state.set_led(2, 3, 4).await.unwrap();
+        assert_eq!(state.led_settings(), (2, 3, 4));
+        assert!(state.led.read().unwrap().needs_update);
+
+        state.flush_display_settings();
+        let saved = PanelState::load_display_settings(&state.settings_file).unwrap();
+        assert!(!saved.display_off);
+
+        recording.take_events();
+        assert!(!state.reopen_lcd());
+        let events = recording.take_events();
+        assert!(matches!(events[0], LcdEvent::Heartbeat { .. }));
+        assert_eq!(events[1], LcdEvent::Orientation { portrait: false });
+
+        state.unblank();
+        assert!(!state.is_blanked());
+        state.render_frame().await.unwrap();
+        assert_ne!(sent_framebuffer(&state).data()[0], 0);
+        assert_eq!(recording.take_events(), vec![LcdEvent::Redraw]);
+    }
+}
@@ -0,0 +1,342 @@
+//! Readiness, status and watchdog notifications for systemd.
+//!
+//! When started by systemd with `Type=notify`, the daemon reports `READY=1`
+//! once D-Bus and the render loops are up, keeps `STATUS=` current with the
+//! state of every panel, and pings the watchdog while its loops keep running.
+//! Loops record when they expect to come around again; the watchdog ping is
+//! withheld while any of them is overdue, e.g. stuck in a HID write, so
+//! systemd restarts the daemon. Outside systemd all of this is a no-op.
+
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::dbus::DaemonSignals;
+use crate::state::{AppState, PanelState};
+
+/// Longest wait for the first frames before reporting readiness anyway.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval between status refreshes without a signal.
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sends notifications to the socket in `NOTIFY_SOCKET`.
+pub struct Notifier {
+    socket: UnixDatagram,
+    addr: SocketAddr,
+}
+
+impl Notifier {
+    /// Returns a notifier if the service manager expects notifications.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("NOTIFY_SOCKET").ok()?;
+        match Self::connect(&path) {
+            Ok(notifier) => Some(notifier),
+            Err(e) => {
+                warn!("Ignoring NOTIFY_SOCKET '{}': {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Creates a notifier for a socket path, or an abstract socket name
+    /// starting with '@'.
+    pub fn connect(path: &str) -> std::io::Result<Self> {
+        let addr = match path.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name)?,
+            None => SocketAddr::from_pathname(path)?,
+        };
+        let socket = UnixDatagram::unbound()?;
+        // A full queue drops the message rather than stalling the runtime
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, addr })
+    }
+
+    /// Sends a notification of newline-separated `KEY=value` assignments.
+    pub fn notify(&self, message: &str) {
+        if let Err(e) = self.socket.send_to_addr(message.as_bytes(), &self.addr) {
+            debug!("Failed to notify systemd: {}", e);
+        }
+    }
+}
+
+/// Reads the watchdog timeout systemd set for this process, if any.
+pub fn watchdog_timeout() -> Option<Duration> {
+    parse_watchdog(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn parse_watchdog(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    // The timeout may be meant for another process, e.g. a wrapper script
+    if let Some(pid) = pid {
+        if pid.parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    let usec = usec?.parse::<u64>().ok().filter(|&usec| usec > 0)?;
+    Some(Duration::from_micros(usec))
+}
+
+/// When a loop is next due.
+struct Deadline {
+    name: String,
+    due: Instant,
+}
+
+/// Tracks whether the daemon's loops keep coming around.
+#[derive(Clone, Default)]
+pub struct Liveness {
+    loops: Arc<Mutex<Vec<Arc<Mutex<Deadline>>>>>,
+}
+
+/// A loop's handle for reporting progress to [`Liveness`].
+pub struct Beat {
+    deadline: Arc<Mutex<Deadline>>,
+}
+
+impl Beat {
+    /// Records a successful iteration and when the next one is due.
+    pub fn completed(&self, next_within: Duration) {
+        self.deadline.lock().unwrap().due = Instant::now() + next_within;
+    }
+}
+
+impl Liveness {
+    /// Registers a loop, due to complete its first iteration right away.
+    pub fn register(&self, name: impl Into<String>) -> Beat {
+        let deadline = Arc::new(Mutex::new(Deadline {
+            name: name.into(),
+            due: Instant::now(),
+        }));
+        self.loops.lock().unwrap().push(deadline.clone());
+        Beat { deadline }
+    }
+
+    /// Returns the names of loops more than `slack` past their deadline.
+    pub fn stalled(&self, slack: Duration) -> Vec<String> {
+        let now = Instant::now();
+        self.loops
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|deadline| {
+                let deadline = deadline.lock().unwrap();
+                (now > deadline.due + slack).then(|| deadline.name.clone())
+            })
+            .collect()
+    }
+}
+
+/// Pings the watchdog at half its timeout while no loop has stalled.
+pub async fn run_watchdog(notifier: Arc<Notifier>, liveness: Liveness, timeout: Duration) {
+    info!("Watchdog enabled with a {:?} timeout", timeout);
+    let mut ticker = tokio::time::interval((timeout / 2).max(Duration::from_millis(1)));
+    let mut was_stalled = false;
+    loop {
+        ticker.tick().await;
+        let stalled = liveness.stalled(timeout);
+        if stalled.is_empty() {
+            notifier.notify("WATCHDOG=1");
+        } else if !was_stalled {
+            warn!(
+                "Withholding watchdog pings, stalled: {}",
+                stalled.join(", ")
+            );
+        }
+        was_stalled = !stalled.is_empty();
+    }
+}
+
+/// Waits for every panel's first frame, then reports readiness.
+pub async fn notify_ready(notifier: &Notifier, state: &AppState) {
+    let first_frames = async {
+        for panel in state.panels() {
+            let _ = panel.subscribe_frames().wait_for(|f| f.is_some()).await;
+        }
+    };
+    if tokio::time::timeout(READY_TIMEOUT, first_frames)
+        .await
+        .is_err()
+    {
+        warn!(
+            "No frame rendered after {:?}, reporting ready anyway",
+            READY_TIMEOUT
+        );
+    }
+    notifier.notify(&format!("READY=1\nSTATUS={}", status_line(state)));
+}
+
+/// Keeps the service status current as panels change.
+pub async fn run_status(
+    notifier: Arc<Notifier>,
+    state: Arc<AppState>,
+    signal_tx: broadcast::Sender<DaemonSignals>,
+) {
+    let mut signal_rx = signal_tx.subscribe();
+    let mut ticker = tokio::time::interval(STATUS_INTERVAL);
+    // Readiness already reported the current status
+    let mut last = status_line(&state);
+    loop {
+        tokio::select! {
+            result = signal_rx.recv() => {
+                if let Err(broadcast::error::RecvError::Closed) = result {
+                    return;
+                }
+            }
+            _ = ticker.tick() => {}
+        }
+        let status = status_line(&state);
+        if status != last {
+            notifier.notify(&format!("STATUS={}", status));
+            last = status;
+        }
+    }
+}
+
+/// Describes the panels, e.g. "LCD connected, face=arcs, 2.5s refresh".
+pub fn status_line(state: &AppState) -> String {
+    let panels = state.panels();
+    if let [panel] = panels {
+        return panel_status(panel);
+    }
+    panels
+        .iter()
+        .map(|panel| format!("{}: {}", panel.id(), panel_status(panel)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn panel_status(panel: &PanelState) -> String {
+    if !panel.is_lcd_connected() {
+        return "LCD missing, retrying".to_string();
+    }
+    let refresh = panel.refresh_interval_ms() as f64 / 1000.0;
+    let mut status = format!(
+        "LCD connected, face={}, {}s refresh",
+        panel.face_name(),
+        refresh
+    );
+    if panel.is_blanked() {
+        status.push_str(", blanked");
+    } else if panel.is_display_off() {
+        status.push_str(", display off");
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::app_state;
+
+    /// Binds a fake `NOTIFY_SOCKET` and returns its path.
+    fn fake_notify_socket(name: &str) -> (tokio::net::UnixDatagram, String) {
+        let path = std::env::temp_dir().join(format!(
+            "ht32-panel-test-{}-{}.sock",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        let socket = tokio::net::UnixDatagram::bind(&path).unwrap();
+        (socket, path.to_string_lossy().into_owned())
+    }
+
+    async fn recv(socket: &tokio::net::UnixDatagram) -> String {
+        let mut buf = [0u8; 256];
+        let len = tokio::time::timeout(Duration::from_secs(5), socket.recv(&mut buf))
+            .await
+            .expect("no notification")
+            .unwrap();
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_reports_ready_with_status() {
+        let (socket, path) = fake_notify_socket("ready");
+        let notifier = Notifier::connect(&path).unwrap();
+        let (state, _recording) = app_state("notify-ready", |config| {
+            config.refresh_interval = 2500;
+        });
+        state.default_panel().render_frame().await.unwrap();
+
+        notify_ready(&notifier, &state).await;
+        let face = state.default_panel().face_name();
+        assert_eq!(
+            recv(&socket).await,
+            format!("READY=1\nSTATUS=LCD connected, face={}, 2.5s refresh", face)
+        );
+
+        state.default_panel().detach_lcd();
+        assert_eq!(status_line(&state), "LCD missing, retrying");
+    }
+
+    #[tokio::test]
+    async fn test_notifies_abstract_socket() {
+        let name = format!("ht32-panel-test-{}-abstract", std::process::id());
+        let addr = SocketAddr::from_abstract_name(&name).unwrap();
+        let socket = UnixDatagram::bind_addr(&addr).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let socket = tokio::net::UnixDatagram::from_std(socket).unwrap();
+
+        Notifier::connect(&format!("@{}", name))
+            .unwrap()
+            .notify("STATUS=hello");
+        assert_eq!(recv(&socket).await, "STATUS=hello");
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_pings_only_while_loops_run() {
+        let (socket, path) = fake_notify_socket("watchdog");
+        let notifier = Arc::new(Notifier::connect(&path).unwrap());
+        let liveness = Liveness::default();
+        let beat = liveness.register("render");
+        beat.completed(Duration::from_secs(60));
+
+        let timeout = Duration::from_millis(100);
+        tokio::spawn(run_watchdog(notifier, liveness.clone(), timeout));
+        assert_eq!(recv(&socket).await, "WATCHDOG=1");
+
+        // A loop that misses its deadline by more than the timeout stalls
+        beat.completed(Duration::ZERO);
+        assert!(liveness.stalled(timeout).is_empty());
+        tokio::time::sleep(timeout * 2).await;
+        assert_eq!(liveness.stalled(timeout), ["render"]);
+
+        // Drop the pings sent before the stall, then expect silence
+        let mut buf = [0u8; 256];
+        while socket.try_recv(&mut buf).is_ok() {}
+        assert!(
+            tokio::time::timeout(timeout * 2, socket.recv(&mut buf))
+                .await
+                .is_err(),
+            "pinged while stalled"
+        );
+
+        // Pings resume once the loop comes around again
+        beat.completed(Duration::from_secs(60));
+        assert!(liveness.stalled(timeout).is_empty());
+        assert_eq!(recv(&socket).await, "WATCHDOG=1");
+    }
+
+    #[test]
+    fn test_parse_watchdog() {
+        assert_eq!(
+            parse_watchdog(Some("30000000"), None, 42),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_watchdog(Some("30000000"), Some("42"), 42),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_watchdog(Some("30000000"), Some("7"), 42), None);
+        assert_eq!(parse_watchdog(Some("0"), None, 42), None);
+        assert_eq!(parse_watchdog(None, None, 42), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! Helpers for tests that drive the daemon state, optionally against a
+//! private bus.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ht32_panel_hw::lcd::{LcdDevice, RecordingTransport};
+
+use crate::config::Config;
+use crate::state::{AppState, LcdOpener};
+
+/// A private bus, killed when dropped.
+pub struct Bus {
+    daemon: Child,
+    address: String,
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+impl Bus {
+    /// Starts a private bus. Tests using it are ignored by default, as
+    /// they need dbus-daemon; run them with `cargo test -- --ignored`.
+    pub fn start() -> Self {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start dbus-daemon");
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .expect("failed to read the bus address");
+        Self {
+            daemon,
+            address: address.trim().to_string(),
+        }
+    }
+
+    /// Returns a builder for a connection to the bus.
+    pub fn connect(&self) -> zbus::connection::Builder<'static> {
+        zbus::connection::Builder::address(self.address.as_str()).unwrap()
+    }
+}
+
+/// Creates a config with a unique, empty state directory for a test.
+pub fn test_config(name: &str) -> Config {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "ht32-panel-test-{}-{}-{}",
+        std::process::id(),
+        name,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut config = Config {
+        state_dir: dir.to_string_lossy().into_owned(),
+        ..Config::default()
+    };
+    // Never talk to a real LED controller from tests
+    config.devices.led = dir.join("led").to_string_lossy().into_owned();
+    config
+}
+
+/// Returns an opener whose LCD writes into the given recording.
+pub fn recorded_opener(recording: &RecordingTransport) -> LcdOpener {
+    let recording = recording.clone();
+    Box::new(move || Ok(LcdDevice::with_transport(recording.clone())))
+}
+
+/// Creates a single-panel state whose LCD writes into the returned
+/// recording, after letting the test adjust the config.
+pub fn app_state(
+    name: &str,
+    configure: impl FnOnce(&mut Config),
+) -> (Arc<AppState>, RecordingTransport) {
+    let mut config = test_config(name);
+    configure(&mut config);
+    let recording = RecordingTransport::new();
+    let state = AppState::with_lcd_opener(config, |_| recorded_opener(&recording)).unwrap();
+    (Arc::new(state), recording)
+}
+
+/// Waits for a condition a watcher brings about.
+pub async fn wait_for(what: &str, condition: impl Fn() -> bool) {
+    for _ in 0..200 {
+        if condition() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("timed out waiting for {}", what);
+}
@@ -0,0 +1,1618 @@
+//! Versioned JSON API.
+//!
+//! `/api/v1` offers the operations of the D-Bus interface as JSON, for
+//! scripts. Like the HTMX routes, a request picks a panel with the `panel`
+//! query parameter and falls back to the default panel. Errors are JSON
+//! objects with an `error` message and a matching status code. The OpenAPI
+//! description is served at `/api/v1/openapi.json`.
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{
+        rejection::JsonRejection, FromRequest, FromRequestParts, Path, Query, Request, State,
+    },
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use ht32_panel_hw::Orientation;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use super::WebState;
+use crate::carousel::PlaylistEntry;
+use crate::dbus::DaemonSignals;
+use crate::faces::{self, ComplicationOptionType};
+use crate::notifications::{self, Notification, Severity};
+use crate::state::{AppState, PanelState};
+
+/// OpenAPI description of the API.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "HT32 Panel API",
+        description = "Control the panels driven by ht32paneld."
+    ),
+    paths(
+        status,
+        panels,
+        faces_list,
+        face_get,
+        face_set,
+        themes_list,
+        theme_get,
+        theme_set,
+        orientation_get,
+        orientation_set,
+        refresh_interval_get,
+        refresh_interval_set,
+        brightness_get,
+        brightness_set,
+        display_off_get,
+        display_off_set,
+        led_get,
+        led_set,
+        led_off,
+        complications_get,
+        complication_set,
+        complication_option_set,
+        playlist_get,
+        playlist_set,
+        carousel_get,
+        carousel_start,
+        carousel_stop,
+        carousel_next,
+        carousel_previous,
+        schedules,
+        notifications_list,
+        notification_show,
+        custom_values_list,
+        custom_value_set,
+        network_interfaces,
+        temperature_sensors,
+        screenshot,
+    )
+)]
+pub struct ApiDoc;
+
+/// Creates the router served under `/api/v1`.
+pub fn router() -> Router<WebState> {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/status", get(status))
+        .route("/panels", get(panels))
+        .route("/faces", get(faces_list))
+        .route("/face", get(face_get).put(face_set))
+        .route("/themes", get(themes_list))
+        .route("/theme", get(theme_get).put(theme_set))
+        .route("/orientation", get(orientation_get).put(orientation_set))
+        .route(
+            "/refresh-interval",
+            get(refresh_interval_get).put(refresh_interval_set),
+        )
+        .route("/brightness", get(brightness_get).put(brightness_set))
+        .route("/display-off", get(display_off_get).put(display_off_set))
+        .route("/led", get(led_get).put(led_set))
+        .route("/led/off", post(led_off))
+        .route("/complications", get(complications_get))
+        .route("/complications/:id", put(complication_set))
+        .route(
+            "/complications/:id/options/:option",
+            put(complication_option_set),
+        )
+        .route("/playlist", get(playlist_get).put(playlist_set))
+        .route("/carousel", get(carousel_get))
+        .route("/carousel/start", post(carousel_start))
+        .route("/carousel/stop", post(carousel_stop))
+        .route("/carousel/next", post(carousel_next))
+        .route("/carousel/previous", post(carousel_previous))
+        .route("/schedules", get(schedules))
+        .route(
+            "/notifications",
+            get(notifications_list).post(notification_show),
+        )
+        .route("/custom-values", get(custom_values_list))
+        .route("/custom-values/:key", put(custom_value_set))
+        .route("/network-interfaces", get(network_interfaces))
+        .route("/temperature-sensors", get(temperature_sensors))
+        .route("/screenshot", get(screenshot))
+        .fallback(|| async { Error::not_found("No such API route") })
+}
+
+/// Error body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    /// What went wrong
+    error: String,
+}
+
+/// An error returned as a JSON body with its status code.
+#[derive(Debug)]
+struct Error(StatusCode, String);
+
+impl Error {
+    fn bad_request(e: impl Display) -> Self {
+        Self(StatusCode::BAD_REQUEST, e.to_string())
+    }
+
+    fn not_found(e: impl Display) -> Self {
+        Self(StatusCode::NOT_FOUND, e.to_string())
+    }
+
+    fn internal(e: impl Display) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, Error>;
+
+/// Query parameters selecting a panel.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PanelParams {
+    /// Panel id; the default panel if unset
+    panel: Option<String>,
+}
+
+/// The panel a request refers to.
+struct Panel(Arc<PanelState>);
+
+#[async_trait]
+impl FromRequestParts<WebState> for Panel {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &WebState) -> Result<Self, Error> {
+        let Query(params) =
+            Query::<PanelParams>::try_from_uri(&parts.uri).map_err(Error::bad_request)?;
+        match params.panel {
+            None => Ok(Self(state.app.default_panel().clone())),
+            Some(id) => state
+                .app
+                .panel(&id)
+                .map(|panel| Self(panel.clone()))
+                .ok_or_else(|| Error::not_found(format!("Unknown panel: {}", id))),
+        }
+    }
+}
+
+/// A JSON request body, rejected with a JSON error.
+struct Body<T>(T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Body<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Error> {
+        Json::<T>::from_request(req, state)
+            .await
+            .map(|Json(value)| Self(value))
+            .map_err(|e: JsonRejection| Error(e.status(), e.body_text()))
+    }
+}
+
+/// State of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Status {
+    /// Panel id
+    panel: String,
+    /// Whether the LCD is connected
+    connected: bool,
+    /// Face id
+    face: String,
+    /// Color theme id
+    theme: String,
+    /// Display orientation
+    orientation: String,
+    /// Refresh interval in milliseconds
+    refresh_interval_ms: u32,
+    /// Brightness in percent
+    brightness: u8,
+    /// Whether the display is switched off
+    display_off: bool,
+    /// Whether the panel is blanked while the session is locked or idle
+    blanked: bool,
+    /// Whether the face carousel is running
+    carousel_running: bool,
+    /// Active schedule rule
+    active_schedule: Option<String>,
+    /// LED settings
+    led: Led,
+}
+
+/// A face.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FaceEntry {
+    id: String,
+    display_name: String,
+}
+
+/// A color theme.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ThemeEntry {
+    id: String,
+    display_name: String,
+    /// Whether this is a built-in theme rather than a user theme
+    builtin: bool,
+}
+
+/// The face of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Face {
+    /// Face id
+    face: String,
+}
+
+/// The color theme of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Theme {
+    /// Theme id
+    theme: String,
+}
+
+/// The orientation of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrientationBody {
+    /// `landscape`, `portrait`, `landscape-upside-down` or `portrait-upside-down`
+    orientation: String,
+}
+
+/// The refresh interval of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RefreshInterval {
+    /// Milliseconds between frames (500-10000)
+    interval_ms: u32,
+}
+
+/// The brightness of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Brightness {
+    /// Brightness in percent (0-100)
+    percent: u8,
+}
+
+/// Whether the display of a panel is switched off.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DisplayOff {
+    /// Whether the panel shows black frames
+    off: bool,
+}
+
+/// LED settings.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Led {
+    /// Theme: 1 rainbow, 2 breathing, 3 colors, 4 off, 5 auto
+    theme: u8,
+    /// Intensity (1-5)
+    intensity: u8,
+    /// Speed (1-5)
+    speed: u8,
+}
+
+/// A complication of the current face.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ComplicationState {
+    id: String,
+    name: String,
+    description: String,
+    enabled: bool,
+    options: Vec<OptionState>,
+}
+
+/// Kind of a complication option.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionKind {
+    Choice,
+    Boolean,
+    Range,
+}
+
+/// An option of a complication.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OptionState {
+    id: String,
+    name: String,
+    description: String,
+    /// Current value
+    value: String,
+    #[serde(rename = "type")]
+    kind: OptionKind,
+    /// Allowed values of choice and boolean options
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    choices: Vec<Choice>,
+    /// Bounds and step of range options
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    range: Option<Range>,
+}
+
+/// A value of a choice option.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Choice {
+    value: String,
+    label: String,
+}
+
+/// Bounds and step of a range option.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Range {
+    min: f32,
+    max: f32,
+    step: f32,
+}
+
+/// Whether a complication is enabled.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Enabled {
+    enabled: bool,
+}
+
+/// Value of a complication option.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OptionValue {
+    value: String,
+}
+
+/// A face of the carousel playlist.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistItem {
+    /// Face id
+    face: String,
+    /// Color theme to switch to; the current theme is kept if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    theme: Option<String>,
+    /// Seconds the face is shown
+    duration: u32,
+}
+
+/// State of the face carousel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Carousel {
+    /// Whether the carousel is rotating
+    running: bool,
+    /// Index of the current playlist entry
+    index: usize,
+}
+
+/// A schedule rule of a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScheduleEntry {
+    /// Rule name
+    name: String,
+    /// Start time ("HH:MM")
+    start: String,
+    /// End time ("HH:MM"); the rule lasts until the next one starts if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    /// Days the rule starts on; every day if empty
+    days: Vec<String>,
+}
+
+/// A notification to show.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewNotification {
+    /// Message text
+    text: String,
+    /// `info`, `warning` or `critical`; `info` if unset
+    #[serde(default)]
+    severity: Option<String>,
+    /// Seconds the notification stays on screen; 5 if unset
+    #[serde(default)]
+    seconds: Option<u32>,
+}
+
+/// A notification pushed to a panel.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationEntry {
+    /// Id, unique per panel
+    id: u32,
+    /// When the notification was pushed, in RFC 3339 format
+    time: String,
+    /// `info`, `warning` or `critical`
+    severity: String,
+    /// Message text
+    text: String,
+    /// Seconds the notification stays on screen
+    seconds: u32,
+}
+
+/// A custom value shown by faces bound to `custom:<key>`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomValueEntry {
+    key: String,
+    value: f64,
+    /// Unit shown after the value; may be empty
+    unit: String,
+}
+
+/// A new custom value.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomValueBody {
+    value: f64,
+    /// Unit shown after the value; none if unset
+    #[serde(default)]
+    unit: String,
+}
+
+fn led_settings(panel: &PanelState) -> Led {
+    let (theme, intensity, speed) = panel.led_settings();
+    Led {
+        theme,
+        intensity,
+        speed,
+    }
+}
+
+fn playlist_items(panel: &PanelState) -> Vec<PlaylistItem> {
+    panel
+        .playlist()
+        .into_iter()
+        .map(|e| PlaylistItem {
+            face: e.face,
+            theme: e.theme,
+            duration: e.duration,
+        })
+        .collect()
+}
+
+fn carousel_state(panel: &PanelState) -> Carousel {
+    Carousel {
+        running: panel.is_carousel_running(),
+        index: panel.carousel_index(),
+    }
+}
+
+fn notification_entry(notification: Notification) -> NotificationEntry {
+    NotificationEntry {
+        id: notification.id,
+        time: notification
+            .time
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        severity: notification.severity.to_string(),
+        text: notification.text,
+        seconds: notification.seconds,
+    }
+}
+
+/// Returns the complications of the panel's current face.
+fn complication_states(app: &AppState, panel: &PanelState) -> Vec<ComplicationState> {
+    let enabled = panel.enabled_complications();
+    panel
+        .available_complications()
+        .into_iter()
+        .map(|c| {
+            let options = c
+                .options
+                .iter()
+                .map(|opt| {
+                    let value = panel
+                        .get_complication_option(&c.id, &opt.id)
+                        .unwrap_or_else(|| opt.default_value.clone());
+                    let (kind, choices, range) = match &opt.option_type {
+                        ComplicationOptionType::Choice(list) => (
+                            OptionKind::Choice,
+                            app.complication_choices(&c.id, &opt.id, list)
+                                .into_iter()
+                                .map(|ch| Choice {
+                                    value: ch.value,
+                                    label: ch.label,
+                                })
+                                .collect(),
+                            None,
+                        ),
+                        ComplicationOptionType::Boolean => (
+                            OptionKind::Boolean,
+                            vec![
+                                Choice {
+                                    value: "true".to_string(),
+                                    label: "Yes".to_string(),
+                                },
+                                Choice {
+                                    value: "false".to_string(),
+                                    label: "No".to_string(),
+                                },
+                            ],
+                            None,
+                        ),
+                        ComplicationOptionType::Range { min, max, step } => (
+                            OptionKind::Range,
+                            Vec::new(),
+                            Some(Range {
+                                min: *min,
+                                max: *max,
+                                step: *step,
+                            }),
+                        ),
+                    };
+                    OptionState {
+                        id: opt.id.clone(),
+                        name: opt.name.clone(),
+                        description: opt.description.clone(),
+                        value,
+                        kind,
+                        choices,
+                        range,
+                    }
+                })
+                .collect();
+            ComplicationState {
+                enabled: enabled.contains(&c.id),
+                id: c.id,
+                name: c.name,
+                description: c.description,
+                options,
+            }
+        })
+        .collect()
+}
+
+/// Returns the state of one complication, or 404 if the face has none by
+/// that id.
+fn complication_state(
+    app: &AppState,
+    panel: &PanelState,
+    id: &str,
+) -> Result<ComplicationState, Error> {
+    complication_states(app, panel)
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| {
+            Error::not_found(format!(
+                "Face '{}' has no complication '{}'",
+                panel.face_name(),
+                id
+            ))
+        })
+}
+
+/// GET /api/v1/openapi.json - OpenAPI description
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Returns the state of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Status),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn status(Panel(panel): Panel) -> Json<Status> {
+    Json(Status {
+        panel: panel.id().to_string(),
+        connected: panel.is_lcd_connected(),
+        face: panel.face_name(),
+        theme: panel.theme_name(),
+        orientation: panel.orientation().to_string(),
+        refresh_interval_ms: panel.refresh_interval_ms(),
+        brightness: panel.brightness(),
+        display_off: panel.is_display_off(),
+        blanked: panel.is_blanked(),
+        carousel_running: panel.is_carousel_running(),
+        active_schedule: panel.active_schedule(),
+        led: led_settings(&panel),
+    })
+}
+
+/// Lists the ids of all panels, default first.
+#[utoipa::path(get, path = "/api/v1/panels", responses((status = 200, body = Vec<String>)))]
+async fn panels(State(state): State<WebState>) -> Json<Vec<String>> {
+    Json(state.app.panel_ids())
+}
+
+/// Lists the available faces.
+#[utoipa::path(get, path = "/api/v1/faces", responses((status = 200, body = Vec<FaceEntry>)))]
+async fn faces_list() -> Json<Vec<FaceEntry>> {
+    Json(
+        faces::available_faces()
+            .into_iter()
+            .map(|f| FaceEntry {
+                id: f.id.to_string(),
+                display_name: f.display_name.to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// Returns the face of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/face",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Face),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn face_get(Panel(panel): Panel) -> Json<Face> {
+    Json(Face {
+        face: panel.face_name(),
+    })
+}
+
+/// Sets the face of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/face",
+    params(PanelParams),
+    request_body = Face,
+    responses(
+        (status = 200, body = Face),
+        (status = 400, description = "Unknown face", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn face_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<Face>,
+) -> ApiResult<Face> {
+    panel.set_face(&body.face).map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(Face {
+        face: panel.face_name(),
+    }))
+}
+
+/// Lists the available color themes.
+#[utoipa::path(get, path = "/api/v1/themes", responses((status = 200, body = Vec<ThemeEntry>)))]
+async fn themes_list() -> Json<Vec<ThemeEntry>> {
+    Json(
+        faces::available_themes()
+            .into_iter()
+            .map(|t| ThemeEntry {
+                id: t.id,
+                display_name: t.display_name,
+                builtin: t.builtin,
+            })
+            .collect(),
+    )
+}
+
+/// Returns the color theme of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/theme",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Theme),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn theme_get(Panel(panel): Panel) -> Json<Theme> {
+    Json(Theme {
+        theme: panel.theme_name(),
+    })
+}
+
+/// Sets the color theme of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/theme",
+    params(PanelParams),
+    request_body = Theme,
+    responses(
+        (status = 200, body = Theme),
+        (status = 400, description = "Unknown theme", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn theme_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<Theme>,
+) -> ApiResult<Theme> {
+    panel.set_theme(&body.theme).map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(Theme {
+        theme: panel.theme_name(),
+    }))
+}
+
+/// Returns the orientation of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/orientation",
+    params(PanelParams),
+    responses(
+        (status = 200, body = OrientationBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn orientation_get(Panel(panel): Panel) -> Json<OrientationBody> {
+    Json(OrientationBody {
+        orientation: panel.orientation().to_string(),
+    })
+}
+
+/// Sets the orientation of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/orientation",
+    params(PanelParams),
+    request_body = OrientationBody,
+    responses(
+        (status = 200, body = OrientationBody),
+        (status = 400, description = "Invalid orientation", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody),
+        (status = 500, description = "The LCD rejected the orientation", body = ErrorBody)
+    )
+)]
+async fn orientation_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<OrientationBody>,
+) -> ApiResult<OrientationBody> {
+    let orientation: Orientation = body.orientation.parse().map_err(Error::bad_request)?;
+    panel
+        .set_orientation(orientation)
+        .map_err(Error::internal)?;
+    let _ = state.signal_tx.send(DaemonSignals::OrientationChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(OrientationBody {
+        orientation: panel.orientation().to_string(),
+    }))
+}
+
+/// Returns the refresh interval of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/refresh-interval",
+    params(PanelParams),
+    responses(
+        (status = 200, body = RefreshInterval),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn refresh_interval_get(Panel(panel): Panel) -> Json<RefreshInterval> {
+    Json(RefreshInterval {
+        interval_ms: panel.refresh_interval_ms(),
+    })
+}
+
+/// Sets the refresh interval of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/refresh-interval",
+    params(PanelParams),
+    request_body = RefreshInterval,
+    responses(
+        (status = 200, body = RefreshInterval),
+        (status = 400, description = "Interval out of range", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn refresh_interval_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<RefreshInterval>,
+) -> ApiResult<RefreshInterval> {
+    panel
+        .set_refresh_interval(body.interval_ms)
+        .map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(RefreshInterval {
+        interval_ms: panel.refresh_interval_ms(),
+    }))
+}
+
+/// Returns the brightness of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/brightness",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Brightness),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn brightness_get(Panel(panel): Panel) -> Json<Brightness> {
+    Json(Brightness {
+        percent: panel.brightness(),
+    })
+}
+
+/// Sets the brightness of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/brightness",
+    params(PanelParams),
+    request_body = Brightness,
+    responses(
+        (status = 200, body = Brightness),
+        (status = 400, description = "Brightness out of range", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn brightness_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<Brightness>,
+) -> ApiResult<Brightness> {
+    panel
+        .set_brightness(body.percent)
+        .map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(Brightness {
+        percent: panel.brightness(),
+    }))
+}
+
+/// Returns whether the display of a panel is switched off.
+#[utoipa::path(
+    get,
+    path = "/api/v1/display-off",
+    params(PanelParams),
+    responses(
+        (status = 200, body = DisplayOff),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn display_off_get(Panel(panel): Panel) -> Json<DisplayOff> {
+    Json(DisplayOff {
+        off: panel.is_display_off(),
+    })
+}
+
+/// Switches the display of a panel off or back on.
+#[utoipa::path(
+    put,
+    path = "/api/v1/display-off",
+    params(PanelParams),
+    request_body = DisplayOff,
+    responses(
+        (status = 200, body = DisplayOff),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn display_off_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<DisplayOff>,
+) -> Json<DisplayOff> {
+    panel.set_display_off(body.off);
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Json(DisplayOff {
+        off: panel.is_display_off(),
+    })
+}
+
+/// Returns the LED settings of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/led",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Led),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn led_get(Panel(panel): Panel) -> Json<Led> {
+    Json(led_settings(&panel))
+}
+
+/// Sets the LED settings of a panel.
+#[utoipa::path(
+    put,
+    path = "/api/v1/led",
+    params(PanelParams),
+    request_body = Led,
+    responses(
+        (status = 200, body = Led),
+        (status = 400, description = "Value out of range", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody),
+        (status = 500, description = "The LED controller failed", body = ErrorBody)
+    )
+)]
+async fn led_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<Led>,
+) -> ApiResult<Led> {
+    for (name, value) in [
+        ("Theme", body.theme),
+        ("Intensity", body.intensity),
+        ("Speed", body.speed),
+    ] {
+        if !(1..=5).contains(&value) {
+            return Err(Error::bad_request(format!("{} must be 1-5", name)));
+        }
+    }
+    panel
+        .set_led(body.theme, body.intensity, body.speed)
+        .await
+        .map_err(Error::internal)?;
+    let _ = state.signal_tx.send(DaemonSignals::LedChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(led_settings(&panel)))
+}
+
+/// Turns off the LEDs of a panel.
+#[utoipa::path(
+    post,
+    path = "/api/v1/led/off",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Led),
+        (status = 404, description = "Unknown panel", body = ErrorBody),
+        (status = 500, description = "The LED controller failed", body = ErrorBody)
+    )
+)]
+async fn led_off(State(state): State<WebState>, Panel(panel): Panel) -> ApiResult<Led> {
+    panel.led_off().await.map_err(Error::internal)?;
+    let _ = state.signal_tx.send(DaemonSignals::LedChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(led_settings(&panel)))
+}
+
+/// Lists the complications of the panel's current face.
+#[utoipa::path(
+    get,
+    path = "/api/v1/complications",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Vec<ComplicationState>),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn complications_get(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+) -> Json<Vec<ComplicationState>> {
+    Json(complication_states(&state.app, &panel))
+}
+
+/// Enables or disables a complication of the current face.
+#[utoipa::path(
+    put,
+    path = "/api/v1/complications/{id}",
+    params(("id" = String, Path, description = "Complication id"), PanelParams),
+    request_body = Enabled,
+    responses(
+        (status = 200, body = ComplicationState),
+        (status = 404, description = "Unknown panel or complication", body = ErrorBody)
+    )
+)]
+async fn complication_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Path(id): Path<String>,
+    Body(body): Body<Enabled>,
+) -> ApiResult<ComplicationState> {
+    complication_state(&state.app, &panel, &id)?;
+    panel
+        .set_complication_enabled(&id, body.enabled)
+        .map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    complication_state(&state.app, &panel, &id).map(Json)
+}
+
+/// Sets an option of a complication of the current face.
+#[utoipa::path(
+    put,
+    path = "/api/v1/complications/{id}/options/{option}",
+    params(
+        ("id" = String, Path, description = "Complication id"),
+        ("option" = String, Path, description = "Option id"),
+        PanelParams
+    ),
+    request_body = OptionValue,
+    responses(
+        (status = 200, body = ComplicationState),
+        (status = 400, description = "Invalid value", body = ErrorBody),
+        (status = 404, description = "Unknown panel, complication or option", body = ErrorBody)
+    )
+)]
+async fn complication_option_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Path((id, option)): Path<(String, String)>,
+    Body(body): Body<OptionValue>,
+) -> ApiResult<ComplicationState> {
+    let complication = complication_state(&state.app, &panel, &id)?;
+    if !complication.options.iter().any(|o| o.id == option) {
+        return Err(Error::not_found(format!(
+            "Complication '{}' has no option '{}'",
+            id, option
+        )));
+    }
+    panel
+        .set_complication_option(&id, &option, &body.value)
+        .map_err(Error::bad_request)?;
+    let _ = state
+        .signal_tx
+        .send(DaemonSignals::ComplicationOptionChanged {
+            panel: panel.id().to_string(),
+            complication: id.clone(),
+            option,
+            value: body.value,
+        });
+    complication_state(&state.app, &panel, &id).map(Json)
+}
+
+/// Returns the carousel playlist of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/playlist",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Vec<PlaylistItem>),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn playlist_get(Panel(panel): Panel) -> Json<Vec<PlaylistItem>> {
+    Json(playlist_items(&panel))
+}
+
+/// Replaces the carousel playlist of a panel. A running carousel restarts
+/// from the first entry; an empty playlist stops it.
+#[utoipa::path(
+    put,
+    path = "/api/v1/playlist",
+    params(PanelParams),
+    request_body = Vec<PlaylistItem>,
+    responses(
+        (status = 200, body = Vec<PlaylistItem>),
+        (status = 400, description = "Unknown face or theme", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn playlist_set(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<Vec<PlaylistItem>>,
+) -> ApiResult<Vec<PlaylistItem>> {
+    let entries = body
+        .into_iter()
+        .map(|item| PlaylistEntry {
+            face: item.face,
+            theme: item.theme,
+            duration: item.duration,
+        })
+        .collect();
+    panel.set_playlist(entries).map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(playlist_items(&panel)))
+}
+
+/// Returns the carousel state of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/carousel",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Carousel),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn carousel_get(Panel(panel): Panel) -> Json<Carousel> {
+    Json(carousel_state(&panel))
+}
+
+/// Starts rotating through the playlist.
+#[utoipa::path(
+    post,
+    path = "/api/v1/carousel/start",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Carousel),
+        (status = 400, description = "The playlist is empty", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn carousel_start(State(state): State<WebState>, Panel(panel): Panel) -> ApiResult<Carousel> {
+    panel.start_carousel().map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(carousel_state(&panel)))
+}
+
+/// Stops the carousel on the current face.
+#[utoipa::path(
+    post,
+    path = "/api/v1/carousel/stop",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Carousel),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn carousel_stop(State(state): State<WebState>, Panel(panel): Panel) -> Json<Carousel> {
+    if panel.stop_carousel() {
+        let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+            panel: panel.id().to_string(),
+        });
+    }
+    Json(carousel_state(&panel))
+}
+
+/// Shows the next face of the playlist.
+#[utoipa::path(
+    post,
+    path = "/api/v1/carousel/next",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Carousel),
+        (status = 400, description = "The playlist is empty", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn carousel_next(State(state): State<WebState>, Panel(panel): Panel) -> ApiResult<Carousel> {
+    panel.next_face().map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(carousel_state(&panel)))
+}
+
+/// Shows the previous face of the playlist.
+#[utoipa::path(
+    post,
+    path = "/api/v1/carousel/previous",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Carousel),
+        (status = 400, description = "The playlist is empty", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn carousel_previous(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+) -> ApiResult<Carousel> {
+    panel.previous_face().map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    Ok(Json(carousel_state(&panel)))
+}
+
+/// Lists the schedule rules of a panel.
+#[utoipa::path(
+    get,
+    path = "/api/v1/schedules",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Vec<ScheduleEntry>),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn schedules(Panel(panel): Panel) -> Json<Vec<ScheduleEntry>> {
+    Json(
+        panel
+            .schedule_rules()
+            .into_iter()
+            .map(|rule| ScheduleEntry {
+                name: rule.name,
+                start: rule.start,
+                end: rule.end,
+                days: rule.days,
+            })
+            .collect(),
+    )
+}
+
+/// Lists the notifications pushed to a panel, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    params(PanelParams),
+    responses(
+        (status = 200, body = Vec<NotificationEntry>),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn notifications_list(Panel(panel): Panel) -> Json<Vec<NotificationEntry>> {
+    Json(
+        panel
+            .notification_history()
+            .into_iter()
+            .map(notification_entry)
+            .collect(),
+    )
+}
+
+/// Draws a notification over the face of a panel.
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications",
+    params(PanelParams),
+    request_body = NewNotification,
+    responses(
+        (status = 200, body = NotificationEntry),
+        (status = 400, description = "Invalid text or severity", body = ErrorBody),
+        (status = 404, description = "Unknown panel", body = ErrorBody)
+    )
+)]
+async fn notification_show(
+    State(state): State<WebState>,
+    Panel(panel): Panel,
+    Body(body): Body<NewNotification>,
+) -> ApiResult<NotificationEntry> {
+    let severity: Severity = match body.severity {
+        Some(severity) => severity.parse().map_err(Error::bad_request)?,
+        None => Severity::Info,
+    };
+    let notification = panel
+        .show_notification(
+            &body.text,
+            severity,
+            body.seconds.unwrap_or(notifications::DEFAULT_SECONDS),
+        )
+        .map_err(Error::bad_request)?;
+    let _ = state.signal_tx.send(DaemonSignals::NotificationPosted {
+        panel: panel.id().to_string(),
+        id: notification.id,
+        text: notification.text.clone(),
+        severity: severity.to_string(),
+    });
+    Ok(Json(notification_entry(notification)))
+}
+
+/// Lists the custom values, sorted by key.
+#[utoipa::path(
+    get,
+    path = "/api/v1/custom-values",
+    responses((status = 200, body = Vec<CustomValueEntry>))
+)]
+async fn custom_values_list(State(state): State<WebState>) -> Json<Vec<CustomValueEntry>> {
+    Json(
+        state
+            .app
+            .custom_values()
+            .snapshot()
+            .into_iter()
+            .map(|(key, v)| CustomValueEntry {
+                key,
+                value: v.value,
+                unit: v.unit,
+            })
+            .collect(),
+    )
+}
+
+/// Sets a custom value; the value is shared by all panels.
+#[utoipa::path(
+    put,
+    path = "/api/v1/custom-values/{key}",
+    params(("key" = String, Path, description = "Custom value key")),
+    request_body = CustomValueBody,
+    responses(
+        (status = 200, body = CustomValueEntry),
+        (status = 400, description = "Invalid key, value or unit", body = ErrorBody)
+    )
+)]
+async fn custom_value_set(
+    State(state): State<WebState>,
+    Path(key): Path<String>,
+    Body(body): Body<CustomValueBody>,
+) -> ApiResult<CustomValueEntry> {
+    state
+        .app
+        .custom_values()
+        .set(&key, body.value, &body.unit)
+        .map_err(Error::bad_request)?;
+    Ok(Json(CustomValueEntry {
+        key,
+        value: body.value,
+        unit: body.unit,
+    }))
+}
+
+/// Lists the network interfaces the network complication can show.
+#[utoipa::path(
+    get,
+    path = "/api/v1/network-interfaces",
+    responses((status = 200, body = Vec<String>))
+)]
+async fn network_interfaces(State(state): State<WebState>) -> Json<Vec<String>> {
+    Json(state.app.list_network_interfaces())
+}
+
+/// Lists the temperature sensors the CPU temperature complication can show.
+#[utoipa::path(
+    get,
+    path = "/api/v1/temperature-sensors",
+    responses((status = 200, body = Vec<String>))
+)]
+async fn temperature_sensors(State(state): State<WebState>) -> Json<Vec<String>> {
+    Json(state.app.list_temperature_sensors())
+}
+
+/// Returns the current frame of a panel as PNG.
+#[utoipa::path(
+    get,
+    path = "/api/v1/screenshot",
+    params(PanelParams),
+    responses(
+        (status = 200, description = "PNG image", content_type = "image/png", body = Vec<u8>),
+        (status = 404, description = "Unknown panel", body = ErrorBody),
+        (status = 500, description = "Encoding failed", body = ErrorBody)
+    )
+)]
+async fn screenshot(Panel(panel): Panel) -> Result<Response, Error> {
+    let png = panel.get_screen_png().map_err(Error::internal)?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body as HttpBody};
+    use axum::http::Request as HttpRequest;
+    use serde_json::{json, Value};
+    use tokio::sync::broadcast;
+    use tower::ServiceExt;
+
+    use super::super::create_router;
+    use crate::test_support::app_state;
+
+    /// Sends a request to the router and returns the status and JSON body.
+    async fn call(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = HttpRequest::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(HttpBody::from(body.to_string())),
+            None => request.body(HttpBody::empty()),
+        }
+        .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_and_set_face() {
+        let (app, _) = app_state("api-face", |_| {});
+        let (signal_tx, mut signals) = broadcast::channel(16);
+        let router = create_router(app.clone(), signal_tx);
+
+        let (status, body) = call(
+            &router,
+            "PUT",
+            "/api/v1/face",
+            Some(json!({"face": "clock"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({"face": "clock"}));
+        assert_eq!(app.default_panel().face_name(), "clock");
+        assert!(matches!(
+            signals.try_recv().unwrap(),
+            DaemonSignals::DisplaySettingsChanged { .. }
+        ));
+
+        let (status, body) = call(&router, "GET", "/api/v1/status?panel=default", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["face"], "clock");
+        assert_eq!(body["panel"], "default");
+        assert!(body["led"]["intensity"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_json_with_status() {
+        let (app, _) = app_state("api-errors", |_| {});
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = create_router(app, signal_tx);
+
+        let cases = [
+            (
+                "PUT",
+                "/api/v1/face",
+                Some(json!({"face": "nope"})),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "PUT",
+                "/api/v1/orientation",
+                Some(json!({"orientation": "sideways"})),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "PUT",
+                "/api/v1/led",
+                Some(json!({"theme": 9, "intensity": 1, "speed": 1})),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "PUT",
+                "/api/v1/face",
+                Some(json!({"wrong": 1})),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                "GET",
+                "/api/v1/face?panel=missing",
+                None,
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                "PUT",
+                "/api/v1/complications/nope",
+                Some(json!({"enabled": true})),
+                StatusCode::NOT_FOUND,
+            ),
+            ("GET", "/api/v1/nothing", None, StatusCode::NOT_FOUND),
+        ];
+        for (method, uri, body, expected) in cases {
+            let (status, body) = call(&router, method, uri, body).await;
+            assert_eq!(status, expected, "{} {}", method, uri);
+            assert!(body["error"].is_string(), "{} {}: {}", method, uri, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complication_options() {
+        let (app, _) = app_state("api-complications", |_| {});
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = create_router(app.clone(), signal_tx);
+        call(
+            &router,
+            "PUT",
+            "/api/v1/face",
+            Some(json!({"face": "professional"})),
+        )
+        .await;
+
+        let (status, body) = call(&router, "GET", "/api/v1/complications", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let time = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["id"] == "time")
+            .unwrap();
+        assert_eq!(time["options"][0]["type"], "choice");
+
+        let (status, body) = call(
+            &router,
+            "PUT",
+            "/api/v1/complications/time/options/format",
+            Some(json!({"value": "digital-12h"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["options"][0]["value"], "digital-12h");
+
+        let (status, _) = call(
+            &router,
+            "PUT",
+            "/api/v1/complications/time/options/nope",
+            Some(json!({"value": "x"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_display_carousel_and_notifications() {
+        let (app, _) = app_state("api-display", |_| {});
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = create_router(app.clone(), signal_tx);
+
+        let (status, body) = call(
+            &router,
+            "PUT",
+            "/api/v1/brightness",
+            Some(json!({"percent": 40})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({"percent": 40}));
+        let (status, _) = call(
+            &router,
+            "PUT",
+            "/api/v1/refresh-interval",
+            Some(json!({"interval_ms": 1})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = call(&router, "POST", "/api/v1/carousel/next", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let playlist = json!([
+            {"face": "clock", "duration": 10},
+            {"face": "professional", "theme": "nord", "duration": 20}
+        ]);
+        let (status, body) = call(&router, "PUT", "/api/v1/playlist", Some(playlist.clone())).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body, playlist);
+        let (status, body) = call(&router, "POST", "/api/v1/carousel/next", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["index"], 1);
+        assert_eq!(app.default_panel().face_name(), "professional");
+
+        let (status, body) = call(
+            &router,
+            "POST",
+            "/api/v1/notifications",
+            Some(json!({"text": "Backup done", "severity": "warning"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["seconds"], notifications::DEFAULT_SECONDS);
+        let (_, body) = call(&router, "GET", "/api/v1/notifications", None).await;
+        assert_eq!(body[0]["text"], "Backup done");
+        assert_eq!(body[0]["severity"], "warning");
+
+        let (status, body) = call(
+            &router,
+            "PUT",
+            "/api/v1/custom-values/fan",
+            Some(json!({"value": 1200.0, "unit": "RPM"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let (_, body) = call(&router, "GET", "/api/v1/custom-values", None).await;
+        assert_eq!(
+            body,
+            json!([{"key": "fan", "value": 1200.0, "unit": "RPM"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openapi_describes_routes() {
+        let (app, _) = app_state("api-openapi", |_| {});
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = create_router(app, signal_tx);
+
+        let (status, body) = call(&router, "GET", "/api/v1/openapi.json", None).await;
+        assert_eq!(status, StatusCode::OK);
+        for path in [
+            "/api/v1/face",
+            "/api/v1/led/off",
+            "/api/v1/complications/{id}/options/{option}",
+            "/api/v1/brightness",
+            "/api/v1/carousel/next",
+            "/api/v1/custom-values/{key}",
+        ] {
+            assert!(body["paths"][path].is_object(), "{}", path);
+        }
+        assert!(body["components"]["schemas"]["ErrorBody"].is_object());
+    }
+}
@@ -0,0 +1,725 @@
+//! Authentication, roles and CSRF protection.
+//!
+//! With `web.users` or `web.tokens` configured, every request must carry
+//! HTTP basic credentials or a bearer token. Read-only clients may only
+//! send safe (GET/HEAD) requests. Browsers send basic credentials by
+//! themselves, so changes authenticated that way must also carry the
+//! `X-CSRF-Token` header the index page hands to HTMX. Changes from other
+//! origins are refused whether or not authentication is enabled.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tracing::{debug, warn};
+
+use super::WebState;
+use crate::config::{WebConfig, WebRole, WebToken, WebUser};
+
+/// Header carrying the CSRF token on changes made from the web UI.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Failed password checks allowed per client and [`FAILURE_WINDOW`] before
+/// further unknown credentials from that client are refused without hashing.
+const MAX_FAILURES: u32 = 10;
+
+/// Period over which failed password checks are counted.
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Clients whose failed password checks are remembered at once.
+const MAX_CLIENTS: usize = 1024;
+
+/// SHA-256 digest of a secret.
+type Digest = [u8; 32];
+
+/// Credentials the server accepts.
+pub struct Auth {
+    users: Vec<WebUser>,
+    /// Tokens with the SHA-256 digest of their secret
+    tokens: Vec<(WebToken, Digest)>,
+    /// Hash checked for unknown user names, so they take as long to refuse
+    /// as wrong passwords
+    dummy_hash: Option<String>,
+    csrf_token: String,
+    /// Digests of the basic credentials already verified, with their role.
+    /// Only correct passwords get in, so this holds one entry per user.
+    verified: Mutex<HashMap<Digest, WebRole>>,
+    failures: Mutex<HashMap<Client, Failures>>,
+}
+
+/// Where a request came from: the client's address, or `None` for clients
+/// on a Unix socket, which all share one count of failed password checks.
+type Client = Option<IpAddr>;
+
+/// Failed password checks of one client in its current window.
+struct Failures {
+    since: Instant,
+    count: u32,
+}
+
+/// Why credentials were refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Refusal {
+    /// The credentials are wrong or malformed.
+    Invalid,
+    /// Too many password checks failed lately; try again later.
+    Throttled,
+}
+
+/// How a request authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scheme {
+    Basic,
+    Bearer,
+}
+
+impl Auth {
+    /// Creates the authenticator for the web config, with a fresh CSRF token.
+    pub fn new(config: &WebConfig) -> Self {
+        let mut token = [0u8; 32];
+        argon2::password_hash::rand_core::RngCore::fill_bytes(
+            &mut argon2::password_hash::rand_core::OsRng,
+            &mut token,
+        );
+        Self {
+            users: config.users.clone(),
+            // The config was validated, so every hash decodes
+            tokens: config
+                .tokens
+                .iter()
+                .filter_map(|t| Some((t.clone(), t.digest().ok()?)))
+                .collect(),
+            dummy_hash: config
+                .users
+                .first()
+                .and_then(|u| dummy_hash(&u.password_hash)),
+            csrf_token: token.iter().map(|b| format!("{:02x}", b)).collect(),
+            verified: Mutex::new(HashMap::new()),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether requests must authenticate.
+    fn enabled(&self) -> bool {
+        !self.users.is_empty() || !self.tokens.is_empty()
+    }
+
+    /// Returns the token the web UI sends with its changes.
+    pub fn csrf_token(&self) -> &str {
+        &self.csrf_token
+    }
+
+    /// Checks an Authorization header sent by `client`, returning the
+    /// client's role.
+    fn verify(&self, authorization: &str, client: Client) -> Result<WebRole, Refusal> {
+        match scheme(authorization).ok_or(Refusal::Invalid)? {
+            (Scheme::Bearer, token) => self.verify_token(token).ok_or(Refusal::Invalid),
+            (Scheme::Basic, encoded) => {
+                let decoded = STANDARD.decode(encoded).map_err(|_| Refusal::Invalid)?;
+                let decoded = String::from_utf8(decoded).map_err(|_| Refusal::Invalid)?;
+                self.verify_password(&decoded, client)
+            }
+        }
+    }
+
+    /// Checks a bearer token against every configured token.
+    fn verify_token(&self, token: &str) -> Option<WebRole> {
+        let digest = sha256(token.as_bytes());
+        // No early exit, so the time taken doesn't tell which token matched
+        self.tokens
+            .iter()
+            .fold(None, |found, (t, hash)| {
+                if constant_time_eq(&digest, hash) {
+                    Some(t)
+                } else {
+                    found
+                }
+            })
+            .map(|t| {
+                debug!("Web: token '{}' authenticated", t.name);
+                t.role
+            })
+    }
+
+    /// Checks decoded basic credentials (`name:password`).
+    fn verify_password(&self, credentials: &str, client: Client) -> Result<WebRole, Refusal> {
+        let digest = sha256(credentials.as_bytes());
+        if let Some(role) = self.verified.lock().unwrap().get(&digest) {
+            return Ok(*role);
+        }
+        let (name, password) = credentials.split_once(':').ok_or(Refusal::Invalid)?;
+        self.check_failures(client)?;
+
+        let user = self.users.iter().find(|u| u.name == name);
+        // Unknown names are checked against a dummy hash, so the time taken
+        // doesn't tell which names exist
+        let hash = match (user, &self.dummy_hash) {
+            (Some(user), _) => &user.password_hash,
+            (None, Some(dummy)) => dummy,
+            (None, None) => return Err(Refusal::Invalid),
+        };
+        match user.filter(|_| matches_hash(password.as_bytes(), hash)) {
+            Some(user) => {
+                self.verified.lock().unwrap().insert(digest, user.role);
+                Ok(user.role)
+            }
+            None => {
+                self.record_failure(client);
+                Err(Refusal::Invalid)
+            }
+        }
+    }
+
+    /// Refuses to check another password for a client once too many of its
+    /// checks failed in the current window.
+    fn check_failures(&self, client: Client) -> Result<(), Refusal> {
+        match self.failures.lock().unwrap().get(&client) {
+            Some(f) if f.since.elapsed() < FAILURE_WINDOW && f.count >= MAX_FAILURES => {
+                Err(Refusal::Throttled)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a failed password check against a client.
+    fn record_failure(&self, client: Client) {
+        let mut failures = self.failures.lock().unwrap();
+        if !failures.contains_key(&client) && failures.len() >= MAX_CLIENTS {
+            failures.retain(|_, f| f.since.elapsed() < FAILURE_WINDOW);
+            // Still full: forget the client whose window started first
+            if failures.len() >= MAX_CLIENTS {
+                let oldest = failures
+                    .iter()
+                    .min_by_key(|(_, f)| f.since)
+                    .map(|(c, _)| *c);
+                if let Some(oldest) = oldest {
+                    failures.remove(&oldest);
+                }
+            }
+        }
+        let entry = failures.entry(client).or_insert(Failures {
+            since: Instant::now(),
+            count: 0,
+        });
+        if entry.since.elapsed() >= FAILURE_WINDOW {
+            entry.since = Instant::now();
+            entry.count = 0;
+        }
+        entry.count += 1;
+    }
+
+    /// Returns the challenge sent with 401 responses.
+    fn challenge(&self) -> &'static str {
+        if self.users.is_empty() {
+            "Bearer"
+        } else {
+            "Basic realm=\"ht32-panel\", charset=\"UTF-8\""
+        }
+    }
+}
+
+/// Returns the client a peer address counts as. IPv6 clients usually hold
+/// a whole /64, so they are counted by that prefix.
+fn client(peer: Option<SocketAddr>) -> Client {
+    Some(match peer?.ip().to_canonical() {
+        IpAddr::V6(ip) => IpAddr::V6((u128::from(ip) & !(u64::MAX as u128)).into()),
+        ip => ip,
+    })
+}
+
+/// Splits an Authorization header into its scheme and credentials.
+fn scheme(authorization: &str) -> Option<(Scheme, &str)> {
+    let (scheme, credentials) = authorization.split_once(' ')?;
+    let scheme = if scheme.eq_ignore_ascii_case("basic") {
+        Scheme::Basic
+    } else if scheme.eq_ignore_ascii_case("bearer") {
+        Scheme::Bearer
+    } else {
+        return None;
+    };
+    Some((scheme, credentials.trim()))
+}
+
+/// Checks a secret against an Argon2 hash in PHC format.
+fn matches_hash(secret: &[u8], hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(secret, &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// Hashes a random password with the parameters of `like`, so checking it
+/// takes as long as checking a real password.
+fn dummy_hash(like: &str) -> Option<String> {
+    let like = PasswordHash::new(like).ok()?;
+    let argon2 = Argon2::new(
+        Algorithm::try_from(like.algorithm).ok()?,
+        Version::try_from(like.version?).ok()?,
+        Params::try_from(&like).ok()?,
+    );
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = argon2.hash_password(salt.as_str().as_bytes(), &salt).ok()?;
+    Some(hash.to_string())
+}
+
+fn sha256(data: &[u8]) -> Digest {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(ring::digest::digest(&ring::digest::SHA256, data).as_ref());
+    digest
+}
+
+/// Compares two byte strings in time independent of where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether a request was sent by a page of another site.
+fn is_cross_origin(headers: &HeaderMap) -> bool {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(site) = header("sec-fetch-site") {
+        return !matches!(site, "same-origin" | "none");
+    }
+    match (
+        header(header::ORIGIN.as_str()),
+        header(header::HOST.as_str()),
+    ) {
+        (Some(origin), Some(host)) => origin
+            .split_once("://")
+            .is_none_or(|(_, authority)| authority != host),
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Refuses a request, with a JSON body under `/api/`.
+fn deny(path: &str, status: StatusCode, message: &str) -> Response {
+    if path.starts_with("/api/") {
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    } else {
+        (status, message.to_string()).into_response()
+    }
+}
+
+/// Middleware enforcing authentication, roles and CSRF protection.
+pub async fn require_auth(State(state): State<WebState>, request: Request, next: Next) -> Response {
+    let auth = state.auth.clone();
+    let path = request.uri().path().to_string();
+    // Only TCP connections carry the peer address
+    let client = client(
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0),
+    );
+    let safe = matches!(*request.method(), Method::GET | Method::HEAD);
+
+    if !safe && is_cross_origin(request.headers()) {
+        warn!("Web: refused cross-origin {} {}", request.method(), path);
+        return deny(&path, StatusCode::FORBIDDEN, "Cross-origin request refused");
+    }
+    if !auth.enabled() {
+        return next.run(request).await;
+    }
+
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let verified = match authorization {
+        Some(authorization) => {
+            // Argon2 is slow on purpose; keep it off the async workers
+            let checker = Arc::clone(&auth);
+            let header = authorization.clone();
+            tokio::task::spawn_blocking(move || checker.verify(&header, client))
+                .await
+                .unwrap_or(Err(Refusal::Invalid))
+                .map(|role| (role, scheme(&authorization).map(|(s, _)| s)))
+        }
+        None => Err(Refusal::Invalid),
+    };
+    let (role, scheme) = match verified {
+        Ok(verified) => verified,
+        Err(Refusal::Throttled) => {
+            match client {
+                Some(ip) => warn!("Web: too many failed logins from {}, refusing {}", ip, path),
+                None => warn!(
+                    "Web: too many failed logins on the Unix socket, refusing {}",
+                    path
+                ),
+            }
+            let mut response = deny(
+                &path,
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many failed logins, try again later",
+            );
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                header::HeaderValue::from(FAILURE_WINDOW.as_secs()),
+            );
+            return response;
+        }
+        Err(Refusal::Invalid) => {
+            let mut response = deny(&path, StatusCode::UNAUTHORIZED, "Authentication required");
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                header::HeaderValue::from_static(auth.challenge()),
+            );
+            return response;
+        }
+    };
+
+    if !safe {
+        if role == WebRole::ReadOnly {
+            return deny(&path, StatusCode::FORBIDDEN, "Read-only access");
+        }
+        let csrf_ok = request
+            .headers()
+            .get(CSRF_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), auth.csrf_token().as_bytes()));
+        if scheme == Some(Scheme::Basic) && !csrf_ok {
+            return deny(
+                &path,
+                StatusCode::FORBIDDEN,
+                "Missing or invalid CSRF token",
+            );
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Router;
+    use tokio::sync::broadcast;
+    use tower::ServiceExt;
+
+    use crate::test_support::app_state;
+
+    /// Hashes a secret with parameters cheap enough for tests.
+    fn hash(secret: &str) -> String {
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(64, 1, 1, None).unwrap(),
+        );
+        argon2
+            .hash_password(secret.as_bytes(), &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string()
+    }
+
+    /// Hashes a token the way `token_hash` expects.
+    fn token_hash(token: &str) -> String {
+        sha256(token.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Creates a router with an admin user, an admin token and a read-only
+    /// token.
+    fn router(name: &str) -> Router {
+        let (app, _) = app_state(name, |config| {
+            config.web.users = vec![WebUser {
+                name: "admin".to_string(),
+                password_hash: hash("hunter2"),
+                role: WebRole::Admin,
+            }];
+            config.web.tokens = vec![
+                WebToken {
+                    name: "script".to_string(),
+                    token_hash: token_hash("s3cret"),
+                    role: WebRole::Admin,
+                },
+                WebToken {
+                    name: "dashboard".to_string(),
+                    token_hash: token_hash("look"),
+                    role: WebRole::ReadOnly,
+                },
+            ];
+        });
+        let (signal_tx, _) = broadcast::channel(16);
+        super::super::create_router(app, signal_tx)
+    }
+
+    async fn status(router: &Router, request: axum::http::request::Builder) -> StatusCode {
+        let request = request.body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    fn basic(credentials: &str) -> String {
+        format!("Basic {}", STANDARD.encode(credentials))
+    }
+
+    #[tokio::test]
+    async fn test_requires_credentials() {
+        let router = router("auth-required");
+        let response = router
+            .clone()
+            .oneshot(Request::get("/api/v1/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers()[header::WWW_AUTHENTICATE]
+            .to_str()
+            .unwrap()
+            .starts_with("Basic"));
+
+        let get = |authorization: String| {
+            Request::get("/api/v1/status").header(header::AUTHORIZATION, authorization)
+        };
+        assert_eq!(
+            status(&router, get(basic("admin:wrong"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&router, get("Bearer nope".into())).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&router, get(basic("admin:hunter2"))).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&router, get("Bearer look".into())).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_cannot_change() {
+        let router = router("auth-read-only");
+        let put = |authorization: &str| {
+            Request::put("/api/v1/face")
+                .header(header::AUTHORIZATION, authorization)
+                .header(header::CONTENT_TYPE, "application/json")
+        };
+        let request = put("Bearer look")
+            .body(Body::from(r#"{"face":"clock"}"#))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let request = put("Bearer s3cret")
+            .body(Body::from(r#"{"face":"clock"}"#))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_changes_need_csrf_token() {
+        let (app, _) = app_state("auth-csrf", |config| {
+            config.web.users = vec![WebUser {
+                name: "admin".to_string(),
+                password_hash: hash("hunter2"),
+                role: WebRole::Admin,
+            }];
+        });
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = super::super::create_router(app.clone(), signal_tx);
+
+        let post = || {
+            Request::post("/face")
+                .header(header::AUTHORIZATION, basic("admin:hunter2"))
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        };
+        let response = router
+            .clone()
+            .oneshot(post().body(Body::from("face=clock")).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // The index page hands the token to HTMX
+        let response = router
+            .clone()
+            .oneshot(
+                Request::get("/")
+                    .header(header::AUTHORIZATION, basic("admin:hunter2"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let page = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let page = String::from_utf8(page.to_vec()).unwrap();
+        let token = page
+            .split("\"X-CSRF-Token\": \"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap()
+            .to_string();
+
+        let response = router
+            .clone()
+            .oneshot(
+                post()
+                    .header(CSRF_HEADER, token)
+                    .body(Body::from("face=clock"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(app.default_panel().face_name(), "clock");
+    }
+
+    #[tokio::test]
+    async fn test_cross_origin_changes_are_refused() {
+        let (app, _) = app_state("auth-origin", |_| {});
+        let (signal_tx, _) = broadcast::channel(16);
+        let router = super::super::create_router(app, signal_tx);
+        let post = || Request::post("/api/v1/led/off").header(header::HOST, "localhost:8686");
+
+        assert_eq!(
+            status(
+                &router,
+                post().header(header::ORIGIN, "http://evil.example")
+            )
+            .await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&router, post().header("sec-fetch-site", "cross-site")).await,
+            StatusCode::FORBIDDEN
+        );
+        // Same-origin pages and non-browser clients get through
+        assert_ne!(
+            status(
+                &router,
+                post().header(header::ORIGIN, "http://localhost:8686")
+            )
+            .await,
+            StatusCode::FORBIDDEN
+        );
+        assert_ne!(status(&router, post()).await, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_failed_logins_are_throttled() {
+        let auth = Auth::new(&WebConfig {
+            users: vec![WebUser {
+                name: "admin".to_string(),
+                password_hash: hash("hunter2"),
+                role: WebRole::Admin,
+            }],
+            ..WebConfig::default()
+        });
+        let client = client(Some("192.0.2.1:4000".parse().unwrap()));
+        assert_eq!(
+            auth.verify(&basic("admin:hunter2"), client),
+            Ok(WebRole::Admin)
+        );
+        // Spelling the header differently hits the same cache entry
+        assert_eq!(
+            auth.verify(
+                &format!("basic  {}", STANDARD.encode("admin:hunter2")),
+                client
+            ),
+            Ok(WebRole::Admin)
+        );
+        assert_eq!(auth.verified.lock().unwrap().len(), 1);
+
+        for _ in 0..MAX_FAILURES {
+            assert_eq!(
+                auth.verify(&basic("nobody:guess"), client),
+                Err(Refusal::Invalid)
+            );
+        }
+        assert_eq!(
+            auth.verify(&basic("admin:guess"), client),
+            Err(Refusal::Throttled)
+        );
+        // Clients that already logged in keep working
+        assert_eq!(
+            auth.verify(&basic("admin:hunter2"), client),
+            Ok(WebRole::Admin)
+        );
+
+        auth.failures
+            .lock()
+            .unwrap()
+            .get_mut(&client)
+            .unwrap()
+            .since -= FAILURE_WINDOW;
+        assert_eq!(
+            auth.verify(&basic("admin:guess"), client),
+            Err(Refusal::Invalid)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_throttling_one_client_spares_others() {
+        let router = router("auth-throttle-clients");
+        let get = |peer: &str, credentials: &str| {
+            let peer: SocketAddr = peer.parse().unwrap();
+            Request::get("/api/v1/status")
+                .header(header::AUTHORIZATION, basic(credentials))
+                .extension(ConnectInfo(peer))
+        };
+
+        for _ in 0..MAX_FAILURES {
+            assert_eq!(
+                status(&router, get("192.0.2.1:4000", "admin:guess")).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        let response = router
+            .clone()
+            .oneshot(
+                get("192.0.2.1:4001", "admin:hunter2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+        // Other clients, including those on a Unix socket, are still heard
+        assert_eq!(
+            status(&router, get("198.51.100.7:4000", "admin:hunter2")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&router, get("198.51.100.7:4000", "admin:guess")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let unix =
+            Request::get("/api/v1/status").header(header::AUTHORIZATION, basic("admin:guess"));
+        assert_eq!(status(&router, unix).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_ipv6_clients_count_by_prefix() {
+        let client = |peer: &str| client(Some(peer.parse().unwrap()));
+        assert_eq!(client("[2001:db8::1]:80"), client("[2001:db8::ffff:1]:80"));
+        assert_ne!(client("[2001:db8::1]:80"), client("[2001:db8:0:1::1]:80"));
+        assert_eq!(client("[::ffff:192.0.2.1]:80"), client("192.0.2.1:80"));
+        assert_eq!(client("192.0.2.1:80"), Some(IpAddr::from([192, 0, 2, 1])));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
@@ -0,0 +1,383 @@
+//! Listeners of the web server.
+//!
+//! The server listens on a TCP address or a Unix socket from `web.listen`,
+//! unless systemd passes it an open socket (socket activation).
+
+use std::net::SocketAddr;
+use std::os::fd::{FromRawFd, RawFd};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::UnixListener;
+use tracing::{debug, info, warn};
+
+use crate::config::{ListenAddr, WebConfig};
+
+/// First file descriptor passed by systemd.
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// A bound listener.
+pub enum Listener {
+    Tcp(std::net::TcpListener),
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl Listener {
+    /// Takes the socket systemd passed, or else binds the configured
+    /// address. Fails if the socket cannot carry the configured TLS.
+    pub fn open(config: &WebConfig) -> Result<Self> {
+        let listener = match Self::from_systemd()? {
+            Some(listener) => {
+                info!("Using the socket passed by systemd");
+                listener
+            }
+            None => Self::bind(config)?,
+        };
+        if matches!(listener, Self::Unix(_)) && config.tls_cert.is_some() {
+            bail!("web.tls_cert is set, but the socket systemd passed is a Unix socket");
+        }
+        Ok(listener)
+    }
+
+    /// Returns the socket passed by systemd socket activation, if any.
+    pub fn from_systemd() -> Result<Option<Self>> {
+        let ours = std::env::var("LISTEN_PID")
+            .ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_some_and(|pid| pid == std::process::id());
+        let count = std::env::var("LISTEN_FDS")
+            .ok()
+            .and_then(|n| n.parse::<u32>().ok())
+            .unwrap_or(0);
+        if !ours || count == 0 {
+            return Ok(None);
+        }
+        if count > 1 {
+            bail!("systemd passed {} sockets; the web server takes one", count);
+        }
+
+        let fd = SD_LISTEN_FDS_START;
+        // SAFETY: systemd hands the descriptor to this process, which owns it
+        // from here on; getsockname only writes into the storage it is given.
+        let family = unsafe {
+            let mut addr: libc::sockaddr_storage = std::mem::zeroed();
+            let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+            if libc::getsockname(fd, &mut addr as *mut _ as *mut libc::sockaddr, &mut len) != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .context("systemd passed a descriptor that is not a socket");
+            }
+            // Keep it from leaking into the commands the daemon runs
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            libc::c_int::from(addr.ss_family)
+        };
+        let listener = match family {
+            libc::AF_UNIX => {
+                // SAFETY: see above
+                Self::Unix(unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) })
+            }
+            libc::AF_INET | libc::AF_INET6 => {
+                // SAFETY: see above
+                Self::Tcp(unsafe { std::net::TcpListener::from_raw_fd(fd) })
+            }
+            family => bail!("systemd passed a socket of unsupported family {}", family),
+        };
+        Ok(Some(listener))
+    }
+
+    /// Binds the address configured in `web.listen`.
+    pub fn bind(config: &WebConfig) -> Result<Self> {
+        match config.listen_addr()? {
+            ListenAddr::Tcp(addr) => {
+                let listener = std::net::TcpListener::bind(addr)
+                    .with_context(|| format!("Failed to listen on {}", addr))?;
+                Ok(Self::Tcp(listener))
+            }
+            ListenAddr::Unix(path) => {
+                remove_stale_socket(&path)?;
+                Ok(Self::Unix(bind_unix(&path, config)?))
+            }
+        }
+    }
+
+    /// Describes the listener for logs.
+    pub fn describe(&self, tls: bool) -> String {
+        match self {
+            Self::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) if tls => format!("https://{}", addr),
+                Ok(addr) => format!("http://{}", addr),
+                Err(_) => "a TCP socket".to_string(),
+            },
+            Self::Unix(listener) => match listener.local_addr() {
+                Ok(addr) => match addr.as_pathname() {
+                    Some(path) => format!("unix:{}", path.display()),
+                    None => "an unnamed Unix socket".to_string(),
+                },
+                Err(_) => "a Unix socket".to_string(),
+            },
+        }
+    }
+}
+
+/// Removes a socket left behind by a previous run, refusing to remove
+/// anything else.
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display())),
+        Ok(_) => bail!("{} exists and is not a socket", path.display()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Binds a Unix socket with `web.socket_mode` and `web.socket_group`.
+/// With either set, the socket is bound in a private directory and only
+/// moved into place once its access is restricted, so no client can
+/// connect while it still has the default mode.
+fn bind_unix(path: &Path, config: &WebConfig) -> Result<std::os::unix::net::UnixListener> {
+    let bind = |path: &Path| {
+        std::os::unix::net::UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))
+    };
+    if config.socket_mode.is_none() && config.socket_group.is_none() {
+        return bind(path);
+    }
+
+    let name = path.file_name().context("Socket path has no file name")?;
+    let private = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&private);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .with_context(|| format!("Failed to create {}", private.display()))?;
+    let staged = private.join(name);
+    let result = bind(&staged).and_then(|listener| {
+        set_socket_access(&staged, config)?;
+        std::fs::rename(&staged, path)
+            .with_context(|| format!("Failed to move the socket to {}", path.display()))?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    result
+}
+
+/// Applies `web.socket_mode` and `web.socket_group` to a Unix socket.
+fn set_socket_access(path: &Path, config: &WebConfig) -> Result<()> {
+    if let Some(group) = &config.socket_group {
+        let gid = group_id(group)?;
+        std::os::unix::fs::chown(path, None, Some(gid))
+            .with_context(|| format!("Failed to give {} to group '{}'", path.display(), group))?;
+    }
+    if let Some(mode) = config.socket_mode()? {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .with_context(|| format!("Failed to set the mode of {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Looks up a group by name or numeric id.
+fn group_id(group: &str) -> Result<u32> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+    let name = std::ffi::CString::new(group).context("Invalid group name")?;
+    // SAFETY: an all-zero group is valid; getgrnam_r fills it in
+    let mut entry: libc::group = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: every pointer refers to storage that outlives the call
+    let rc = unsafe {
+        libc::getgrnam_r(
+            name.as_ptr(),
+            &mut entry,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        )
+    };
+    if rc != 0 || result.is_null() {
+        bail!("Unknown group '{}'", group);
+    }
+    Ok(entry.gr_gid)
+}
+
+/// Serves the router on a Unix socket.
+pub async fn serve_unix(listener: std::os::unix::net::UnixListener, app: Router) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    let listener = UnixListener::from_std(listener)?;
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            // The client went away before it was accepted
+            Err(e) if is_connection_error(&e) => continue,
+            // E.g. out of file descriptors; wait for some to be freed, as
+            // axum::serve does
+            Err(e) => {
+                warn!("Web: failed to accept a connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(e) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Web: connection error: {}", e);
+            }
+        });
+    }
+}
+
+/// Whether an accept error concerns only the connection being accepted.
+fn is_connection_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Serves the router on a listener from [`Listener::open`], over HTTPS when
+/// a certificate is configured.
+pub async fn serve(app: Router, listener: Listener, config: &WebConfig) -> Result<()> {
+    let tls = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => {
+            // Fails only if a provider is already installed
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            Some(
+                axum_server::tls_rustls::RustlsConfig::from_pem_file(cert, key)
+                    .await
+                    .context("Failed to load TLS certificate and key")?,
+            )
+        }
+        _ => None,
+    };
+    info!(
+        "Web server listening on {}",
+        listener.describe(tls.is_some())
+    );
+
+    match (listener, tls) {
+        (Listener::Tcp(listener), Some(tls)) => {
+            axum_server::from_tcp_rustls(listener, tls)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
+        (Listener::Tcp(listener), None) => {
+            listener.set_nonblocking(true)?;
+            axum::serve(
+                tokio::net::TcpListener::from_std(listener)?,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await?;
+        }
+        // Listener::open refuses this combination
+        (Listener::Unix(_), Some(_)) => bail!("TLS is not supported on Unix sockets"),
+        (Listener::Unix(listener), None) => serve_unix(listener, app).await?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_serves_on_unix_socket_with_mode() {
+        let dir = std::env::temp_dir().join(format!("ht32-panel-test-{}-unix", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("web.sock");
+        // A stale socket from an earlier run is replaced
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        let config = WebConfig {
+            listen: format!("unix:{}", path.display()),
+            socket_mode: Some("0600".to_string()),
+            ..WebConfig::default()
+        };
+        let Listener::Unix(listener) = Listener::bind(&config).unwrap() else {
+            panic!("expected a Unix socket");
+        };
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // The private directory it was bound in is gone
+        let entries: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["web.sock"]);
+
+        let app = Router::new().route("/", axum::routing::get(|| async { "hello" }));
+        tokio::spawn(serve_unix(listener, app));
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("hello"));
+    }
+
+    #[test]
+    fn test_refuses_tls_on_unix_socket() {
+        let path =
+            std::env::temp_dir().join(format!("ht32-panel-test-{}-tls.sock", std::process::id()));
+        let config = WebConfig {
+            listen: format!("unix:{}", path.display()),
+            tls_cert: Some("cert.pem".into()),
+            tls_key: Some("key.pem".into()),
+            ..WebConfig::default()
+        };
+        assert!(Listener::open(&config).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_refuses_to_replace_other_files() {
+        let path =
+            std::env::temp_dir().join(format!("ht32-panel-test-{}-file", std::process::id()));
+        std::fs::write(&path, "keep").unwrap();
+        assert!(remove_stale_socket(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_listen_addresses() {
+        let parse = |listen: &str| {
+            WebConfig {
+                listen: listen.to_string(),
+                ..WebConfig::default()
+            }
+            .listen_addr()
+        };
+        assert_eq!(
+            parse("[::1]:8686").unwrap(),
+            ListenAddr::Tcp("[::1]:8686".parse().unwrap())
+        );
+        assert_eq!(
+            parse("unix:/run/ht32-panel/web.sock").unwrap(),
+            ListenAddr::Unix("/run/ht32-panel/web.sock".into())
+        );
+        assert!(parse("unix:web.sock").is_err());
+        assert!(parse("localhost").is_err());
+        assert_eq!(group_id("0").unwrap(), 0);
+    }
+}
@@ -1,9 +1,17 @@
 //! HTMX web UI module.
 
+mod api;
+mod auth;
+mod listen;
+
+pub use listen::{serve, Listener};
+
 use askama::Template;
 use axum::{
-    extract::{Form, State},
-    http::{header, StatusCode},
+    async_trait,
+    extract::{Form, FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, StatusCode},
+    middleware,
     response::{
         sse::{Event, KeepAlive, Sse},
         Html, IntoResponse, Response,
@@ -15,15 +23,17 @@ use serde::Deserialize;
 use std::convert::Infallible;
 use std::sync::Arc;
 use tokio::sync::broadcast;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, WatchStream};
 use tokio_stream::{Stream, StreamExt};
+use tracing::warn;
 
+use crate::carousel::PlaylistEntry;
+use crate::config::{MAX_REFRESH_INTERVAL, MIN_REFRESH_INTERVAL};
 use crate::dbus::DaemonSignals;
-use crate::faces::{
-    available_faces, available_themes, complication_names, complication_options,
-    ComplicationOptionType,
-};
-use crate::state::AppState;
+use crate::faces::{available_faces, available_themes, ComplicationOptionType};
+use crate::metrics;
+use crate::notifications::{self, Severity};
+use crate::state::{AppState, PanelState};
 
 /// Face option for template rendering.
 #[derive(Clone)]
@@ -43,7 +53,11 @@ use ht32_panel_hw::Orientation;
 /// Main index page template.
 #[derive(Template)]
 #[template(path = "index.html")]
-struct IndexTemplate;
+struct IndexTemplate {
+    panel: String,
+    panels: Vec<String>,
+    csrf_token: String,
+}
 
 /// Status partial template.
 #[derive(Template)]
@@ -59,6 +73,40 @@ struct OrientationTemplate {
     current: String,
 }
 
+/// Refresh interval partial template.
+#[derive(Template)]
+#[template(path = "partials/refresh.html")]
+struct RefreshTemplate {
+    ms: u32,
+    min: u32,
+    max: u32,
+    error: Option<String>,
+}
+
+/// Brightness partial template.
+#[derive(Template)]
+#[template(path = "partials/brightness.html")]
+struct BrightnessTemplate {
+    percent: u8,
+    off: bool,
+    error: Option<String>,
+}
+
+/// Notification history entry for template rendering.
+struct NotificationItem {
+    time: String,
+    severity: String,
+    text: String,
+}
+
+/// Notifications partial template.
+#[derive(Template)]
+#[template(path = "partials/notifications.html")]
+struct NotificationsTemplate {
+    history: Vec<NotificationItem>,
+    error: Option<String>,
+}
+
 /// Face partial template.
 #[derive(Template)]
 #[template(path = "partials/face.html")]
@@ -67,6 +115,16 @@ struct FaceTemplate {
     faces: Vec<FaceOption>,
 }
 
+/// Carousel partial template.
+#[derive(Template)]
+#[template(path = "partials/carousel.html")]
+struct CarouselTemplate {
+    running: bool,
+    index: usize,
+    entries: Vec<PlaylistEntry>,
+    error: Option<String>,
+}
+
 /// LED controls partial template.
 #[derive(Template)]
 #[template(path = "partials/led.html")]
@@ -89,6 +147,7 @@ struct ThemeTemplate {
 #[derive(Template)]
 #[template(path = "partials/preview.html")]
 struct PreviewTemplate {
+    panel: String,
     timestamp: u128,
 }
 
@@ -137,11 +196,54 @@ struct ComplicationsTemplate {
 pub struct WebState {
     pub app: Arc<AppState>,
     pub signal_tx: broadcast::Sender<DaemonSignals>,
+    pub auth: Arc<auth::Auth>,
+}
+
+/// Header carrying the panel id on HTMX requests.
+const PANEL_HEADER: &str = "x-panel";
+
+/// Query parameter selecting a panel.
+#[derive(Deserialize)]
+struct PanelQuery {
+    panel: Option<String>,
+}
+
+/// The panel a request refers to.
+///
+/// Taken from the `X-Panel` header (sent by HTMX from the index page), then
+/// the `panel` query parameter, falling back to the default panel.
+struct SelectedPanel(Arc<PanelState>);
+
+#[async_trait]
+impl FromRequestParts<WebState> for SelectedPanel {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &WebState) -> Result<Self, Response> {
+        let from_header = parts
+            .headers
+            .get(PANEL_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let from_query = Query::<PanelQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|q| q.0.panel);
+
+        match from_header.or(from_query) {
+            None => Ok(Self(state.app.default_panel().clone())),
+            Some(id) => match state.app.panel(&id) {
+                Some(panel) => Ok(Self(panel.clone())),
+                None => {
+                    Err((StatusCode::NOT_FOUND, format!("Unknown panel: {}", id)).into_response())
+                }
+            },
+        }
+    }
 }
 
 /// Creates the web router with all routes.
 pub fn create_router(state: Arc<AppState>, signal_tx: broadcast::Sender<DaemonSignals>) -> Router {
     let web_state = WebState {
+        auth: Arc::new(auth::Auth::new(&state.config().web)),
         app: state,
         signal_tx,
     };
@@ -153,11 +255,22 @@ pub fn create_router(state: Arc<AppState>, signal_tx: broadcast::Sender<DaemonSi
         .route("/lcd.png", get(lcd_png))
         // Server-Sent Events for live updates
         .route("/events", get(events_stream))
+        .route("/samples", get(samples_stream))
         // Partials for HTMX
         .route("/status", get(status))
+        .route("/metrics", get(metrics))
         .route("/orientation", get(orientation_get).post(orientation_set))
+        .route(
+            "/refresh-interval",
+            get(refresh_interval_get).post(refresh_interval_set),
+        )
+        .route("/brightness", get(brightness_get).post(brightness_set))
         .route("/face", get(face_get).post(face_set))
+        .route("/carousel", get(carousel_get).post(carousel_set))
         .route("/led", get(led_get).post(led_set))
+        .route("/notifications", get(notifications_get))
+        .route("/notify", post(notify))
+        .route("/custom/:key", post(custom_value_set))
         .route("/theme", get(theme_get).post(theme_set))
         .route(
             "/complications",
@@ -165,32 +278,97 @@ pub fn create_router(state: Arc<AppState>, signal_tx: broadcast::Sender<DaemonSi
         )
         .route("/complication-option", post(complication_option_set))
         .route("/preview", get(preview_get))
+        // JSON API
+        .nest("/api/v1", api::router())
+        // Authentication, roles and CSRF protection
+        .layer(middleware::from_fn_with_state(
+            web_state.clone(),
+            auth::require_auth,
+        ))
         // State
         .with_state(web_state)
 }
 
 /// GET / - Main page
-async fn index() -> impl IntoResponse {
-    Html(IndexTemplate.render().unwrap())
+async fn index(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+) -> impl IntoResponse {
+    Html(
+        IndexTemplate {
+            panel: panel.id().to_string(),
+            panels: state.app.panel_ids(),
+            csrf_token: state.auth.csrf_token().to_string(),
+        }
+        .render()
+        .unwrap(),
+    )
 }
 
-/// GET /events - Server-Sent Events stream for live updates
+/// GET /events - Server-Sent Events stream for live updates of one panel
+///
+/// Settings changes are sent as events named after what changed, with the
+/// data "reload". A `frame` event carrying the frame counter follows each
+/// drawn frame. A client that falls behind gets a `lagged` event, since
+/// changes it missed were dropped.
 async fn events_stream(
     State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let rx = state.signal_tx.subscribe();
-    let stream = BroadcastStream::new(rx).filter_map(|result| {
+    let panel_id = panel.id().to_string();
+    let signals = BroadcastStream::new(rx).filter_map(move |result| {
         match result {
-            Ok(signal) => {
+            Ok(signal) if signal.panel() == panel_id => {
                 let event_type = match signal {
-                    DaemonSignals::OrientationChanged => "orientation",
-                    DaemonSignals::LedChanged => "led",
-                    DaemonSignals::DisplaySettingsChanged => "display",
-                    DaemonSignals::ComplicationOptionChanged => "complication",
+                    DaemonSignals::OrientationChanged { .. } => "orientation",
+                    DaemonSignals::LedChanged { .. } => "led",
+                    DaemonSignals::DisplaySettingsChanged { .. } => "display",
+                    DaemonSignals::ComplicationOptionChanged { .. } => "complication",
+                    DaemonSignals::ConnectionChanged { .. } => "status",
+                    DaemonSignals::AlertRaised { .. } => "alert",
+                    DaemonSignals::ScheduleChanged { .. } => "schedule",
+                    DaemonSignals::NotificationPosted { .. } => "notification",
                 };
                 Some(Ok(Event::default().event(event_type).data("reload")))
             }
-            Err(_) => None, // Skip lagged messages
+            Ok(_) => None, // Another panel
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Ok(Event::default()
+                .event("lagged")
+                .data(missed.to_string()))),
+        }
+    });
+    let frames = WatchStream::from_changes(panel.subscribe_frames()).filter_map(|frame| {
+        frame.map(|frame| {
+            Ok(Event::default()
+                .event("frame")
+                .data(frame.frame.to_string()))
+        })
+    });
+
+    Sse::new(signals.merge(frames)).keep_alive(KeepAlive::default())
+}
+
+/// GET /samples - Server-Sent Events stream of the sensor sample of each
+/// drawn frame, as JSON `{"frame": n, "data": {...}}`
+///
+/// Starts with the last frame. A client that falls behind skips to the
+/// newest frame.
+async fn samples_stream(
+    SelectedPanel(panel): SelectedPanel,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = WatchStream::new(panel.subscribe_frames()).filter_map(|frame| {
+        let frame = frame?;
+        match Event::default()
+            .event("sample")
+            .id(frame.frame.to_string())
+            .json_data(&*frame)
+        {
+            Ok(event) => Some(Ok(event)),
+            Err(e) => {
+                warn!("Failed to encode sample: {}", e);
+                None
+            }
         }
     });
 
@@ -198,8 +376,8 @@ async fn events_stream(
 }
 
 /// GET /lcd.png - LCD framebuffer as PNG
-async fn lcd_png(State(state): State<WebState>) -> Response {
-    match state.app.get_screen_png() {
+async fn lcd_png(SelectedPanel(panel): SelectedPanel) -> Response {
+    match panel.get_screen_png() {
         Ok(png_data) => (
             StatusCode::OK,
             [
@@ -218,14 +396,22 @@ async fn lcd_png(State(state): State<WebState>) -> Response {
 }
 
 /// GET /status - Connection status partial
-async fn status(State(state): State<WebState>) -> impl IntoResponse {
-    let connected = state.app.is_lcd_connected();
+async fn status(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    let connected = panel.is_lcd_connected();
     Html(StatusTemplate { connected }.render().unwrap())
 }
 
+/// GET /metrics - Sensor samples and daemon health for Prometheus
+async fn metrics(State(state): State<WebState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)],
+        metrics::render(&state.app),
+    )
+}
+
 /// GET /orientation - Orientation controls partial
-async fn orientation_get(State(state): State<WebState>) -> impl IntoResponse {
-    let current = state.app.orientation().to_string();
+async fn orientation_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    let current = panel.orientation().to_string();
     Html(OrientationTemplate { current }.render().unwrap())
 }
 
@@ -238,6 +424,7 @@ struct OrientationForm {
 /// POST /orientation - Set orientation
 async fn orientation_set(
     State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
     Form(form): Form<OrientationForm>,
 ) -> Response {
     let orientation = match form.orientation.parse::<Orientation>() {
@@ -250,20 +437,208 @@ async fn orientation_set(
                 .into_response();
         }
     };
-    if let Err(e) = state.app.set_orientation(orientation) {
+    if let Err(e) = panel.set_orientation(orientation) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to set orientation: {}", e),
         )
             .into_response();
     }
-    let current = state.app.orientation().to_string();
+    let _ = state.signal_tx.send(DaemonSignals::OrientationChanged {
+        panel: panel.id().to_string(),
+    });
+    let current = panel.orientation().to_string();
     Html(OrientationTemplate { current }.render().unwrap()).into_response()
 }
 
+/// Renders the refresh interval partial.
+fn render_refresh_interval(panel: &PanelState, error: Option<String>) -> Html<String> {
+    Html(
+        RefreshTemplate {
+            ms: panel.refresh_interval_ms(),
+            min: MIN_REFRESH_INTERVAL,
+            max: MAX_REFRESH_INTERVAL,
+            error,
+        }
+        .render()
+        .unwrap(),
+    )
+}
+
+/// GET /refresh-interval - Refresh interval controls partial
+async fn refresh_interval_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    render_refresh_interval(&panel, None)
+}
+
+/// Form data for the refresh interval.
+#[derive(Deserialize)]
+struct RefreshForm {
+    ms: u32,
+}
+
+/// POST /refresh-interval - Set the refresh interval
+async fn refresh_interval_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<RefreshForm>,
+) -> impl IntoResponse {
+    let error = match panel.set_refresh_interval(form.ms) {
+        Ok(()) => {
+            let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: panel.id().to_string(),
+            });
+            None
+        }
+        Err(e) => Some(e.to_string()),
+    };
+    render_refresh_interval(&panel, error)
+}
+
+/// Renders the brightness partial.
+fn render_brightness(panel: &PanelState, error: Option<String>) -> Html<String> {
+    Html(
+        BrightnessTemplate {
+            percent: panel.brightness(),
+            off: panel.is_display_off(),
+            error,
+        }
+        .render()
+        .unwrap(),
+    )
+}
+
+/// GET /brightness - Brightness and display power controls partial
+async fn brightness_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    render_brightness(&panel, None)
+}
+
+/// Form data for brightness and display power.
+#[derive(Deserialize)]
+struct BrightnessForm {
+    percent: Option<u8>,
+    /// "on" or "off"
+    power: Option<String>,
+}
+
+/// POST /brightness - Set the brightness or switch the display on or off
+async fn brightness_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<BrightnessForm>,
+) -> impl IntoResponse {
+    let result = match (form.power.as_deref(), form.percent) {
+        (Some(power @ ("on" | "off")), _) => {
+            panel.set_display_off(power == "off");
+            Ok(())
+        }
+        (Some(power), _) => Err(format!("Unknown power state: {}", power)),
+        (None, Some(percent)) => panel.set_brightness(percent).map_err(|e| e.to_string()),
+        (None, None) => Err("Missing brightness".to_string()),
+    };
+    let error = match result {
+        Ok(()) => {
+            let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: panel.id().to_string(),
+            });
+            None
+        }
+        Err(e) => Some(e),
+    };
+    render_brightness(&panel, error)
+}
+
+/// Notifications listed in the web UI.
+const NOTIFICATION_HISTORY_SHOWN: usize = 10;
+
+/// Renders the notifications partial, newest first.
+fn render_notifications(panel: &PanelState, error: Option<String>) -> Html<String> {
+    let history = panel
+        .notification_history()
+        .into_iter()
+        .rev()
+        .take(NOTIFICATION_HISTORY_SHOWN)
+        .map(|n| NotificationItem {
+            time: n.time.format("%H:%M:%S").to_string(),
+            severity: n.severity.to_string(),
+            text: n.text,
+        })
+        .collect();
+    Html(NotificationsTemplate { history, error }.render().unwrap())
+}
+
+/// GET /notifications - Notification form and history partial
+async fn notifications_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    render_notifications(&panel, None)
+}
+
+/// Form data for a notification.
+#[derive(Deserialize)]
+struct NotifyForm {
+    text: String,
+    /// "info", "warning" or "critical" (default: info)
+    severity: Option<String>,
+    seconds: Option<u32>,
+}
+
+/// POST /notify - Draw a notification over the face
+async fn notify(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<NotifyForm>,
+) -> impl IntoResponse {
+    let severity = form
+        .severity
+        .as_deref()
+        .map(str::parse::<Severity>)
+        .unwrap_or(Ok(Severity::Info));
+    let result = severity.and_then(|severity| {
+        panel.show_notification(
+            &form.text,
+            severity,
+            form.seconds.unwrap_or(notifications::DEFAULT_SECONDS),
+        )
+    });
+    let error = match result {
+        Ok(notification) => {
+            let _ = state.signal_tx.send(DaemonSignals::NotificationPosted {
+                panel: panel.id().to_string(),
+                id: notification.id,
+                text: notification.text,
+                severity: notification.severity.to_string(),
+            });
+            None
+        }
+        Err(e) => Some(e.to_string()),
+    };
+    render_notifications(&panel, error)
+}
+
+/// Form data for a custom value.
+#[derive(Deserialize)]
+struct CustomValueForm {
+    value: f64,
+    unit: Option<String>,
+}
+
+/// POST /custom/{key} - Set a custom value shown by faces
+async fn custom_value_set(
+    State(state): State<WebState>,
+    Path(key): Path<String>,
+    Form(form): Form<CustomValueForm>,
+) -> impl IntoResponse {
+    match state
+        .app
+        .custom_values()
+        .set(&key, form.value, form.unit.as_deref().unwrap_or_default())
+    {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
 /// GET /face - Face controls partial
-async fn face_get(State(state): State<WebState>) -> impl IntoResponse {
-    let current = state.app.face_name();
+async fn face_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    let current = panel.face_name();
     let faces: Vec<FaceOption> = available_faces()
         .iter()
         .map(|f| FaceOption {
@@ -281,15 +656,22 @@ struct FaceForm {
 }
 
 /// POST /face - Set face
-async fn face_set(State(state): State<WebState>, Form(form): Form<FaceForm>) -> Response {
-    if let Err(e) = state.app.set_face(&form.face) {
+async fn face_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<FaceForm>,
+) -> Response {
+    if let Err(e) = panel.set_face(&form.face) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to set face: {}", e),
         )
             .into_response();
     }
-    let current = state.app.face_name();
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    let current = panel.face_name();
     let faces: Vec<FaceOption> = available_faces()
         .iter()
         .map(|f| FaceOption {
@@ -300,9 +682,68 @@ async fn face_set(State(state): State<WebState>, Form(form): Form<FaceForm>) ->
     Html(FaceTemplate { current, faces }.render().unwrap()).into_response()
 }
 
+/// Renders the carousel partial.
+fn render_carousel(panel: &PanelState, error: Option<String>) -> Html<String> {
+    Html(
+        CarouselTemplate {
+            running: panel.is_carousel_running(),
+            index: panel.carousel_index(),
+            entries: panel.playlist(),
+            error,
+        }
+        .render()
+        .unwrap(),
+    )
+}
+
+/// GET /carousel - Carousel controls partial
+async fn carousel_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    render_carousel(&panel, None)
+}
+
+/// Form data for carousel controls.
+#[derive(Deserialize)]
+struct CarouselForm {
+    action: String,
+}
+
+/// POST /carousel - Start, stop or step the carousel
+async fn carousel_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<CarouselForm>,
+) -> Response {
+    let result = match form.action.as_str() {
+        "start" => panel.start_carousel(),
+        "stop" => {
+            panel.stop_carousel();
+            Ok(())
+        }
+        "next" => panel.next_face(),
+        "previous" => panel.previous_face(),
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown carousel action: {}", other),
+            )
+                .into_response();
+        }
+    };
+    let error = match result {
+        Ok(()) => {
+            let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+                panel: panel.id().to_string(),
+            });
+            None
+        }
+        Err(e) => Some(e.to_string()),
+    };
+    render_carousel(&panel, error).into_response()
+}
+
 /// GET /led - LED controls partial
-async fn led_get(State(state): State<WebState>) -> impl IntoResponse {
-    let (theme, intensity, speed) = state.app.led_settings();
+async fn led_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    let (theme, intensity, speed) = panel.led_settings();
     Html(
         LedTemplate {
             theme,
@@ -330,20 +771,29 @@ fn default_led() -> u8 {
 }
 
 /// POST /led - Set LED settings
-async fn led_set(State(state): State<WebState>, Form(form): Form<LedForm>) -> impl IntoResponse {
+async fn led_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<LedForm>,
+) -> impl IntoResponse {
     let theme = form.theme.clamp(1, 5);
     let intensity = form.intensity.clamp(1, 5);
     let speed = form.speed.clamp(1, 5);
 
-    let error = match state.app.set_led(theme, intensity, speed).await {
-        Ok(()) => None,
+    let error = match panel.set_led(theme, intensity, speed).await {
+        Ok(()) => {
+            let _ = state.signal_tx.send(DaemonSignals::LedChanged {
+                panel: panel.id().to_string(),
+            });
+            None
+        }
         Err(e) => {
             tracing::error!("Failed to set LED: {}", e);
             Some(e.to_string())
         }
     };
 
-    let (theme, intensity, speed) = state.app.led_settings();
+    let (theme, intensity, speed) = panel.led_settings();
     Html(
         LedTemplate {
             theme,
@@ -357,8 +807,8 @@ async fn led_set(State(state): State<WebState>, Form(form): Form<LedForm>) -> im
 }
 
 /// GET /theme - Theme controls partial
-async fn theme_get(State(state): State<WebState>) -> impl IntoResponse {
-    let current = state.app.theme_name();
+async fn theme_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
+    let current = panel.theme_name();
     let themes: Vec<ThemeOption> = available_themes()
         .iter()
         .map(|t| ThemeOption {
@@ -376,15 +826,22 @@ struct ThemeForm {
 }
 
 /// POST /theme - Set theme
-async fn theme_set(State(state): State<WebState>, Form(form): Form<ThemeForm>) -> Response {
-    if let Err(e) = state.app.set_theme(&form.theme) {
+async fn theme_set(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+    Form(form): Form<ThemeForm>,
+) -> Response {
+    if let Err(e) = panel.set_theme(&form.theme) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to set theme: {}", e),
         )
             .into_response();
     }
-    let current = state.app.theme_name();
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
+    let current = panel.theme_name();
     let themes: Vec<ThemeOption> = available_themes()
         .iter()
         .map(|t| ThemeOption {
@@ -396,20 +853,29 @@ async fn theme_set(State(state): State<WebState>, Form(form): Form<ThemeForm>) -
 }
 
 /// GET /preview - Preview image partial
-async fn preview_get() -> impl IntoResponse {
+async fn preview_get(SelectedPanel(panel): SelectedPanel) -> impl IntoResponse {
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_millis();
-    Html(PreviewTemplate { timestamp }.render().unwrap())
+    Html(
+        PreviewTemplate {
+            panel: panel.id().to_string(),
+            timestamp,
+        }
+        .render()
+        .unwrap(),
+    )
 }
 
 /// GET /complications - Complications controls partial
-async fn complications_get(State(state): State<WebState>) -> impl IntoResponse {
-    let face_name = state.app.face_name();
-    let available = state.app.available_complications();
-    let enabled = state.app.enabled_complications();
-    let interfaces = state.app.list_network_interfaces();
+async fn complications_get(
+    State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
+) -> impl IntoResponse {
+    let face_name = panel.face_name();
+    let available = panel.available_complications();
+    let enabled = panel.enabled_complications();
 
     let complications: Vec<ComplicationItem> = available
         .into_iter()
@@ -418,37 +884,21 @@ async fn complications_get(State(state): State<WebState>) -> impl IntoResponse {
                 .options
                 .iter()
                 .map(|opt| {
-                    let current_value = state
-                        .app
+                    let current_value = panel
                         .get_complication_option(&c.id, &opt.id)
                         .unwrap_or_else(|| opt.default_value.clone());
 
                     match &opt.option_type {
                         ComplicationOptionType::Choice(choices) => {
-                            // For network interface, dynamically populate with available interfaces
-                            let choice_list = if c.id == complication_names::NETWORK
-                                && opt.id == complication_options::INTERFACE
-                            {
-                                let mut iface_choices = vec![ComplicationOptionChoice {
-                                    value: "auto".to_string(),
-                                    label: "Auto-detect".to_string(),
-                                }];
-                                for iface in &interfaces {
-                                    iface_choices.push(ComplicationOptionChoice {
-                                        value: iface.clone(),
-                                        label: iface.clone(),
-                                    });
-                                }
-                                iface_choices
-                            } else {
-                                choices
-                                    .iter()
-                                    .map(|ch| ComplicationOptionChoice {
-                                        value: ch.value.clone(),
-                                        label: ch.label.clone(),
-                                    })
-                                    .collect()
-                            };
+                            let choice_list = state
+                                .app
+                                .complication_choices(&c.id, &opt.id, choices)
+                                .into_iter()
+                                .map(|ch| ComplicationOptionChoice {
+                                    value: ch.value,
+                                    label: ch.label,
+                                })
+                                .collect();
                             ComplicationOptionItem {
                                 id: opt.id.clone(),
                                 name: opt.name.clone(),
@@ -523,22 +973,23 @@ struct ComplicationForm {
 /// POST /complications - Toggle a complication
 async fn complications_set(
     State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
     Form(form): Form<ComplicationForm>,
 ) -> Response {
     let enabled = form.enabled.as_deref() == Some("on");
-    if let Err(e) = state
-        .app
-        .set_complication_enabled(&form.complication, enabled)
-    {
+    if let Err(e) = panel.set_complication_enabled(&form.complication, enabled) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to toggle complication: {}", e),
         )
             .into_response();
     }
+    let _ = state.signal_tx.send(DaemonSignals::DisplaySettingsChanged {
+        panel: panel.id().to_string(),
+    });
 
     // Re-render the complications list
-    render_complications(&state.app).into_response()
+    render_complications(&state.app, &panel).into_response()
 }
 
 /// Form data for complication option.
@@ -552,29 +1003,34 @@ struct ComplicationOptionForm {
 /// POST /complication-option - Set a complication option value
 async fn complication_option_set(
     State(state): State<WebState>,
+    SelectedPanel(panel): SelectedPanel,
     Form(form): Form<ComplicationOptionForm>,
 ) -> Response {
-    if let Err(e) = state
-        .app
-        .set_complication_option(&form.complication, &form.option, &form.value)
-    {
+    if let Err(e) = panel.set_complication_option(&form.complication, &form.option, &form.value) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to set complication option: {}", e),
         )
             .into_response();
     }
+    let _ = state
+        .signal_tx
+        .send(DaemonSignals::ComplicationOptionChanged {
+            panel: panel.id().to_string(),
+            complication: form.complication,
+            option: form.option,
+            value: form.value,
+        });
 
     // Re-render the complications list
-    render_complications(&state.app).into_response()
+    render_complications(&state.app, &panel).into_response()
 }
 
 /// Helper to render the complications template
-fn render_complications(state: &Arc<AppState>) -> Html<String> {
+fn render_complications(app: &AppState, state: &PanelState) -> Html<String> {
     let face_name = state.face_name();
     let available = state.available_complications();
     let enabled_set = state.enabled_complications();
-    let interfaces = state.list_network_interfaces();
 
     let complications: Vec<ComplicationItem> = available
         .into_iter()
@@ -589,30 +1045,14 @@ fn render_complications(state: &Arc<AppState>) -> Html<String> {
 
                     match &opt.option_type {
                         ComplicationOptionType::Choice(choices) => {
-                            // For network interface, dynamically populate with available interfaces
-                            let choice_list = if c.id == complication_names::NETWORK
-                                && opt.id == complication_options::INTERFACE
-                            {
-                                let mut iface_choices = vec![ComplicationOptionChoice {
-                                    value: "auto".to_string(),
-                                    label: "Auto-detect".to_string(),
-                                }];
-                                for iface in &interfaces {
-                                    iface_choices.push(ComplicationOptionChoice {
-                                        value: iface.clone(),
-                                        label: iface.clone(),
-                                    });
-                                }
-                                iface_choices
-                            } else {
-                                choices
-                                    .iter()
-                                    .map(|ch| ComplicationOptionChoice {
-                                        value: ch.value.clone(),
-                                        label: ch.label.clone(),
-                                    })
-                                    .collect()
-                            };
+                            let choice_list = app
+                                .complication_choices(&c.id, &opt.id, choices)
+                                .into_iter()
+                                .map(|ch| ComplicationOptionChoice {
+                                    value: ch.value,
+                                    label: ch.label,
+                                })
+                                .collect();
                             ComplicationOptionItem {
                                 id: opt.id.clone(),
                                 name: opt.name.clone(),
@@ -12,6 +12,17 @@ pub enum Error {
     #[error("LCD device not found (VID:PID 04D9:FD01)")]
     LcdNotFound,
 
+    /// LCD device stopped accepting writes.
+    #[error("LCD device disconnected")]
+    LcdDisconnected,
+
+    /// Virtual LCD stream could not be opened or written.
+    #[error("Virtual LCD error at {path}: {source}")]
+    VirtualLcd {
+        path: String,
+        source: std::io::Error,
+    },
+
     /// LED device not found or could not be opened.
     #[error("LED device not found at {0}")]
     LedNotFound(String),
@@ -1,20 +1,24 @@
 //! LCD device communication via USB HID.
 
 use crate::orientation::Orientation;
-use crate::{Error, Result, LCD_PID, LCD_VID};
-use hidapi::{HidApi, HidDevice};
+use crate::{Error, Result, LCD_HEIGHT, LCD_PID, LCD_VID, LCD_WIDTH};
+use hidapi::HidApi;
+use std::path::Path;
 use std::sync::Mutex;
 use tracing::{debug, info};
 
+use super::dirty::{diff_framebuffers, DirtyRect};
 use super::framebuffer::Framebuffer;
 use super::protocol::{
     build_heartbeat_packet, build_orientation_packet, build_redraw_chunk, build_refresh_packet,
     CHUNK_COUNT, DATA_SIZE,
 };
+use super::stream::StreamTransport;
+use super::transport::{HidTransport, LcdTransport};
 
 /// LCD device controller.
 pub struct LcdDevice {
-    device: Mutex<HidDevice>,
+    transport: Mutex<Box<dyn LcdTransport>>,
     current_orientation: Mutex<Orientation>,
 }
 
@@ -29,12 +33,24 @@ impl LcdDevice {
     /// The device has multiple HID interfaces. This function finds and opens
     /// the correct interface for display control (interface 1).
     pub fn open() -> Result<Self> {
+        Self::open_matching(None)
+    }
+
+    /// Opens the LCD device with the given USB serial number.
+    ///
+    /// Use this to pick a specific panel when several are connected.
+    pub fn open_serial(serial: &str) -> Result<Self> {
+        Self::open_matching(Some(serial))
+    }
+
+    fn open_matching(serial: Option<&str>) -> Result<Self> {
         let api = HidApi::new()?;
 
         // Enumerate all devices to find the correct interface
         let devices: Vec<_> = api
             .device_list()
             .filter(|d| d.vendor_id() == LCD_VID && d.product_id() == LCD_PID)
+            .filter(|d| serial.is_none() || d.serial_number() == serial)
             .collect();
 
         if devices.is_empty() {
@@ -63,10 +79,11 @@ impl LcdDevice {
         })?;
 
         info!(
-            "LCD device opened (VID:{:04X} PID:{:04X}, interface={})",
+            "LCD device opened (VID:{:04X} PID:{:04X}, interface={}, serial={})",
             LCD_VID,
             LCD_PID,
-            device_info.interface_number()
+            device_info.interface_number(),
+            device_info.serial_number().unwrap_or("unknown")
         );
 
         // Initial cooldown period - device needs time to initialize after opening.
@@ -75,10 +92,15 @@ impl LcdDevice {
         debug!("Waiting for device initialization (1s cooldown)...");
         std::thread::sleep(std::time::Duration::from_millis(1000));
 
-        Ok(Self {
-            device: Mutex::new(device),
+        Ok(Self::with_transport(HidTransport::new(device)))
+    }
+
+    /// Creates a device that writes packets to the given transport.
+    pub fn with_transport(transport: impl LcdTransport + 'static) -> Self {
+        Self {
+            transport: Mutex::new(Box::new(transport)),
             current_orientation: Mutex::new(Orientation::default()),
-        })
+        }
     }
 
     /// Opens a specific LCD device by path.
@@ -97,10 +119,14 @@ impl LcdDevice {
 
         info!("LCD device opened at path: {}", path);
 
-        Ok(Self {
-            device: Mutex::new(device),
-            current_orientation: Mutex::new(Orientation::default()),
-        })
+        Ok(Self::with_transport(HidTransport::new(device)))
+    }
+
+    /// Opens a virtual LCD that writes packets to a file, FIFO or Unix socket.
+    pub fn open_virtual(path: impl AsRef<Path>) -> Result<Self> {
+        let transport = StreamTransport::open(path)?;
+        info!("Virtual LCD opened at path: {}", transport.path().display());
+        Ok(Self::with_transport(transport))
     }
 
     /// Sets the display orientation.
@@ -109,8 +135,8 @@ impl LcdDevice {
 
         debug!("Orientation packet header: {:02X?}", &packet[0..10]);
 
-        let device = self.device.lock().unwrap();
-        device.write(&packet)?;
+        let mut transport = self.transport.lock().unwrap();
+        transport.write(&packet)?;
 
         *self.current_orientation.lock().unwrap() = orientation;
         debug!("Set orientation to {}", orientation);
@@ -129,8 +155,8 @@ impl LcdDevice {
 
         debug!("Heartbeat packet header: {:02X?}", &packet[0..10]);
 
-        let device = self.device.lock().unwrap();
-        device.write(&packet)?;
+        let mut transport = self.transport.lock().unwrap();
+        transport.write(&packet)?;
         debug!("Heartbeat sent: {:02}:{:02}:{:02}", hours, minutes, seconds);
 
         Ok(())
@@ -146,7 +172,7 @@ impl LcdDevice {
             Orientation::rotate_180(&mut data, framebuffer.width(), framebuffer.height());
         }
 
-        let device = self.device.lock().unwrap();
+        let mut transport = self.transport.lock().unwrap();
 
         for chunk_idx in 0..CHUNK_COUNT {
             let offset = chunk_idx * (DATA_SIZE / 2);
@@ -161,7 +187,7 @@ impl LcdDevice {
                 );
             }
 
-            device.write(&packet)?;
+            transport.write(&packet)?;
         }
 
         debug!("Full redraw completed ({} chunks)", CHUNK_COUNT);
@@ -172,21 +198,51 @@ impl LcdDevice {
     pub fn refresh(&self, x: u16, y: u16, width: u8, height: u8, pixels: &[u16]) -> Result<()> {
         let orientation = *self.current_orientation.lock().unwrap();
         let mut data = pixels.to_vec();
+        let (mut x, mut y) = (x, y);
 
-        // Apply software rotation if needed
+        // Apply software rotation if needed, mirroring the region position too
         if orientation.needs_rotation() {
             Orientation::rotate_180(&mut data, width as u16, height as u16);
+            x = LCD_WIDTH.saturating_sub(x + width as u16);
+            y = LCD_HEIGHT.saturating_sub(y + height as u16);
         }
 
         let packet = build_refresh_packet(x, y, width, height, &data);
 
-        let device = self.device.lock().unwrap();
-        device.write(&packet)?;
+        let mut transport = self.transport.lock().unwrap();
+        transport.write(&packet)?;
 
         debug!("Partial refresh at ({}, {}) {}x{}", x, y, width, height);
         Ok(())
     }
 
+    /// Refreshes a single region of the framebuffer.
+    pub fn refresh_rect(&self, framebuffer: &Framebuffer, rect: &DirtyRect) -> Result<()> {
+        let pixels = framebuffer.extract_region(rect.x, rect.y, rect.width, rect.height);
+        self.refresh(rect.x, rect.y, rect.width as u8, rect.height as u8, &pixels)
+    }
+
+    /// Sends only the parts of `current` that differ from `previous`.
+    ///
+    /// Falls back to a full redraw when the changed regions would take at
+    /// least as many packets as a redraw, or when the framebuffers cannot be
+    /// compared. Returns the number of packets written.
+    pub fn update(&self, previous: &Framebuffer, current: &Framebuffer) -> Result<usize> {
+        match diff_framebuffers(previous, current) {
+            Some(rects) if rects.len() < CHUNK_COUNT => {
+                for rect in &rects {
+                    self.refresh_rect(current, rect)?;
+                }
+                debug!("Partial update completed ({} regions)", rects.len());
+                Ok(rects.len())
+            }
+            _ => {
+                self.redraw(current)?;
+                Ok(CHUNK_COUNT)
+            }
+        }
+    }
+
     /// Clears the display to a solid color.
     pub fn clear(&self, color: u16) -> Result<()> {
         let mut fb = Framebuffer::new();
@@ -215,6 +271,7 @@ impl LcdDevice {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lcd::{LcdEvent, RecordingTransport};
 
     // Hardware tests are skipped by default
     #[test]
@@ -223,4 +280,84 @@ mod tests {
         let device = LcdDevice::open();
         assert!(device.is_ok());
     }
+
+    #[test]
+    fn test_redraw_rebuilds_screen() {
+        let recording = RecordingTransport::new();
+        let device = LcdDevice::with_transport(recording.clone());
+
+        let mut fb = Framebuffer::new();
+        fb.fill_rect(0, 0, 320, 85, 0xF800);
+        fb.set_pixel(319, 169, 0x07E0);
+        device.redraw(&fb).unwrap();
+
+        assert_eq!(recording.events(), vec![LcdEvent::Redraw]);
+        assert_eq!(recording.packet_count(), CHUNK_COUNT);
+        assert_eq!(recording.screen().data(), fb.data());
+    }
+
+    #[test]
+    fn test_update_sends_changed_regions() {
+        let recording = RecordingTransport::new();
+        let device = LcdDevice::with_transport(recording.clone());
+
+        let previous = Framebuffer::new();
+        device.redraw(&previous).unwrap();
+        recording.take_events();
+
+        let mut current = previous.clone();
+        current.fill_rect(40, 40, 10, 10, 0xFFFF);
+        let packets = device.update(&previous, &current).unwrap();
+
+        assert_eq!(packets, 1);
+        assert_eq!(
+            recording.events(),
+            vec![LcdEvent::Refresh {
+                x: 40,
+                y: 40,
+                width: 10,
+                height: 10
+            }]
+        );
+        assert_eq!(recording.screen().data(), current.data());
+    }
+
+    #[test]
+    fn test_upside_down_refresh_matches_redraw() {
+        let recording = RecordingTransport::new();
+        let device = LcdDevice::with_transport(recording.clone());
+        device
+            .set_orientation(Orientation::LandscapeUpsideDown)
+            .unwrap();
+
+        let previous = Framebuffer::new();
+        device.redraw(&previous).unwrap();
+        let mut current = previous.clone();
+        current.fill_rect(5, 10, 20, 3, 0x001F);
+        device.update(&previous, &current).unwrap();
+
+        let mut expected = current.clone();
+        expected.rotate_180();
+        assert_eq!(recording.screen().data(), expected.data());
+    }
+
+    #[test]
+    fn test_config_packets_are_recorded() {
+        let recording = RecordingTransport::new();
+        let device = LcdDevice::with_transport(recording.clone());
+        device.set_orientation(Orientation::Portrait).unwrap();
+        device.heartbeat_with_time(12, 34, 56).unwrap();
+
+        assert_eq!(
+            recording.events(),
+            vec![
+                LcdEvent::Orientation { portrait: true },
+                LcdEvent::Heartbeat {
+                    hours: 12,
+                    minutes: 34,
+                    seconds: 56
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,204 @@
+//! Dirty rectangle detection for partial LCD refreshes.
+//!
+//! The framebuffer is split into tiles. Each tile that changed contributes
+//! the bounding box of its changed pixels, and neighbouring boxes are merged
+//! as long as the result still fits into a single refresh packet.
+
+use super::framebuffer::Framebuffer;
+use super::protocol::DATA_SIZE;
+
+/// Maximum number of pixels a single refresh packet can carry.
+pub const MAX_REFRESH_PIXELS: usize = DATA_SIZE / 2;
+
+/// Maximum width or height of a refresh region (encoded as a single byte).
+const MAX_REFRESH_SIDE: u16 = u8::MAX as u16;
+
+/// Tile width used when scanning for changes.
+const TILE_WIDTH: u16 = 32;
+
+/// Tile height used when scanning for changes.
+const TILE_HEIGHT: u16 = 16;
+
+/// A rectangular region of the framebuffer that needs to be refreshed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl DirtyRect {
+    /// Returns the number of pixels covered by this rectangle.
+    pub fn area(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &DirtyRect) -> DirtyRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        DirtyRect {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+
+    /// Returns true if this rectangle fits into a single refresh packet.
+    pub fn fits_refresh(&self) -> bool {
+        self.area() <= MAX_REFRESH_PIXELS
+            && self.width <= MAX_REFRESH_SIDE
+            && self.height <= MAX_REFRESH_SIDE
+    }
+}
+
+/// Computes the regions that differ between two framebuffers.
+///
+/// Every returned rectangle fits into a single refresh packet. Returns `None`
+/// if the framebuffers have different dimensions and cannot be compared.
+pub fn diff_framebuffers(previous: &Framebuffer, current: &Framebuffer) -> Option<Vec<DirtyRect>> {
+    if previous.width() != current.width() || previous.height() != current.height() {
+        return None;
+    }
+
+    let width = current.width();
+    let height = current.height();
+    let old = previous.data();
+    let new = current.data();
+
+    let mut rects: Vec<DirtyRect> = Vec::new();
+
+    for tile_y in (0..height).step_by(TILE_HEIGHT as usize) {
+        for tile_x in (0..width).step_by(TILE_WIDTH as usize) {
+            let tile_right = (tile_x + TILE_WIDTH).min(width);
+            let tile_bottom = (tile_y + TILE_HEIGHT).min(height);
+
+            let Some(bbox) =
+                changed_bounds(old, new, width, tile_x, tile_y, tile_right, tile_bottom)
+            else {
+                continue;
+            };
+
+            // Merge into an existing rectangle if the result still fits a packet
+            match rects.iter_mut().find(|r| r.union(&bbox).fits_refresh()) {
+                Some(existing) => *existing = existing.union(&bbox),
+                None => rects.push(bbox),
+            }
+        }
+    }
+
+    Some(rects)
+}
+
+/// Returns the bounding box of changed pixels within a tile.
+fn changed_bounds(
+    old: &[u16],
+    new: &[u16],
+    stride: u16,
+    left: u16,
+    top: u16,
+    right: u16,
+    bottom: u16,
+) -> Option<DirtyRect> {
+    let mut min_x = u16::MAX;
+    let mut min_y = u16::MAX;
+    let mut max_x = 0;
+    let mut max_y = 0;
+
+    for y in top..bottom {
+        let row = y as usize * stride as usize;
+        for x in left..right {
+            let idx = row + x as usize;
+            if old[idx] != new[idx] {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+
+    if min_x == u16::MAX {
+        return None;
+    }
+
+    Some(DirtyRect {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x + 1,
+        height: max_y - min_y + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_framebuffers() {
+        let a = Framebuffer::new();
+        let b = Framebuffer::new();
+        assert_eq!(diff_framebuffers(&a, &b), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_single_pixel_change() {
+        let a = Framebuffer::new();
+        let mut b = Framebuffer::new();
+        b.set_pixel(100, 50, 0xFFFF);
+
+        let rects = diff_framebuffers(&a, &b).unwrap();
+        assert_eq!(
+            rects,
+            vec![DirtyRect {
+                x: 100,
+                y: 50,
+                width: 1,
+                height: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_changes_are_merged() {
+        let a = Framebuffer::new();
+        let mut b = Framebuffer::new();
+        // Spans two tiles horizontally
+        b.fill_rect(20, 4, 30, 8, 0xF800);
+
+        let rects = diff_framebuffers(&a, &b).unwrap();
+        assert_eq!(
+            rects,
+            vec![DirtyRect {
+                x: 20,
+                y: 4,
+                width: 30,
+                height: 8
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rects_fit_refresh_and_cover_changes() {
+        let a = Framebuffer::new();
+        let mut b = Framebuffer::new();
+        b.fill_rect(0, 0, 320, 170, 0x07E0);
+
+        let rects = diff_framebuffers(&a, &b).unwrap();
+        assert!(rects.iter().all(|r| r.fits_refresh()));
+
+        let covered: usize = rects.iter().map(|r| r.area()).sum();
+        assert!(covered >= 320 * 170);
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let a = Framebuffer::new();
+        let b = Framebuffer::with_dimensions(170, 320);
+        assert_eq!(diff_framebuffers(&a, &b), None);
+    }
+}
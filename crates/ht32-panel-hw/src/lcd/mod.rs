@@ -1,12 +1,23 @@
 //! LCD display module.
 //!
 //! Provides control over the 320x170 RGB565 LCD display via USB HID.
+//! Packets go through an [`LcdTransport`], so the device can also be driven
+//! by an in-memory [`RecordingTransport`] or a [`StreamTransport`] writing to
+//! a file or socket when no panel is attached.
 
 mod device;
+mod dirty;
 mod protocol;
+mod recording;
+mod stream;
+mod transport;
 
 pub mod framebuffer;
 
 pub use device::LcdDevice;
+pub use dirty::{diff_framebuffers, DirtyRect, MAX_REFRESH_PIXELS};
 pub use framebuffer::{parse_hex_color, rgb565_to_rgb888, rgb888_to_rgb565, Framebuffer};
-pub use protocol::{Command, SubCommand};
+pub use protocol::{decode_packet, Command, Packet, SubCommand, BUFFER_SIZE, CHUNK_COUNT};
+pub use recording::{LcdEvent, RecordingTransport};
+pub use stream::StreamTransport;
+pub use transport::{HidTransport, LcdTransport};
@@ -5,6 +5,8 @@
 //! - Signature byte: 0x55
 //! - Command bytes: 0xA1 (config), 0xA2 (refresh), 0xA3 (redraw)
 
+use super::framebuffer::Framebuffer;
+
 /// Total buffer size including report byte.
 pub const BUFFER_SIZE: usize = 4105; // 1 report + 8 header + 4096 data
 
@@ -170,6 +172,135 @@ pub fn build_redraw_chunk(
     buffer
 }
 
+/// A decoded LCD packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    /// Orientation command.
+    Orientation { portrait: bool },
+    /// Heartbeat with time.
+    Heartbeat { hours: u8, minutes: u8, seconds: u8 },
+    /// Partial refresh of a rectangular region.
+    Refresh {
+        x: u16,
+        y: u16,
+        width: u8,
+        height: u8,
+        pixels: Vec<u16>,
+    },
+    /// One chunk of a full screen redraw.
+    RedrawChunk {
+        /// Zero-based chunk index.
+        index: usize,
+        /// Offset of the first pixel in the image.
+        offset: usize,
+        pixels: Vec<u16>,
+    },
+}
+
+impl Packet {
+    /// Applies the pixel data of this packet to a screen image.
+    /// Configuration packets leave the screen unchanged.
+    pub fn apply(&self, screen: &mut Framebuffer) {
+        match self {
+            Packet::Refresh {
+                x,
+                y,
+                width,
+                height,
+                pixels,
+            } => {
+                let mut pixels = pixels.iter();
+                for dy in 0..*height as u16 {
+                    for dx in 0..*width as u16 {
+                        if let Some(&pixel) = pixels.next() {
+                            screen.set_pixel(x + dx, y + dy, pixel);
+                        }
+                    }
+                }
+            }
+            Packet::RedrawChunk { offset, pixels, .. } => {
+                let data = screen.data_mut();
+                let end = (offset + pixels.len()).min(data.len());
+                if *offset < end {
+                    data[*offset..end].copy_from_slice(&pixels[..end - offset]);
+                }
+            }
+            Packet::Orientation { .. } | Packet::Heartbeat { .. } => {}
+        }
+    }
+
+    /// Returns true if this packet carries pixel data.
+    pub fn has_pixels(&self) -> bool {
+        matches!(self, Packet::Refresh { .. } | Packet::RedrawChunk { .. })
+    }
+
+    /// Returns true if this is the final chunk of a full redraw.
+    pub fn ends_redraw(&self) -> bool {
+        matches!(self, Packet::RedrawChunk { index, .. } if *index == CHUNK_COUNT - 1)
+    }
+}
+
+/// Reads big-endian RGB565 pixels from the packet payload.
+fn read_pixels(buffer: &[u8], count: usize) -> Vec<u16> {
+    let data = &buffer[REPORT_SIZE + HEADER_SIZE..];
+    data.chunks_exact(2)
+        .take(count)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .collect()
+}
+
+/// Decodes a packet produced by one of the `build_*` functions.
+///
+/// Returns `None` if the buffer is not a recognised LCD packet.
+pub fn decode_packet(buffer: &[u8]) -> Option<Packet> {
+    if buffer.len() != BUFFER_SIZE || buffer[1] != LCD_SIGNATURE {
+        return None;
+    }
+
+    match buffer[2] {
+        c if c == Command::Config as u8 => match buffer[3] {
+            s if s == SubCommand::Orientation as u8 => Some(Packet::Orientation {
+                portrait: buffer[4] == 0x02,
+            }),
+            s if s == SubCommand::SetTime as u8 => Some(Packet::Heartbeat {
+                hours: buffer[4],
+                minutes: buffer[5],
+                seconds: buffer[6],
+            }),
+            _ => None,
+        },
+        c if c == Command::Refresh as u8 => {
+            let width = buffer[7];
+            let height = buffer[8];
+            let count = (width as usize * height as usize).min(DATA_SIZE / 2);
+            Some(Packet::Refresh {
+                x: u16::from_le_bytes([buffer[3], buffer[4]]),
+                y: u16::from_le_bytes([buffer[5], buffer[6]]),
+                width,
+                height,
+                pixels: read_pixels(buffer, count),
+            })
+        }
+        c if c == Command::Redraw as u8 => {
+            // The offset field is truncated to 16 bits on the wire, so the
+            // sequence number is used to locate the chunk instead.
+            let index = (buffer[4] as usize).checked_sub(1)?;
+            if index >= CHUNK_COUNT {
+                return None;
+            }
+            // Only the high byte of the chunk size survives; the low byte
+            // shares its position with the first payload byte.
+            let size = ((buffer[8] as usize) << 8).min(DATA_SIZE);
+            Some(Packet::RedrawChunk {
+                index,
+                offset: index * (DATA_SIZE / 2),
+                pixels: read_pixels(buffer, size / 2),
+            })
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,4 +327,55 @@ mod tests {
         assert_eq!(packet[5], 30);
         assert_eq!(packet[6], 45);
     }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        assert_eq!(
+            decode_packet(&build_orientation_packet(true)),
+            Some(Packet::Orientation { portrait: true })
+        );
+        assert_eq!(
+            decode_packet(&build_heartbeat_packet(1, 2, 3)),
+            Some(Packet::Heartbeat {
+                hours: 1,
+                minutes: 2,
+                seconds: 3
+            })
+        );
+
+        let pixels = [0x1234, 0xABCD, 0xFFFF, 0x0001];
+        assert_eq!(
+            decode_packet(&build_refresh_packet(300, 2, 2, 2, &pixels)),
+            Some(Packet::Refresh {
+                x: 300,
+                y: 2,
+                width: 2,
+                height: 2,
+                pixels: pixels.to_vec()
+            })
+        );
+
+        let image: Vec<u16> = (0..(320 * 170)).map(|i| i as u16).collect();
+        let last = CHUNK_COUNT - 1;
+        let offset = last * (DATA_SIZE / 2);
+        match decode_packet(&build_redraw_chunk(last, &image, offset)) {
+            Some(Packet::RedrawChunk {
+                index,
+                offset: decoded_offset,
+                pixels,
+            }) => {
+                assert_eq!(index, last);
+                assert_eq!(decoded_offset, offset);
+                assert_eq!(pixels.len(), FINAL_CHUNK_SIZE / 2);
+                assert_eq!(pixels[..], image[offset..]);
+            }
+            other => panic!("unexpected packet: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert_eq!(decode_packet(&[0u8; 16]), None);
+        assert_eq!(decode_packet(&[0u8; BUFFER_SIZE]), None);
+    }
 }
@@ -0,0 +1,162 @@
+//! In-memory LCD transport for testing without hardware.
+//!
+//! Decodes every written packet back into an [`LcdEvent`] and applies it to
+//! a framebuffer, so the resulting screen image can be inspected.
+
+use std::sync::{Arc, Mutex};
+
+use super::framebuffer::Framebuffer;
+use super::protocol::{decode_packet, Packet};
+use super::transport::LcdTransport;
+use crate::{Error, Result};
+
+/// A decoded operation observed by the recording transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LcdEvent {
+    /// Hardware orientation was set.
+    Orientation { portrait: bool },
+    /// Heartbeat was sent.
+    Heartbeat { hours: u8, minutes: u8, seconds: u8 },
+    /// A region was refreshed.
+    Refresh {
+        x: u16,
+        y: u16,
+        width: u8,
+        height: u8,
+    },
+    /// A full redraw completed (all chunks received).
+    Redraw,
+    /// A packet that could not be decoded.
+    Unknown,
+}
+
+#[derive(Default)]
+struct Recording {
+    events: Vec<LcdEvent>,
+    screen: Framebuffer,
+    packets: usize,
+    disconnected: bool,
+}
+
+/// LCD transport that records packets in memory.
+///
+/// Clones share the same recording, so a test can keep one handle while the
+/// other is moved into an [`LcdDevice`](super::LcdDevice).
+#[derive(Clone, Default)]
+pub struct RecordingTransport {
+    inner: Arc<Mutex<Recording>>,
+}
+
+impl RecordingTransport {
+    /// Creates a new recording transport with a black screen.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all events recorded so far.
+    pub fn events(&self) -> Vec<LcdEvent> {
+        self.inner.lock().unwrap().events.clone()
+    }
+
+    /// Returns and clears the recorded events.
+    pub fn take_events(&self) -> Vec<LcdEvent> {
+        std::mem::take(&mut self.inner.lock().unwrap().events)
+    }
+
+    /// Returns the screen image as rebuilt from the written packets.
+    pub fn screen(&self) -> Framebuffer {
+        self.inner.lock().unwrap().screen.clone()
+    }
+
+    /// Returns the total number of packets written.
+    pub fn packet_count(&self) -> usize {
+        self.inner.lock().unwrap().packets
+    }
+
+    /// Simulates unplugging or replugging the device.
+    /// While disconnected, writes fail with [`Error::LcdDisconnected`].
+    pub fn set_disconnected(&self, disconnected: bool) {
+        self.inner.lock().unwrap().disconnected = disconnected;
+    }
+}
+
+impl LcdTransport for RecordingTransport {
+    fn write(&mut self, packet: &[u8]) -> Result<()> {
+        let mut recording = self.inner.lock().unwrap();
+        if recording.disconnected {
+            return Err(Error::LcdDisconnected);
+        }
+        recording.packets += 1;
+
+        let Some(packet) = decode_packet(packet) else {
+            recording.events.push(LcdEvent::Unknown);
+            return Ok(());
+        };
+        packet.apply(&mut recording.screen);
+
+        let event = match packet {
+            Packet::Orientation { portrait } => LcdEvent::Orientation { portrait },
+            Packet::Heartbeat {
+                hours,
+                minutes,
+                seconds,
+            } => LcdEvent::Heartbeat {
+                hours,
+                minutes,
+                seconds,
+            },
+            Packet::Refresh {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => LcdEvent::Refresh {
+                x,
+                y,
+                width,
+                height,
+            },
+            // Intermediate chunks only update the screen image
+            Packet::RedrawChunk { .. } if !packet.ends_redraw() => return Ok(()),
+            Packet::RedrawChunk { .. } => LcdEvent::Redraw,
+        };
+
+        recording.events.push(event);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::protocol::build_refresh_packet;
+
+    #[test]
+    fn test_refresh_updates_screen() {
+        let mut transport = RecordingTransport::new();
+        let packet = build_refresh_packet(10, 20, 2, 1, &[0xF800, 0x07E0]);
+        transport.write(&packet).unwrap();
+
+        let screen = transport.screen();
+        assert_eq!(screen.get_pixel(10, 20), Some(0xF800));
+        assert_eq!(screen.get_pixel(11, 20), Some(0x07E0));
+        assert_eq!(
+            transport.events(),
+            vec![LcdEvent::Refresh {
+                x: 10,
+                y: 20,
+                width: 2,
+                height: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_disconnected_write_fails() {
+        let mut transport = RecordingTransport::new();
+        transport.set_disconnected(true);
+        assert!(transport.write(&[0u8; 4]).is_err());
+        assert_eq!(transport.packet_count(), 0);
+    }
+}
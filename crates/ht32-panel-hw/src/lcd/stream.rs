@@ -0,0 +1,180 @@
+//! LCD transport that writes packets to a file, FIFO or Unix socket.
+//!
+//! Every packet is written verbatim as the HID report the panel would
+//! receive, so the stream can be decoded with [`decode_packet`] to rebuild
+//! the screen image without hardware.
+//!
+//! [`decode_packet`]: super::decode_packet
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use tracing::debug;
+
+use super::transport::LcdTransport;
+use crate::{Error, Result};
+
+/// Transport backed by a file, FIFO or Unix socket.
+///
+/// The target is chosen by what exists at the path:
+/// - a Unix socket is connected to (the reader must be listening),
+/// - a FIFO is opened for writing (fails if nobody is reading),
+/// - anything else is created or truncated as a regular file.
+///
+/// If a write fails, for example because the reader went away, the target
+/// is reopened on the next write.
+pub struct StreamTransport {
+    path: PathBuf,
+    writer: Option<Box<dyn Write + Send>>,
+}
+
+impl StreamTransport {
+    /// Opens the stream at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let writer = connect(&path)?;
+        Ok(Self {
+            path,
+            writer: Some(writer),
+        })
+    }
+
+    /// Returns the path this transport writes to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn io_error(&self, source: std::io::Error) -> Error {
+        Error::VirtualLcd {
+            path: self.path.display().to_string(),
+            source,
+        }
+    }
+}
+
+impl LcdTransport for StreamTransport {
+    fn write(&mut self, packet: &[u8]) -> Result<()> {
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => {
+                let writer = connect(&self.path)?;
+                debug!("Reopened virtual LCD at {}", self.path.display());
+                self.writer.insert(writer)
+            }
+        };
+
+        if let Err(e) = writer.write_all(packet).and_then(|_| writer.flush()) {
+            self.writer = None;
+            return Err(self.io_error(e));
+        }
+        Ok(())
+    }
+}
+
+/// Opens the writer for whatever exists at the path.
+fn connect(path: &Path) -> Result<Box<dyn Write + Send>> {
+    let file_type = std::fs::metadata(path).ok().map(|m| m.file_type());
+    let result: std::io::Result<Box<dyn Write + Send>> = match file_type {
+        Some(t) if t.is_socket() => UnixStream::connect(path).map(|s| Box::new(s) as _),
+        Some(t) if t.is_fifo() => open_fifo(path).map(|f| Box::new(f) as _),
+        _ => File::create(path).map(|f| Box::new(f) as _),
+    };
+
+    result.map_err(|source| Error::VirtualLcd {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Opens a FIFO for writing without blocking when there is no reader.
+///
+/// The FIFO is opened non-blocking so that a missing reader is reported as
+/// an error instead of hanging, then switched back to blocking writes so
+/// packets are never split.
+fn open_fifo(path: &Path) -> std::io::Result<File> {
+    let file = OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)?;
+
+    let fd = file.as_raw_fd();
+    // SAFETY: fd is a valid descriptor owned by `file` for the duration of these calls.
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::protocol::{build_heartbeat_packet, decode_packet, Packet, BUFFER_SIZE};
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ht32-stream-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_file_receives_packets() {
+        let path = temp_path("file");
+        let mut transport = StreamTransport::open(&path).unwrap();
+        transport.write(&build_heartbeat_packet(1, 2, 3)).unwrap();
+        transport.write(&build_heartbeat_packet(4, 5, 6)).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(data.len(), 2 * BUFFER_SIZE);
+        assert_eq!(
+            decode_packet(&data[BUFFER_SIZE..]),
+            Some(Packet::Heartbeat {
+                hours: 4,
+                minutes: 5,
+                seconds: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_socket_receives_packets() {
+        let path = temp_path("sock");
+        std::fs::remove_file(&path).ok();
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let mut transport = StreamTransport::open(&path).unwrap();
+        transport.write(&build_heartbeat_packet(7, 8, 9)).unwrap();
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut packet = vec![0u8; BUFFER_SIZE];
+        stream.read_exact(&mut packet).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(matches!(
+            decode_packet(&packet),
+            Some(Packet::Heartbeat { hours: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_fifo_without_reader_fails() {
+        let path = temp_path("fifo");
+        std::fs::remove_file(&path).ok();
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        let result = StreamTransport::open(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(matches!(result, Err(Error::VirtualLcd { .. })));
+    }
+}
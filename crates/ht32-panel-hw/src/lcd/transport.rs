@@ -0,0 +1,32 @@
+//! Transports that carry LCD packets to the panel.
+
+use crate::Result;
+use hidapi::HidDevice;
+
+/// A sink for raw LCD packets.
+///
+/// Each call to [`LcdTransport::write`] carries one complete HID report,
+/// including the leading report byte.
+pub trait LcdTransport: Send {
+    /// Writes a single packet.
+    fn write(&mut self, packet: &[u8]) -> Result<()>;
+}
+
+/// Transport backed by a USB HID device.
+pub struct HidTransport {
+    device: HidDevice,
+}
+
+impl HidTransport {
+    /// Wraps an opened HID device.
+    pub fn new(device: HidDevice) -> Self {
+        Self { device }
+    }
+}
+
+impl LcdTransport for HidTransport {
+    fn write(&mut self, packet: &[u8]) -> Result<()> {
+        self.device.write(packet)?;
+        Ok(())
+    }
+}
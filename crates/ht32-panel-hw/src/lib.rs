@@ -9,7 +9,7 @@ pub mod led;
 pub mod orientation;
 
 pub use error::{Error, Result};
-pub use lcd::{Framebuffer, LcdDevice};
+pub use lcd::{Framebuffer, LcdDevice, LcdTransport, RecordingTransport};
 pub use led::{LedDevice, LedTheme};
 pub use orientation::Orientation;
 
@@ -20,19 +20,39 @@ let
   cfg = config.services.ht32-panel;
   settingsFormat = pkgs.formats.toml { };
 
+  facesDir = pkgs.linkFarm "ht32-panel-faces" (lib.mapAttrsToList (name: face: {
+    name = "${name}.toml";
+    path = settingsFormat.generate "${name}.toml" face;
+  }) cfg.faces);
+
   configFile = settingsFormat.generate "config.toml" ({
     web = {
       enable = cfg.web.enable;
       listen = cfg.web.listen;
-    };
+    }
+    // lib.optionalAttrs (cfg.web.socket_mode != null) { socket_mode = cfg.web.socket_mode; }
+    // lib.optionalAttrs (cfg.web.socket_group != null) { socket_group = cfg.web.socket_group; }
+    // lib.optionalAttrs (cfg.web.users != [ ]) { users = cfg.web.users; }
+    // lib.optionalAttrs (cfg.web.tokens != [ ]) { tokens = cfg.web.tokens; }
+    // lib.optionalAttrs (cfg.web.tls_cert != null) { tls_cert = cfg.web.tls_cert; }
+    // lib.optionalAttrs (cfg.web.tls_key != null) { tls_key = cfg.web.tls_key; };
     dbus.bus = cfg.dbus.bus;
     devices = {
       lcd = cfg.devices.lcd;
       led = cfg.devices.led;
-    };
+    } // lib.optionalAttrs (cfg.devices.panels != [ ]) { panels = cfg.devices.panels; };
   }
   // lib.optionalAttrs (cfg.refresh != null) { refresh_interval = cfg.refresh; }
   // lib.optionalAttrs (cfg.heartbeat != null) { heartbeat = cfg.heartbeat; }
+  // lib.optionalAttrs (cfg.faces != { }) { faces_dir = facesDir; }
+  // lib.optionalAttrs (cfg.themes != [ ]) { themes = cfg.themes; }
+  // lib.optionalAttrs (cfg.alerts != { }) { alerts = cfg.alerts; }
+  // lib.optionalAttrs (cfg.schedule != [ ]) { schedule = cfg.schedule; }
+  // lib.optionalAttrs (cfg.logind != { }) { logind = cfg.logind; }
+  // lib.optionalAttrs (cfg.custom_values != [ ]) { custom_values = cfg.custom_values; }
+  // lib.optionalAttrs (cfg.desktop_notifications != { }) {
+    desktop_notifications = cfg.desktop_notifications;
+  }
   // cfg.extraSettings);
 in
 {
@@ -60,7 +80,63 @@ in
       listen = lib.mkOption {
         type = lib.types.str;
         default = "[::1]:8686";
-        description = "Address and port for the web interface.";
+        description = ''
+          Address and port for the web interface, or `unix:<path>` for a Unix
+          socket.
+        '';
+      };
+
+      socket_mode = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        example = "0660";
+        description = "Octal permissions of the Unix socket.";
+      };
+
+      socket_group = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Group owning the Unix socket.";
+      };
+
+      users = lib.mkOption {
+        type = lib.types.listOf settingsFormat.type;
+        default = [ ];
+        example = [
+          {
+            name = "admin";
+            password_hash = "$argon2id$v=19$m=19456,t=2,p=1$...";
+            role = "admin";
+          }
+        ];
+        description = ''
+          Users who log in with HTTP basic auth, each with `name`, an Argon2
+          `password_hash` in PHC format and a `role` (`admin` or `read-only`).
+          Setting users or tokens makes the web interface require
+          authentication. The hashes end up in the world-readable Nix store.
+        '';
+      };
+
+      tokens = lib.mkOption {
+        type = lib.types.listOf settingsFormat.type;
+        default = [ ];
+        description = ''
+          Bearer tokens for scripts, each with a `name`, a `token_hash` (the
+          SHA-256 hash of a random token in hex, e.g. from `sha256sum`) and a
+          `role` (`admin` or `read-only`).
+        '';
+      };
+
+      tls_cert = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "PEM certificate chain; serves HTTPS together with `tls_key`.";
+      };
+
+      tls_key = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "PEM private key of `tls_cert`.";
       };
     };
 
@@ -93,7 +169,7 @@ in
       lcd = lib.mkOption {
         type = lib.types.str;
         default = "auto";
-        description = "LCD device path or 'auto' for auto-detection.";
+        description = "LCD device path, 'auto' for auto-detection, or 'virtual:<path>' to write packets to a file, FIFO or Unix socket.";
       };
 
       led = lib.mkOption {
@@ -105,6 +181,20 @@ in
           Use `ht32panelctl led set <theme>` to change them.
         '';
       };
+
+      panels = lib.mkOption {
+        type = lib.types.listOf (lib.types.attrsOf lib.types.str);
+        default = [ ];
+        example = [
+          { id = "left"; serial = "0001"; led = "/dev/ttyUSB0"; }
+          { id = "right"; serial = "0002"; led = "/dev/ttyUSB1"; face = "clock"; }
+        ];
+        description = ''
+          Named panels to drive from one daemon. Each entry takes `id` and
+          optionally `lcd`, `serial`, `led`, `face`, `theme` and `orientation`.
+          When empty, a single panel is built from `lcd` and `led`.
+        '';
+      };
     };
 
     applet = {
@@ -127,6 +217,101 @@ in
       };
     };
 
+    themes = lib.mkOption {
+      type = lib.types.listOf (lib.types.attrsOf lib.types.str);
+      default = [ ];
+      example = [
+        { id = "midnight"; primary = "#7AA2F7"; secondary = "#9ECE6A"; text = "#C0CAF5"; background = "#16161E"; }
+      ];
+      description = ''
+        User color themes. Each entry takes `id`, `primary`, `secondary`,
+        `text` and `background`, and optionally `display_name`, `accent`,
+        `warning` and `critical` (all colors as `#RRGGBB`).
+      '';
+    };
+
+    schedule = lib.mkOption {
+      type = lib.types.listOf settingsFormat.type;
+      default = [ ];
+      example = [
+        { name = "night"; start = "22:00"; end = "07:00"; theme = "solarized-dark"; led_theme = "off"; }
+        { name = "work"; start = "09:00"; days = [ "weekdays" ]; face = "professional"; }
+      ];
+      description = ''
+        Time-of-day schedule rules. Each entry takes `name` and `start`
+        (`"HH:MM"`), and optionally `end`, `days`, `panels`, `face`, `theme`,
+        `brightness`, `display_off`, `led_theme`, `led_intensity` and
+        `led_speed`.
+      '';
+    };
+
+    logind = lib.mkOption {
+      type = settingsFormat.type;
+      default = { };
+      example = { enable = true; blank_on_idle = false; };
+      description = ''
+        Blanking through logind: `enable` (defaults to false),
+        `blank_on_lock` and `blank_on_idle` (both default to true) and
+        `session`. Once enabled, panels always blank while the machine
+        suspends.
+      '';
+    };
+
+    custom_values = lib.mkOption {
+      type = lib.types.listOf settingsFormat.type;
+      default = [ ];
+      example = [
+        {
+          key = "fan";
+          command = "cat /sys/class/hwmon/hwmon2/fan1_input";
+          interval = 10;
+          unit = "RPM";
+        }
+      ];
+      description = ''
+        Commands whose output sets custom values, each with `key`,
+        `command`, and optionally `interval` (seconds) and `unit`. Custom
+        faces show them by binding a widget to `custom:<key>`.
+      '';
+    };
+
+    desktop_notifications = lib.mkOption {
+      type = settingsFormat.type;
+      default = { };
+      example = {
+        enable = true;
+        rules = [ { app = "Spotify"; show = false; } ];
+      };
+      description = ''
+        Mirroring of desktop notifications: `enable`, `seconds`, `panels`
+        and `rules`. Each rule matches on `app` and `urgency` and sets
+        `show`, `severity` and `seconds`. Needs the session bus.
+      '';
+    };
+
+    alerts = lib.mkOption {
+      type = settingsFormat.type;
+      default = { };
+      example = {
+        flash = true;
+        cpu_temp = { warning = 70; critical = 80; };
+        ram_percent.critical = 90;
+      };
+      description = ''
+        Metric alert thresholds for `cpu_percent`, `cpu_temp` and
+        `ram_percent`, plus optional `flash` and `led_theme` actions.
+      '';
+    };
+
+    faces = lib.mkOption {
+      type = lib.types.attrsOf settingsFormat.type;
+      default = { };
+      description = ''
+        Custom face definitions, keyed by face name. See the configuration
+        documentation for the widget format.
+      '';
+    };
+
     extraSettings = lib.mkOption {
       type = settingsFormat.type;
       default = { };
@@ -186,7 +371,9 @@ in
       };
 
       Service = {
-        Type = "simple";
+        Type = "notify";
+        NotifyAccess = "main";
+        WatchdogSec = 30;
         ExecStart = "${cfg.package}/bin/ht32paneld ${configFile}";
         Restart = "on-failure";
         RestartSec = 5;